@@ -1,26 +1,71 @@
-use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use ratatui::style::{Color, Style};
-use tokio::sync::mpsc;
+use bytes::Bytes;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::style::Style;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{Notify, mpsc};
 use tui_textarea::{Input, TextArea};
 
+use crate::auth::Auth;
+use crate::config::Config;
 use crate::curl::parse_curl;
-use crate::http_client::HttpClient;
+use crate::graphql::{Graphql, INTROSPECTION_QUERY, Schema, operation_names};
+use crate::grpc::{self, Services};
+use crate::http_client::{DEFAULT_MAX_BODY_SIZE, HttpClient, Progress, SendOptions};
+use crate::http_file::{HttpFile, format_request, group_by_source, to_http_file};
+use crate::keymap::Action;
+use crate::mock::{self, Example, Hit, MockServer, Route};
+use crate::openapi::OpenApiSpec;
+use crate::proxy::{self, ProxyServer};
 use crate::request::HttpRequest;
 use crate::response::HttpResponse;
+use crate::script::{self, ScriptReport};
+use crate::secrets;
+use crate::storage::{load_requests, save_request};
 use crate::ui::{MainContentTab, RequestSectionTab, ResponseSectionTab};
+use crate::utils::{compression::StreamDecoder, variables::resolve_references};
 use crate::vim::{Mode, Transition, Vim};
+use crate::websocket::{LogEntry, MessageKind, WebSocketConnection};
+use crate::workspace::{Environment, Workspace};
 
 pub type RequestResult = anyhow::Result<HttpResponse, String>;
 
+/// Sent from the request task: the response as it streams in, then the outcome.
+pub enum ResponseUpdate {
+	Progress(Progress),
+	Done(Box<RequestResult>),
+	/// Outcome of loading a GraphQL schema
+	Schema(anyhow::Result<Schema, String>),
+	/// Outcome of loading gRPC services by server reflection
+	Services(anyhow::Result<Services, String>),
+	/// Set by a post-response script
+	Variables(HashMap<String, String>),
+}
+
+const DEFAULT_HTTP_FILE: &str = "resto.http";
+/// Captured requests kept for their History entries; older ones are dropped so a long capture doesn't fill memory
+const MAX_CAPTURED_REQUESTS: usize = 1000;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AppState {
 	Normal,
 	EditingUrl,
+	EditingName,
+	EditingSavePath,
 	EditingHeaders,
 	EditingBody,
+	EditingGraphqlVariables,
 	EditingQueries,
+	/// The script of the active Pre-request or Post-response tab
+	EditingScript,
+	EditingAuth,
+	EditingExample,
 	InspectingResponseBody,
 	InspectingResponseHeaders,
+	/// The current request with its variables resolved, shown over the request tab
+	PreviewingRequest,
 	Help,
 }
 
@@ -39,6 +84,8 @@ pub enum HttpMethod {
 	Patch,
 	Head,
 	Options,
+	/// Any other method token, e.g. `PURGE`, `PROPFIND` or `QUERY`
+	Custom(String),
 }
 
 impl std::str::FromStr for HttpMethod {
@@ -53,13 +100,19 @@ impl std::str::FromStr for HttpMethod {
 			"DELETE" => Ok(Self::Delete),
 			"HEAD" => Ok(Self::Head),
 			"OPTIONS" => Ok(Self::Options),
+			_ if is_method_token(s) => Ok(Self::Custom(s.to_string())),
 			_ => Err(format!("Unknown HTTP method: {s}")),
 		}
 	}
 }
 
+/// Whether `s` is a valid method token (RFC 9110 `tchar`s only).
+fn is_method_token(s: &str) -> bool {
+	!s.is_empty() && s.chars().all(|ch| ch.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(ch))
+}
+
 impl HttpMethod {
-	pub const fn as_str(&self) -> &str {
+	pub fn as_str(&self) -> &str {
 		match self {
 			Self::Get => "GET",
 			Self::Post => "POST",
@@ -68,21 +121,16 @@ impl HttpMethod {
 			Self::Delete => "DELETE",
 			Self::Head => "HEAD",
 			Self::Options => "OPTIONS",
+			Self::Custom(method) => method,
 		}
 	}
 
-	pub const fn color(&self) -> Color {
-		match self {
-			Self::Get => Color::Green,
-			Self::Post => Color::Blue,
-			Self::Put => Color::Magenta,
-			Self::Patch => Color::LightMagenta,
-			Self::Delete => Color::Red,
-			Self::Head => Color::Cyan,
-			Self::Options => Color::Yellow,
-		}
+	/// Whether a request body is sent by default; see [`HttpRequest::has_body`].
+	pub const fn allows_body(&self) -> bool {
+		matches!(self, Self::Post | Self::Put | Self::Patch | Self::Custom(_))
 	}
 
+	/// Cycles through the common methods; a custom method continues from GET.
 	pub const fn next(&self) -> Self {
 		match self {
 			Self::Get => Self::Post,
@@ -91,13 +139,13 @@ impl HttpMethod {
 			Self::Patch => Self::Delete,
 			Self::Delete => Self::Head,
 			Self::Head => Self::Options,
-			Self::Options => Self::Get,
+			Self::Options | Self::Custom(_) => Self::Get,
 		}
 	}
 
 	pub const fn previous(&self) -> Self {
 		match self {
-			Self::Get => Self::Options,
+			Self::Get | Self::Custom(_) => Self::Options,
 			Self::Post => Self::Get,
 			Self::Put => Self::Post,
 			Self::Patch => Self::Put,
@@ -108,25 +156,124 @@ impl HttpMethod {
 	}
 }
 
+/// Protocol a request is sent with. HTTP/3 isn't supported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum HttpVersion {
+	/// HTTP/2 when the server offers it through ALPN, otherwise HTTP/1.1; plain `http://` always uses HTTP/1.1
+	#[default]
+	#[serde(rename = "negotiate")]
+	Negotiate,
+	#[serde(rename = "HTTP/1.1")]
+	Http1,
+	/// Through ALPN over TLS, or with prior knowledge (h2c) for plain `http://`
+	#[serde(rename = "HTTP/2")]
+	Http2,
+}
+
+impl std::str::FromStr for HttpVersion {
+	type Err = String;
+
+	fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+		match s.to_uppercase().as_str() {
+			"NEGOTIATE" => Ok(Self::Negotiate),
+			"HTTP/1.1" => Ok(Self::Http1),
+			"HTTP/2" | "HTTP/2 (PRIOR KNOWLEDGE)" => Ok(Self::Http2),
+			_ => Err(format!("Unsupported HTTP version: {s}")),
+		}
+	}
+}
+
+impl HttpVersion {
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Negotiate => "negotiate",
+			Self::Http1 => "HTTP/1.1",
+			Self::Http2 => "HTTP/2",
+		}
+	}
+
+	// By reference for `skip_serializing_if`
+	#[allow(clippy::trivially_copy_pass_by_ref)]
+	pub const fn is_negotiate(&self) -> bool {
+		matches!(self, Self::Negotiate)
+	}
+
+	pub const fn next(self) -> Self {
+		match self {
+			Self::Negotiate => Self::Http1,
+			Self::Http1 => Self::Http2,
+			Self::Http2 => Self::Negotiate,
+		}
+	}
+}
+
 pub struct App {
 	pub state: AppState,
 	pub input_mode: InputMode,
 	pub current_request: HttpRequest,
 	pub responses: Vec<HttpResponse>,
 	pub selected_response: Option<usize>,
+	pub collection: Vec<HttpRequest>,
+	pub selected_collection_request: Option<usize>,
+	pub variables: HashMap<String, String>,
+	/// The `@name = value` definitions of each loaded `.http` file, written back when exporting
+	pub http_file_variables: HashMap<PathBuf, HashMap<String, String>>,
+	/// Set by scripts with `set_var`; they override the environment and file variables
+	pub script_variables: HashMap<String, String>,
+	/// From the encrypted secrets file; masked wherever they'd be shown, logged or written out
+	pub secret_variables: HashMap<String, String>,
+	/// Where exports write the requests that weren't loaded from a `.http` file
+	pub http_file_path: PathBuf,
+	/// Files the last key warned would be overwritten; the next key confirms or drops the warning
+	pending_overwrite: Option<Vec<PathBuf>>,
+	pub workspace: Workspace,
+	pub config: Config,
+	pub environments: Vec<Environment>,
+	pub active_environment: Option<usize>,
 
 	pub fullscreen_section: FullscreenSection,
 
 	pub url_textarea: TextArea<'static>,
+	pub name_textarea: TextArea<'static>,
+	pub save_path_textarea: TextArea<'static>,
 	pub headers_textarea: TextArea<'static>,
 	pub body_textarea: TextArea<'static>,
+	pub graphql_variables_textarea: TextArea<'static>,
 	pub queries_textarea: TextArea<'static>,
+	pub script_textarea: TextArea<'static>,
+	pub auth_textarea: TextArea<'static>,
+	pub example_textarea: TextArea<'static>,
 	pub response_body_textarea: TextArea<'static>,
 	pub response_headers_textarea: TextArea<'static>,
 
 	pub http_client: HttpClient,
+	pub send_options: SendOptions,
+	pub openapi: Option<Arc<OpenApiSpec>>,
+	pub websocket: Option<WebSocketConnection>,
+	pub websocket_log: Vec<LogEntry>,
+	pub message_kind: MessageKind,
+	/// From the last introspection, for completing fields in GraphQL queries
+	pub graphql_schema: Option<Schema>,
+	/// From `--proto` files or server reflection, for calling gRPC methods
+	pub grpc_services: Option<Services>,
+	pub mock_server: Option<MockServer>,
+	/// Requests the mock server answered, oldest first
+	pub mock_log: Vec<Hit>,
+	pub proxy: Option<ProxyServer>,
+	/// The latest requests the capture proxy forwarded, oldest first, so their History entries can be opened
+	pub captured_requests: VecDeque<HttpRequest>,
 	pub loading: bool,
+	/// Id of the response whose head arrived and whose body is still coming in
+	pub streaming: Option<String>,
+	/// Body chunks received since the last update, added to the streaming response together
+	pending_chunks: Vec<Bytes>,
+	/// Decompresses the streaming body as it arrives, when its encoding allows
+	stream_decoder: Option<StreamDecoder>,
+	stop_stream: Option<Arc<Notify>>,
 	pub error_message: Option<String>,
+	/// Text of the last request preview
+	pub request_preview: String,
+	pub status_message: Option<String>,
 
 	pub active_tab: MainContentTab,
 	pub request_section_active_tab: RequestSectionTab,
@@ -134,8 +281,8 @@ pub struct App {
 
 	pub vim: Vim,
 
-	response_rx: mpsc::UnboundedReceiver<RequestResult>,
-	response_tx: mpsc::UnboundedSender<RequestResult>,
+	response_rx: mpsc::UnboundedReceiver<ResponseUpdate>,
+	response_tx: mpsc::UnboundedSender<ResponseUpdate>,
 }
 
 pub enum FullscreenSection {
@@ -145,11 +292,19 @@ pub enum FullscreenSection {
 }
 
 impl App {
-	pub fn new() -> Self {
+	pub fn new(workspace: Workspace, config: Config) -> Self {
+		let send_options =
+			SendOptions { max_body_size: config.max_body_size.unwrap_or(DEFAULT_MAX_BODY_SIZE), ..SendOptions::default() };
 		let url_textarea = TextArea::default();
+		let name_textarea = TextArea::default();
+		let save_path_textarea = TextArea::default();
 		let headers_textarea = TextArea::default();
 		let body_textarea = TextArea::default();
+		let graphql_variables_textarea = TextArea::default();
 		let queries_textarea = TextArea::default();
+		let script_textarea = TextArea::default();
+		let auth_textarea = TextArea::default();
+		let example_textarea = TextArea::default();
 		let response_body_textarea = TextArea::default();
 		let response_headers_textarea = TextArea::default();
 
@@ -163,19 +318,54 @@ impl App {
 			current_request: HttpRequest::new(),
 			responses: Vec::new(),
 			selected_response: None,
+			collection: Vec::new(),
+			selected_collection_request: None,
+			variables: HashMap::new(),
+			http_file_variables: HashMap::new(),
+			script_variables: HashMap::new(),
+			secret_variables: HashMap::new(),
+			http_file_path: workspace.root.join(DEFAULT_HTTP_FILE),
+			pending_overwrite: None,
+			workspace,
+			config,
+			environments: Vec::new(),
+			active_environment: None,
 
 			fullscreen_section: FullscreenSection::None,
 
 			url_textarea,
+			name_textarea,
+			save_path_textarea,
 			headers_textarea,
 			body_textarea,
+			graphql_variables_textarea,
 			queries_textarea,
+			script_textarea,
+			auth_textarea,
+			example_textarea,
 			response_body_textarea,
 			response_headers_textarea,
 
 			http_client: HttpClient::new(),
+			send_options,
+			openapi: None,
+			websocket: None,
+			websocket_log: Vec::new(),
+			message_kind: MessageKind::default(),
+			graphql_schema: None,
+			grpc_services: None,
+			mock_server: None,
+			mock_log: Vec::new(),
+			proxy: None,
+			captured_requests: VecDeque::new(),
 			loading: false,
+			streaming: None,
+			pending_chunks: Vec::new(),
+			stream_decoder: None,
+			stop_stream: None,
 			error_message: None,
+			request_preview: String::new(),
+			status_message: None,
 			active_tab: MainContentTab::Request,
 			request_section_active_tab: RequestSectionTab::Headers,
 			response_section_active_tab: ResponseSectionTab::Body,
@@ -185,6 +375,256 @@ impl App {
 		}
 	}
 
+	pub fn set_openapi_spec(&mut self, spec: OpenApiSpec) {
+		self.openapi = Some(Arc::new(spec));
+	}
+
+	/// Loads requests and `@variable` definitions from a `.http` / `.rest` file into the collection.
+	pub fn load_http_file(&mut self, path: &Path) -> anyhow::Result<()> {
+		let http_file = HttpFile::load(path)?;
+
+		self.variables.extend(http_file.variables.clone());
+		self.http_file_variables.insert(path.to_path_buf(), http_file.variables);
+		self.collection.extend(http_file.requests);
+
+		if self.selected_collection_request.is_none() && !self.collection.is_empty() {
+			self.selected_collection_request = Some(0);
+		}
+
+		Ok(())
+	}
+
+	/// Loads the workspace's saved requests into the collection, and its environments.
+	pub fn load_workspace(&mut self) -> anyhow::Result<()> {
+		self.collection.extend(load_requests(&self.workspace.requests_dir())?);
+
+		if self.selected_collection_request.is_none() && !self.collection.is_empty() {
+			self.selected_collection_request = Some(0);
+		}
+
+		self.environments = self.workspace.load_environments()?;
+		self.active_environment = self.workspace.default_environment().and_then(|default_environment| {
+			self.environments.iter().position(|environment| environment.name == default_environment)
+		});
+
+		Ok(())
+	}
+
+	pub fn get_active_environment(&self) -> Option<&Environment> {
+		self.active_environment.and_then(|index| self.environments.get(index))
+	}
+
+	fn next_environment(&mut self) {
+		if self.environments.is_empty() {
+			self.status_message = Some(format!("No environments in {}", self.workspace.environments_dir().display()));
+			return;
+		}
+
+		self.active_environment = match self.active_environment {
+			None => Some(0),
+			Some(index) if index + 1 < self.environments.len() => Some(index + 1),
+			Some(_) => None,
+		};
+		self.status_message = Some(format!(
+			"Environment: {}",
+			self.get_active_environment().map_or("none", |environment| environment.name.as_str())
+		));
+	}
+
+	/// Variables for `{{name}}` substitution; secrets take precedence over the environment and `.http` file variables
+	/// over both.
+	fn resolved_variables(&self) -> HashMap<String, String> {
+		let mut variables =
+			self.get_active_environment().map(|environment| environment.variables.clone()).unwrap_or_default();
+		variables.extend(self.secret_variables.clone());
+		variables.extend(self.variables.clone());
+		variables.extend(self.script_variables.clone());
+		resolve_references(&variables)
+	}
+
+	fn save_current_request(&mut self) {
+		match save_request(&self.workspace.requests_dir(), &self.current_request) {
+			Ok(path) => {
+				self.current_request.file = Some(path.clone());
+				let existing = self.collection.iter().position(|request| request.id == self.current_request.id);
+
+				if let Some(index) = existing {
+					self.collection[index] = self.current_request.clone();
+					self.selected_collection_request = Some(index);
+				} else {
+					self.collection.push(self.current_request.clone());
+					self.selected_collection_request = Some(self.collection.len() - 1);
+				}
+
+				self.status_message = Some(format!(
+					"Saved request to {}{}",
+					path.display(),
+					plain_credentials_warning(&[secrets::referenced_request(&self.current_request)])
+				));
+			},
+			Err(error) => self.error_message = Some(format!("Save failed: {error}")),
+		}
+	}
+
+	/// Writes each request back to the `.http` file it came from and the others to [`Self::http_file_path`],
+	/// asking for the key to be pressed again before replacing files that exist.
+	fn export_http_file(&mut self) {
+		if self.collection.is_empty() {
+			self.status_message = Some(String::from("No requests to export"));
+			return;
+		}
+
+		let files = group_by_source(&self.collection, &self.http_file_path);
+		let existing: Vec<PathBuf> = files.iter().map(|(path, _)| path.clone()).filter(|path| path.exists()).collect();
+		if !existing.is_empty() && self.pending_overwrite.as_ref() != Some(&existing) {
+			let names = existing.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", ");
+			self.status_message = Some(format!(
+				"Export overwrites {names}; press '{}' again to confirm",
+				self.config.keymap.keys_for(Action::ExportCollection)
+			));
+			self.pending_overwrite = Some(existing);
+			return;
+		}
+		self.pending_overwrite = None;
+
+		let mut exported = Vec::new();
+		for (path, requests) in &files {
+			// Typed-in secret values are written as references to the secret variables
+			let variables = self.http_file_variables.get(path).unwrap_or(&self.variables);
+			let variables = variables.iter().map(|(name, value)| (name.clone(), secrets::to_references(value))).collect();
+			let requests: Vec<HttpRequest> = requests.iter().map(secrets::referenced_request).collect();
+			if let Err(error) = std::fs::write(path, to_http_file(&HttpFile { variables, requests: requests.clone() })) {
+				self.error_message = Some(format!("Export to {} failed: {error}", path.display()));
+				return;
+			}
+			exported.extend(requests);
+		}
+		self.status_message = Some(format!(
+			"Exported {} requests to {} files{}",
+			self.collection.len(),
+			files.len(),
+			plain_credentials_warning(&exported)
+		));
+	}
+
+	/// Routes for the collection's requests that have an example.
+	pub fn mock_routes(&self) -> Vec<Route> {
+		mock::routes(&self.collection, &self.resolved_variables())
+	}
+
+	fn toggle_mock_server(&mut self) {
+		if let Some(server) = self.mock_server.take() {
+			self.status_message = Some(format!("Stopped the mock server on http://{}", server.address));
+			return;
+		}
+
+		let address = SocketAddr::from(([127, 0, 0, 1], self.config.mock_port.unwrap_or(mock::DEFAULT_PORT)));
+		match MockServer::start(address, self.mock_routes()) {
+			Ok(server) => {
+				self.status_message = Some(format!("Serving {} mocks on http://{}", server.route_count(), server.address));
+				self.mock_server = Some(server);
+			},
+			Err(error) => self.error_message = Some(format!("Mock server failed to start on {address}: {error}")),
+		}
+	}
+
+	fn toggle_proxy(&mut self) {
+		if let Some(proxy) = self.proxy.take() {
+			self.status_message = Some(format!("Stopped the capture proxy on http://{}", proxy.address));
+			return;
+		}
+
+		let address = SocketAddr::from(([127, 0, 0, 1], self.config.proxy_port.unwrap_or(proxy::DEFAULT_PORT)));
+		match ProxyServer::start(address, self.http_client.clone()) {
+			Ok(proxy) => {
+				self.status_message = Some(format!("Capturing traffic into the history via http://{}", proxy.address));
+				self.proxy = Some(proxy);
+			},
+			Err(error) => self.error_message = Some(format!("Capture proxy failed to start on {address}: {error}")),
+		}
+	}
+
+	/// The request a History entry was the response to, if it's still known.
+	pub fn history_request(&self, response: &HttpResponse) -> Option<&HttpRequest> {
+		self
+			.captured_requests
+			.iter()
+			.chain(std::iter::once(&self.current_request))
+			.chain(&self.collection)
+			.find(|request| request.id == response.request_id)
+	}
+
+	/// Loads the selected History entry's request into the Request tab to be edited and sent again.
+	fn open_history_request(&mut self) {
+		let Some(request) = self.get_current_response().and_then(|response| self.history_request(response)) else {
+			self.status_message = Some(String::from("The request of this response is no longer known"));
+			return;
+		};
+
+		self.current_request = request.clone();
+		self.active_tab = MainContentTab::Request;
+		self.status_message = Some(format!("Opened {} {}", self.current_request.method.as_str(), self.current_request.url));
+	}
+
+	/// Keeps the collection's copy of the current request and a running mock server up to date with its example.
+	fn refresh_mock_routes(&mut self) {
+		if let Some(request) = self.collection.iter_mut().find(|request| request.id == self.current_request.id) {
+			request.example.clone_from(&self.current_request.example);
+		}
+		if let Some(server) = &self.mock_server {
+			server.set_routes(self.mock_routes());
+		}
+	}
+
+	fn save_example(&mut self) {
+		let Some(response) = self.get_current_response() else {
+			self.status_message = Some(String::from("No response to use as the example"));
+			return;
+		};
+
+		self.current_request.example = Some(Example::from_response(response));
+		self.refresh_mock_routes();
+		self.status_message = Some(String::from("Saved the response as the request's example"));
+	}
+
+	fn add_current_request_to_collection(&mut self) {
+		self.collection.push(self.current_request.clone());
+		self.selected_collection_request = Some(self.collection.len() - 1);
+		self.status_message = Some(String::from("Added request to collection"));
+	}
+
+	fn open_collection_request(&mut self) {
+		let Some(request) = self.selected_collection_request.and_then(|index| self.collection.get(index)) else {
+			return;
+		};
+
+		self.current_request = request.clone();
+		self.active_tab = MainContentTab::Request;
+		self.send_request();
+	}
+
+	/// Writes the body exactly as received, asking for Enter to be pressed again before replacing a file.
+	fn save_response_body(&mut self, path: &Path) {
+		let target = vec![path.to_path_buf()];
+		if path.exists() && self.pending_overwrite.as_ref() != Some(&target) {
+			self.status_message = Some(format!("{} exists; press Enter again to overwrite it", path.display()));
+			self.pending_overwrite = Some(target);
+			return;
+		}
+		self.pending_overwrite = None;
+
+		let Some(response) = self.get_current_response() else {
+			return;
+		};
+
+		match std::fs::write(path, &response.bytes) {
+			Ok(()) => {
+				self.status_message = Some(format!("Saved {} to {}", response.formatted_size(), path.display()));
+			},
+			Err(error) => self.error_message = Some(format!("Save failed: {error}")),
+		}
+	}
+
 	fn next_tab(&mut self) {
 		let next_index = (self.active_tab.as_index() + 1) % MainContentTab::TABS.len();
 		self.active_tab = MainContentTab::from_index(next_index).unwrap_or(MainContentTab::Request);
@@ -197,17 +637,16 @@ impl App {
 	}
 
 	fn request_section_next_tab(&mut self) {
-		let next_index = (self.request_section_active_tab.as_index() + 1) % RequestSectionTab::TABS.len();
-		self.request_section_active_tab = RequestSectionTab::from_index(next_index).unwrap_or(RequestSectionTab::Body);
+		let tabs = RequestSectionTab::tabs(self.current_request.is_graphql());
+		let next_index = (self.request_section_active_tab.index_in(tabs) + 1) % tabs.len();
+		self.request_section_active_tab = tabs[next_index].clone();
 	}
 
 	fn request_section_previous_tab(&mut self) {
-		let previous_index = if self.request_section_active_tab.as_index() == 0 {
-			RequestSectionTab::TABS.len() - 1
-		} else {
-			self.request_section_active_tab.as_index() - 1
-		};
-		self.request_section_active_tab = RequestSectionTab::from_index(previous_index).unwrap_or(RequestSectionTab::Body);
+		let tabs = RequestSectionTab::tabs(self.current_request.is_graphql());
+		let current_index = self.request_section_active_tab.index_in(tabs);
+		let previous_index = if current_index == 0 { tabs.len() - 1 } else { current_index - 1 };
+		self.request_section_active_tab = tabs[previous_index].clone();
 	}
 
 	fn response_section_next_tab(&mut self) {
@@ -227,41 +666,52 @@ impl App {
 	}
 
 	pub fn handle_key_event(&mut self, key: KeyEvent) -> anyhow::Result<bool> {
-		if self.state == AppState::Help && key.code == KeyCode::Esc {
+		self.status_message = None;
+
+		if matches!(self.state, AppState::Help | AppState::PreviewingRequest) && key.code == KeyCode::Esc {
 			self.state = AppState::Normal;
 		}
 
-		match self.input_mode {
+		let pending_overwrite = self.pending_overwrite.clone();
+		let result = match self.input_mode {
 			InputMode::Normal => self.handle_normal_mode_key(key),
-			InputMode::Editing => self.handle_editing_mode_key(key),
+			InputMode::Editing => Ok(self.handle_editing_mode_key(key)),
+		};
+		// A warning about overwriting files only holds for the very next key
+		if self.pending_overwrite == pending_overwrite {
+			self.pending_overwrite = None;
 		}
+		result
 	}
 
-	pub fn handle_paste(&mut self, text: &str) -> anyhow::Result<()> {
+	pub fn handle_paste(&mut self, text: &str) {
 		if self.state == AppState::EditingUrl {
 			self.url_textarea.insert_str(text);
-			self.save_current_textarea_content()?;
-			self.state = AppState::Normal;
-			self.input_mode = InputMode::Normal;
-			self.vim = Vim::new(Mode::Normal);
+			if self.save_textarea_or_show_error() {
+				self.state = AppState::Normal;
+				self.input_mode = InputMode::Normal;
+				self.vim = Vim::new(Mode::Normal);
+			}
 		}
-
-		Ok(())
 	}
 
 	#[allow(clippy::unnecessary_wraps)]
 	fn handle_normal_mode_key(&mut self, key: KeyEvent) -> anyhow::Result<bool> {
-		match key.code {
-			KeyCode::Char('q') => {
+		let Some(action) = self.config.keymap.action(key) else {
+			return Ok(false);
+		};
+
+		match action {
+			Action::Quit => {
 				return Ok(true); // Signal quit
 			},
-			KeyCode::Tab => self.next_tab(),
-			KeyCode::BackTab => self.previous_tab(),
-			KeyCode::Char(']') => self.request_section_next_tab(),
-			KeyCode::Char('[') => self.request_section_previous_tab(),
-			KeyCode::Char('}') => self.response_section_next_tab(),
-			KeyCode::Char('{') => self.response_section_previous_tab(),
-			KeyCode::Char('u') => {
+			Action::NextTab => self.next_tab(),
+			Action::PreviousTab => self.previous_tab(),
+			Action::NextRequestTab => self.request_section_next_tab(),
+			Action::PreviousRequestTab => self.request_section_previous_tab(),
+			Action::NextResponseTab => self.response_section_next_tab(),
+			Action::PreviousResponseTab => self.response_section_previous_tab(),
+			Action::EditUrl => {
 				self.state = AppState::EditingUrl;
 				self.input_mode = InputMode::Editing;
 				self.url_textarea = TextArea::from([&self.current_request.url]);
@@ -274,7 +724,20 @@ impl App {
 
 				self.setup_textarea_for_vim();
 			},
-			KeyCode::Char('e') => {
+			Action::EditName => {
+				self.state = AppState::EditingName;
+				self.input_mode = InputMode::Editing;
+				self.name_textarea = TextArea::from([&self.current_request.name]);
+
+				if self.current_request.name.is_empty() {
+					self.vim = Vim::new(Mode::Insert);
+				} else {
+					self.vim = Vim::new(Mode::Normal);
+				}
+
+				self.setup_textarea_for_vim();
+			},
+			Action::EditRequest => {
 				match self.request_section_active_tab {
 					RequestSectionTab::Headers => {
 						self.state = AppState::EditingHeaders;
@@ -300,6 +763,63 @@ impl App {
 							TextArea::from(self.current_request.body.lines().collect::<Vec<_>>())
 						};
 					},
+					RequestSectionTab::Variables => {
+						self.state = AppState::EditingGraphqlVariables;
+
+						let variables =
+							self.current_request.graphql.as_ref().map_or_else(String::new, |graphql| graphql.variables.clone());
+
+						self.graphql_variables_textarea = if variables.is_empty() {
+							self.vim = Vim::new(Mode::Insert);
+							TextArea::default()
+						} else {
+							self.vim = Vim::new(Mode::Normal);
+							TextArea::from(variables.lines().collect::<Vec<_>>())
+						};
+					},
+					RequestSectionTab::PreRequestScript | RequestSectionTab::PostResponseScript => {
+						self.state = AppState::EditingScript;
+
+						let script = if self.request_section_active_tab == RequestSectionTab::PreRequestScript {
+							&self.current_request.pre_request_script
+						} else {
+							&self.current_request.post_response_script
+						};
+
+						self.script_textarea = if script.is_empty() {
+							self.vim = Vim::new(Mode::Insert);
+							TextArea::default()
+						} else {
+							self.vim = Vim::new(Mode::Normal);
+							TextArea::from(script.lines().collect::<Vec<_>>())
+						};
+					},
+					RequestSectionTab::Auth => {
+						self.state = AppState::EditingAuth;
+
+						let auth_form = self.current_request.auth.as_ref().map_or_else(String::new, Auth::to_form);
+
+						self.auth_textarea = if auth_form.is_empty() {
+							self.vim = Vim::new(Mode::Insert);
+							TextArea::default()
+						} else {
+							self.vim = Vim::new(Mode::Normal);
+							TextArea::from(auth_form.lines().collect::<Vec<_>>())
+						};
+					},
+					RequestSectionTab::Example => {
+						self.state = AppState::EditingExample;
+
+						let example_text = self.current_request.example.as_ref().map_or_else(String::new, Example::to_text);
+
+						self.example_textarea = if example_text.is_empty() {
+							self.vim = Vim::new(Mode::Insert);
+							TextArea::default()
+						} else {
+							self.vim = Vim::new(Mode::Normal);
+							TextArea::from(example_text.lines().collect::<Vec<_>>())
+						};
+					},
 					RequestSectionTab::Query => {
 						self.state = AppState::EditingQueries;
 
@@ -319,15 +839,9 @@ impl App {
 				self.input_mode = InputMode::Editing;
 				self.setup_textarea_for_vim();
 			},
-			KeyCode::Char('r') => {
+			Action::InspectResponse => {
 				let should_process = self.get_current_response().is_some();
-				let body_text = self.get_current_response().map_or_else(String::new, |response| {
-					if response.is_json() {
-						response.pretty_json().unwrap_or_else(|_| response.body.clone())
-					} else {
-						response.body.clone()
-					}
-				});
+				let body_text = self.get_current_response().map_or_else(String::new, HttpResponse::formatted_body);
 				let headers_text = self.get_current_response().map_or_else(String::new, HttpResponse::formatted_headers);
 
 				if should_process {
@@ -354,7 +868,14 @@ impl App {
 								TextArea::from(headers_text.lines().collect::<Vec<_>>())
 							};
 						},
-						ResponseSectionTab::Cookies => panic!("not implemented yet"),
+						ResponseSectionTab::Cookies
+						| ResponseSectionTab::Redirects
+						| ResponseSectionTab::Timing
+						| ResponseSectionTab::Connection
+						| ResponseSectionTab::SentRequest
+						| ResponseSectionTab::Tests => {
+							return Ok(false);
+						},
 					}
 
 					self.fullscreen_section = FullscreenSection::Response;
@@ -362,45 +883,140 @@ impl App {
 					self.setup_textarea_for_vim();
 				}
 			},
-			KeyCode::Char('m') => {
+			Action::NextMethod => {
 				self.current_request.set_method(self.current_request.method.next());
 			},
-			KeyCode::Char('M') => {
+			Action::PreviousMethod => {
 				self.current_request.set_method(self.current_request.method.previous());
 			},
-			KeyCode::Enter => {
-				if !self.loading {
-					self.send_request();
+			Action::SaveResponseBody => {
+				let Some(file_name) = self.get_current_response().map(HttpResponse::suggested_file_name) else {
+					self.status_message = Some(String::from("No response to save"));
+					return Ok(false);
+				};
+
+				self.state = AppState::EditingSavePath;
+				self.input_mode = InputMode::Editing;
+				self.save_path_textarea = TextArea::from([file_name]);
+				self.vim = Vim::new(Mode::Normal);
+				self.setup_textarea_for_vim();
+			},
+			Action::ToggleDecompression => {
+				self.send_options.decompress = !self.send_options.decompress;
+				self.status_message = Some(String::from(if self.send_options.decompress {
+					"Decompressing response bodies"
+				} else {
+					"Showing raw response bodies as transferred"
+				}));
+			},
+			Action::ToggleRedirects => {
+				self.send_options.follow_redirects = !self.send_options.follow_redirects;
+				self.status_message = Some(String::from(if self.send_options.follow_redirects {
+					"Following redirects"
+				} else {
+					"Stopping at the first redirect"
+				}));
+			},
+			Action::NextHttpVersion => {
+				self.current_request.http_version = self.current_request.http_version.next();
+				self.status_message = Some(match self.current_request.http_version {
+					HttpVersion::Negotiate => String::from("Negotiating the HTTP version"),
+					version => format!("Sending with {}", version.as_str()),
+				});
+			},
+			Action::NextMessageKind => {
+				self.message_kind = self.message_kind.next();
+				self.status_message = Some(format!("Sending WebSocket messages as {}", self.message_kind.as_str()));
+			},
+			Action::StopStream => match &self.stop_stream {
+				Some(stop) => {
+					stop.notify_one();
+					self.status_message = Some(String::from("Stopping the response stream"));
+				},
+				None => self.status_message = Some(String::from("No response is streaming")),
+			},
+			Action::ToggleGraphql => {
+				self.current_request.toggle_graphql();
+				if !self.current_request.is_graphql() && self.request_section_active_tab == RequestSectionTab::Variables {
+					self.request_section_active_tab = RequestSectionTab::Body;
 				}
+				self.status_message = Some(String::from(if self.current_request.is_graphql() {
+					"GraphQL mode: the body is the query, sent as a JSON POST"
+				} else {
+					"Sending the body as is"
+				}));
+			},
+			Action::NextOperation if self.current_request.is_grpc() => self.next_grpc_method(),
+			Action::NextOperation => self.next_graphql_operation(),
+			Action::IntrospectSchema if self.current_request.is_grpc() => self.reflect_grpc_services(),
+			Action::IntrospectSchema => self.introspect_schema(),
+			Action::CloseWebSocket => match &self.websocket {
+				Some(connection) => {
+					if let Err(error) = connection.close() {
+						self.error_message = Some(error.to_string());
+					}
+				},
+				None => self.status_message = Some(String::from("No WebSocket connection")),
+			},
+			Action::ToggleBody => {
+				self.current_request.toggle_body();
+				self.status_message = Some(format!(
+					"{} body for {}",
+					if self.current_request.has_body() { "Sending" } else { "Not sending" },
+					self.current_request.method.as_str()
+				));
+			},
+			Action::SendRequest if !self.loading && self.active_tab == MainContentTab::Collection => {
+				self.open_collection_request();
 			},
-			KeyCode::Char('?') => {
+			Action::SendRequest if self.active_tab == MainContentTab::History && !self.responses.is_empty() => {
+				self.open_history_request();
+			},
+			Action::SendRequest if !self.loading => {
+				self.send_request();
+			},
+			Action::AddToCollection => self.add_current_request_to_collection(),
+			Action::SaveRequest => self.save_current_request(),
+			Action::NextEnvironment => self.next_environment(),
+			Action::ExportCollection => self.export_http_file(),
+			Action::SaveExample => self.save_example(),
+			Action::ToggleMockServer => self.toggle_mock_server(),
+			Action::ToggleProxy => self.toggle_proxy(),
+			Action::Help => {
 				self.state = AppState::Help;
 			},
-			KeyCode::Char('c') => {
-				if key.modifiers.contains(KeyModifiers::CONTROL) {
-					self.clear_response();
-				}
+			Action::PreviewRequest => {
+				let request = self.current_request.with_variables(&self.resolved_variables());
+				self.request_preview = format_request(&secrets::masked_request(&request));
+				self.state = AppState::PreviewingRequest;
 			},
-			KeyCode::Up => {
-				if self.active_tab == MainContentTab::History && !self.responses.is_empty() {
-					if let Some(selected) = self.selected_response {
-						if selected > 0 {
-							self.selected_response = Some(selected - 1);
-						}
-					} else {
-						self.selected_response = Some(self.responses.len() - 1);
+			Action::ClearResponses => {
+				self.clear_response();
+			},
+			Action::SelectPrevious if self.active_tab == MainContentTab::Collection && !self.collection.is_empty() => {
+				self.selected_collection_request =
+					Some(self.selected_collection_request.map_or(0, |selected| selected.saturating_sub(1)));
+			},
+			Action::SelectNext if self.active_tab == MainContentTab::Collection && !self.collection.is_empty() => {
+				self.selected_collection_request =
+					Some(self.selected_collection_request.map_or(0, |selected| (selected + 1).min(self.collection.len() - 1)));
+			},
+			Action::SelectPrevious if self.active_tab == MainContentTab::History && !self.responses.is_empty() => {
+				if let Some(selected) = self.selected_response {
+					if selected > 0 {
+						self.selected_response = Some(selected - 1);
 					}
+				} else {
+					self.selected_response = Some(self.responses.len() - 1);
 				}
 			},
-			KeyCode::Down => {
-				if self.active_tab == MainContentTab::History && !self.responses.is_empty() {
-					if let Some(selected) = self.selected_response {
-						if selected < self.responses.len() - 1 {
-							self.selected_response = Some(selected + 1);
-						}
-					} else {
-						self.selected_response = Some(0);
+			Action::SelectNext if self.active_tab == MainContentTab::History && !self.responses.is_empty() => {
+				if let Some(selected) = self.selected_response {
+					if selected < self.responses.len() - 1 {
+						self.selected_response = Some(selected + 1);
 					}
+				} else {
+					self.selected_response = Some(0);
 				}
 			},
 			_ => {},
@@ -414,52 +1030,83 @@ impl App {
 		self.input_mode = InputMode::Normal;
 	}
 
-	fn handle_editing_mode_key(&mut self, key: KeyEvent) -> anyhow::Result<bool> {
+	fn handle_editing_mode_key(&mut self, key: KeyEvent) -> bool {
 		if self.vim.mode == Mode::Normal {
 			match key.code {
 				KeyCode::Enter => {
-					self.save_current_textarea_content()?;
-					self.reset_state();
-					return Ok(false);
+					if !self.save_textarea_or_show_error() {
+						return false;
+					}
+					// A save path waiting for its overwrite to be confirmed stays open
+					if self.state != AppState::EditingSavePath || self.pending_overwrite.is_none() {
+						self.reset_state();
+					}
+					return false;
 				},
 				KeyCode::Esc => {
 					self.reset_state();
-					return Ok(false);
+					return false;
 				},
 				_ => {},
 			}
 		}
 
+		if self.state == AppState::EditingBody
+			&& self.vim.mode == Mode::Insert
+			&& key.code == KeyCode::Tab
+			&& self.current_request.is_graphql()
+			&& self.graphql_schema.is_some()
+		{
+			self.complete_graphql_field();
+			return false;
+		}
+
 		let input: Input = key.into();
 
 		let textarea = match self.state {
 			AppState::EditingUrl => &mut self.url_textarea,
+			AppState::EditingName => &mut self.name_textarea,
+			AppState::EditingSavePath => &mut self.save_path_textarea,
 			AppState::EditingHeaders => &mut self.headers_textarea,
 			AppState::EditingBody => &mut self.body_textarea,
+			AppState::EditingGraphqlVariables => &mut self.graphql_variables_textarea,
 			AppState::EditingQueries => &mut self.queries_textarea,
+			AppState::EditingScript => &mut self.script_textarea,
+			AppState::EditingAuth => &mut self.auth_textarea,
+			AppState::EditingExample => &mut self.example_textarea,
 			AppState::InspectingResponseBody => &mut self.response_body_textarea,
 			AppState::InspectingResponseHeaders => &mut self.response_headers_textarea,
-			AppState::Help | AppState::Normal => return Ok(false),
+			AppState::Help | AppState::PreviewingRequest | AppState::Normal => return false,
 		};
 
 		match self.vim.transition(input, textarea) {
 			Transition::Mode(mode) if self.vim.mode != mode => {
-				textarea.set_block(mode.block());
-				textarea.set_cursor_style(mode.cursor_style());
+				textarea.set_block(mode.block(&self.config.theme));
+				textarea.set_cursor_style(mode.cursor_style(&self.config.theme));
 				self.vim = Vim::new(mode);
 			},
 			Transition::Nop | Transition::Mode(_) => {},
 			Transition::Pending(pending_input) => {
 				self.vim = self.vim.clone().with_pending(pending_input);
 			},
-			Transition::Quit => {
-				self.state = AppState::Normal;
-				self.input_mode = InputMode::Normal;
-				self.vim = Vim::new(Mode::Normal);
-			},
 		}
 
-		Ok(false)
+		false
+	}
+
+	/// Saves the textarea being edited, returning whether it could be. When it can't, e.g. for invalid JSON, the error
+	/// is shown and the editor stays open to fix it.
+	fn save_textarea_or_show_error(&mut self) -> bool {
+		match self.save_current_textarea_content() {
+			Ok(()) => {
+				self.error_message = None;
+				true
+			},
+			Err(error) => {
+				self.error_message = Some(format!("{error:#}"));
+				false
+			},
+		}
 	}
 
 	fn save_current_textarea_content(&mut self) -> anyhow::Result<()> {
@@ -469,10 +1116,24 @@ impl App {
 
 				if url_text.starts_with("curl") {
 					self.current_request = parse_curl(&url_text)?;
+				} else if let Some((method, url)) = url_text.split_once(char::is_whitespace)
+					&& let Ok(method) = method.parse::<HttpMethod>()
+				{
+					// `PURGE https://...` sets any method, including ones `m`/`M` don't cycle through
+					self.current_request.set_method(method);
+					self.current_request.set_url(url.trim_start());
 				} else {
 					self.current_request.set_url(&url_text);
 				}
 			},
+			AppState::EditingName => {
+				let name_text = self.name_textarea.lines().join("");
+				name_text.trim().clone_into(&mut self.current_request.name);
+			},
+			AppState::EditingSavePath => {
+				let path_text = self.save_path_textarea.lines().join("");
+				self.save_response_body(Path::new(path_text.trim()));
+			},
 			AppState::EditingHeaders => {
 				self.current_request.headers.clear();
 				for line in self.headers_textarea.lines() {
@@ -487,62 +1148,138 @@ impl App {
 			},
 			AppState::EditingBody => {
 				let body_content = self.body_textarea.lines().join("\n");
-				self.current_request.set_body(&body_content)?;
+				if self.current_request.is_websocket() {
+					// WebSocket messages are text, JSON or hex, checked when they're sent
+					self.current_request.body = body_content;
+				} else if self.current_request.is_graphql() {
+					self.current_request.body = body_content;
+				} else {
+					self.current_request.set_body(&body_content)?;
+				}
+			},
+			AppState::EditingGraphqlVariables => {
+				let variables_content = self.graphql_variables_textarea.lines().join("\n");
+				let variables = if variables_content.trim().is_empty() {
+					String::new()
+				} else {
+					let json_value: serde_json::Value = serde_json::from_str(&variables_content)?;
+					serde_json::to_string_pretty(&json_value)?
+				};
+				if let Some(graphql) = &mut self.current_request.graphql {
+					graphql.variables = variables;
+				}
 			},
 			AppState::EditingQueries => {
 				self.current_request.queries.clear();
 				for line in self.queries_textarea.lines() {
-					if let Some((key, value)) = line.split_once(':') {
-						let key = key.trim().to_string();
-						let value = value.trim().to_string();
-						if !key.is_empty() && !value.is_empty() {
-							self.current_request.add_query(key, value);
-						}
+					// A name without a value is sent as just the name, e.g. `?flag`
+					let (key, value) = line.split_once(':').unwrap_or((line, ""));
+					let key = key.trim().to_string();
+					if !key.is_empty() {
+						self.current_request.add_query(key, value.trim().to_string());
 					}
 				}
 			},
-			AppState::Help | AppState::Normal | AppState::InspectingResponseBody | AppState::InspectingResponseHeaders => {},
+			AppState::EditingScript => {
+				let script = self.script_textarea.lines().join("\n");
+				if self.request_section_active_tab == RequestSectionTab::PreRequestScript {
+					self.current_request.pre_request_script = script;
+				} else {
+					self.current_request.post_response_script = script;
+				}
+			},
+			AppState::EditingAuth => {
+				self.current_request.auth = Auth::from_form(&self.auth_textarea.lines().join("\n"))?;
+			},
+			AppState::EditingExample => {
+				self.current_request.example = Example::from_text(&self.example_textarea.lines().join("\n"))?;
+				self.refresh_mock_routes();
+			},
+			AppState::Help
+			| AppState::PreviewingRequest
+			| AppState::Normal
+			| AppState::InspectingResponseBody
+			| AppState::InspectingResponseHeaders => {},
 		}
 
 		Ok(())
 	}
 
 	fn setup_textarea_for_vim(&mut self) {
+		let line_number_style = Style::default().bg(self.config.theme.line_number_background);
 		let textarea = match self.state {
 			AppState::EditingUrl => &mut self.url_textarea,
+			AppState::EditingName => &mut self.name_textarea,
+			AppState::EditingSavePath => &mut self.save_path_textarea,
 			AppState::EditingHeaders => &mut self.headers_textarea,
 			AppState::EditingBody => &mut self.body_textarea,
+			AppState::EditingGraphqlVariables => &mut self.graphql_variables_textarea,
 			AppState::EditingQueries => &mut self.queries_textarea,
+			AppState::EditingScript => &mut self.script_textarea,
+			AppState::EditingAuth => &mut self.auth_textarea,
+			AppState::EditingExample => &mut self.example_textarea,
 			AppState::InspectingResponseBody => &mut self.response_body_textarea,
 			AppState::InspectingResponseHeaders => &mut self.response_headers_textarea,
-			AppState::Help | AppState::Normal => return,
+			AppState::Help | AppState::PreviewingRequest | AppState::Normal => return,
 		};
 
 		match self.state {
 			AppState::EditingUrl => {
-				textarea.set_placeholder_text("Enter URL... or paste curl");
+				textarea.set_placeholder_text("Enter URL, METHOD URL... or paste curl");
+			},
+			AppState::EditingName => {
+				textarea.set_placeholder_text("Request name...");
+			},
+			AppState::EditingSavePath => {
+				textarea.set_placeholder_text("File to save the response body to...");
 			},
 			AppState::EditingHeaders => {
-				textarea.set_line_number_style(Style::default().bg(Color::DarkGray));
+				textarea.set_line_number_style(line_number_style);
 				textarea.set_placeholder_text("Authorization: Bearer ....");
 			},
+			AppState::EditingBody if self.current_request.is_graphql() => {
+				textarea.set_line_number_style(line_number_style);
+				textarea.set_placeholder_text("query { ... } (Tab completes fields once the schema is loaded)");
+			},
 			AppState::EditingBody => {
-				textarea.set_line_number_style(Style::default().bg(Color::DarkGray));
+				textarea.set_line_number_style(line_number_style);
 				textarea.set_placeholder_text("Request body (JSON, text, etc.)");
 			},
+			AppState::EditingGraphqlVariables => {
+				textarea.set_line_number_style(line_number_style);
+				textarea.set_placeholder_text("{ \"id\": 1 }");
+			},
 			AppState::EditingQueries => {
-				textarea.set_line_number_style(Style::default().bg(Color::DarkGray));
+				textarea.set_line_number_style(line_number_style);
 				textarea.set_placeholder_text("name: Joe ....");
 			},
+			AppState::EditingScript if self.request_section_active_tab == RequestSectionTab::PreRequestScript => {
+				textarea.set_line_number_style(line_number_style);
+				textarea.set_placeholder_text("request.headers[\"X-Time\"] = `${timestamp()}`;");
+			},
+			AppState::EditingScript => {
+				textarea.set_line_number_style(line_number_style);
+				textarea.set_placeholder_text("test(\"status is 200\", response.status == 200);");
+			},
+			AppState::EditingAuth => {
+				textarea.set_line_number_style(line_number_style);
+				textarea.set_placeholder_text(
+					"type: aws-sigv4\naccess_key: {{aws_key}}\nsecret_key: {{aws_secret}}\nregion: us-east-1\nservice: execute-api",
+				);
+			},
+			AppState::EditingExample => {
+				textarea.set_line_number_style(line_number_style);
+				textarea.set_placeholder_text("200 OK\nX-Mock-Delay: 500\nContent-Type: application/json\n\n{ \"id\": 1 }");
+			},
 			AppState::InspectingResponseBody | AppState::InspectingResponseHeaders => {
-				textarea.set_line_number_style(Style::default().bg(Color::DarkGray));
+				textarea.set_line_number_style(line_number_style);
 			},
-			AppState::Help | AppState::Normal => {},
+			AppState::Help | AppState::PreviewingRequest | AppState::Normal => {},
 		}
 
 		textarea.set_tab_length(2);
-		textarea.set_block(self.vim.mode.block());
-		textarea.set_cursor_style(self.vim.mode.cursor_style());
+		textarea.set_block(self.vim.mode.block(&self.config.theme));
+		textarea.set_cursor_style(self.vim.mode.cursor_style(&self.config.theme));
 	}
 
 	fn send_request(&mut self) {
@@ -550,44 +1287,408 @@ impl App {
 			self.error_message = Some("URL cannot be empty".to_string());
 			return;
 		}
+		if self.current_request.is_websocket() {
+			self.send_websocket_message();
+			return;
+		}
+
+		if self.current_request.is_grpc() {
+			self.send_grpc_request();
+			return;
+		}
+
+		let mut request = self.current_request.clone();
+		let mut report = if self.current_request.pre_request_script.trim().is_empty() {
+			None
+		} else {
+			let outcome =
+				script::run_pre_request(&self.current_request.pre_request_script, &mut request, &self.resolved_variables());
+			self.script_variables.extend(outcome.variables);
+			if let Some(error) = outcome.report.error {
+				self.error_message = Some(format!("Not sent, the {error}"));
+				return;
+			}
+			Some(outcome.report)
+		};
+
+		let request = match request.with_variables(&self.resolved_variables()).with_graphql_envelope() {
+			Ok(request) => request,
+			Err(error) => {
+				self.error_message = Some(error.to_string());
+				return;
+			},
+		};
 
 		self.loading = true;
 		self.error_message = None;
 
-		let request = self.current_request.clone();
 		let http_client = self.http_client.clone();
+		let options = self.send_options;
+		let variables = self.resolved_variables();
+		let openapi = self.openapi.clone();
 		let tx = self.response_tx.clone();
+		let stop = Arc::new(Notify::new());
+		self.stop_stream = Some(Arc::clone(&stop));
 
 		tokio::spawn(async move {
-			let result = match http_client.send_request(&request).await {
-				Ok(response) => Ok(response),
+			let progress_tx = tx.clone();
+			let progress = move |progress| {
+				let _ = progress_tx.send(ResponseUpdate::Progress(progress));
+			};
+
+			let result = match http_client.stream_request(&request, options, &progress, &stop).await {
+				Ok(mut response) => {
+					if let Some(spec) = openapi {
+						response.validation = Some(spec.validate(&request.method, &request.url, &response));
+					}
+					if !request.post_response_script.trim().is_empty() {
+						let outcome = script::run_post_response(&request.post_response_script, &request, &response, &variables);
+						report.get_or_insert_with(ScriptReport::default).extend(outcome.report);
+						let _ = tx.send(ResponseUpdate::Variables(outcome.variables));
+					}
+					response.script_report = report;
+					Ok(response)
+				},
 				Err(error) => Err(format!("Request failed: {error}")),
 			};
 
-			let _ = tx.send(result);
+			let _ = tx.send(ResponseUpdate::Done(Box::new(result)));
+		});
+	}
+
+	/// Calls the gRPC method the current URL names, streaming each response message into the response pane.
+	fn send_grpc_request(&mut self) {
+		let Some(services) = self.grpc_services.clone() else {
+			self.error_message = Some(grpc::GrpcError::NoServices.to_string());
+			return;
+		};
+		let request = self.current_request.with_variables(&self.resolved_variables());
+
+		self.loading = true;
+		self.error_message = None;
+
+		let http_client = self.http_client.clone();
+		let tx = self.response_tx.clone();
+		let stop = Arc::new(Notify::new());
+		self.stop_stream = Some(Arc::clone(&stop));
+
+		tokio::spawn(async move {
+			let progress_tx = tx.clone();
+			let progress = move |progress| {
+				let _ = progress_tx.send(ResponseUpdate::Progress(progress));
+			};
+
+			let result = grpc::call(&http_client, &services, &request, &progress, &stop)
+				.await
+				.map_err(|error| format!("Request failed: {error}"));
+			let _ = tx.send(ResponseUpdate::Done(Box::new(result)));
+		});
+	}
+
+	/// Points the URL at the next known gRPC method, filling in an example request message when the body is empty.
+	fn next_grpc_method(&mut self) {
+		let Some(services) = &self.grpc_services else {
+			self.error_message = Some(grpc::GrpcError::NoServices.to_string());
+			return;
+		};
+		let paths = services.method_paths();
+		let Some(first) = paths.first() else {
+			self.status_message = Some(String::from("The loaded services have no methods"));
+			return;
+		};
+
+		let (base_url, current) = grpc::split_method(&self.current_request.url);
+		let path = current
+			.and_then(|current| paths.iter().position(|path| *path == current))
+			.and_then(|index| paths.get(index + 1))
+			.unwrap_or(first);
+
+		self.current_request.set_url(&format!("{base_url}/{path}"));
+		if self.current_request.body.trim().is_empty()
+			&& let Some(example) = services.example_request(path)
+		{
+			self.current_request.body = example;
+		}
+		self.status_message = Some(format!("Calling {path}"));
+	}
+
+	/// Loads the services of the server the current URL points to through its reflection service.
+	fn reflect_grpc_services(&mut self) {
+		let request = self.current_request.with_variables(&self.resolved_variables());
+		self.status_message = Some(format!("Loading gRPC services from {}", request.url));
+		let http_client = self.http_client.clone();
+		let tx = self.response_tx.clone();
+
+		tokio::spawn(async move {
+			let result =
+				Services::from_reflection(&http_client, &request).await.map_err(|error| format!("Reflection failed: {error}"));
+			let _ = tx.send(ResponseUpdate::Services(result));
+		});
+	}
+
+	/// Cycles the GraphQL operation to run through those the query defines, then back to letting the server pick.
+	fn next_graphql_operation(&mut self) {
+		let names = operation_names(&self.current_request.body);
+		let Some(graphql) = &mut self.current_request.graphql else {
+			self.status_message = Some(String::from("Not a GraphQL request"));
+			return;
+		};
+		if names.is_empty() {
+			graphql.operation_name = None;
+			self.status_message = Some(String::from("The query has no named operations"));
+			return;
+		}
+
+		let current = graphql.operation_name.as_ref().and_then(|name| names.iter().position(|other| other == name));
+		graphql.operation_name = current.map_or_else(|| names.first().cloned(), |index| names.get(index + 1).cloned());
+		self.status_message = Some(
+			graphql
+				.operation_name
+				.as_ref()
+				.map_or_else(|| String::from("No operation name sent"), |name| format!("Running operation {name}")),
+		);
+	}
+
+	/// Sends the introspection query to the current URL, with the request's headers, to load the schema.
+	fn introspect_schema(&mut self) {
+		if self.current_request.url.is_empty() {
+			self.error_message = Some("URL cannot be empty".to_string());
+			return;
+		}
+
+		let mut request = self.current_request.with_variables(&self.resolved_variables());
+		request.body = String::from(INTROSPECTION_QUERY);
+		request.graphql = Some(Graphql::default());
+		let request = match request.with_graphql_envelope() {
+			Ok(request) => request,
+			Err(error) => {
+				self.error_message = Some(error.to_string());
+				return;
+			},
+		};
+
+		self.status_message = Some(format!("Loading the GraphQL schema from {}", request.url));
+		let http_client = self.http_client.clone();
+		let options = self.send_options;
+		let tx = self.response_tx.clone();
+
+		tokio::spawn(async move {
+			let result = match http_client.send_request(&request, options).await {
+				Ok(response) => Schema::from_introspection(&response.body).map_err(|error| error.to_string()),
+				Err(error) => Err(format!("Introspection failed: {error}")),
+			};
+			let _ = tx.send(ResponseUpdate::Schema(result));
+		});
+	}
+
+	/// Completes the field name before the cursor as far as the candidates agree, listing them when they don't.
+	fn complete_graphql_field(&mut self) {
+		let Some(schema) = &self.graphql_schema else {
+			return;
+		};
+
+		let lines = self.body_textarea.lines();
+		let (row, col) = self.body_textarea.cursor();
+		let line_offset = lines[row].char_indices().nth(col).map_or(lines[row].len(), |(offset, _)| offset);
+		let cursor = lines[..row].iter().map(|line| line.len() + 1).sum::<usize>() + line_offset;
+		let text = lines.join("\n");
+
+		let completions = schema.completions(&text, cursor);
+		let Some(first) = completions.first() else {
+			self.status_message = Some(String::from("No fields to complete here"));
+			return;
+		};
+
+		let typed =
+			text[..cursor].len() - text[..cursor].trim_end_matches(|ch: char| ch.is_ascii_alphanumeric() || ch == '_').len();
+		let common = completions.iter().fold(first.as_str(), |common, completion| {
+			let length = common.bytes().zip(completion.bytes()).take_while(|(a, b)| a == b).count();
+			&common[..length]
 		});
+		self.body_textarea.insert_str(&common[typed..]);
+
+		if completions.len() > 1 {
+			self.status_message = Some(completions.join("  "));
+		}
+	}
+
+	/// Connects to the current `ws://` / `wss://` URL, or sends the body as a message once connected to it.
+	fn send_websocket_message(&mut self) {
+		let request = self.current_request.with_variables(&self.resolved_variables());
+		self.error_message = None;
+
+		match &self.websocket {
+			Some(connection) if connection.url == request.url && connection.connected => {
+				if let Err(error) = self.message_kind.encode(&request.body).and_then(|message| connection.send(message)) {
+					self.error_message = Some(format!("Send failed: {error}"));
+				}
+			},
+			Some(connection) if connection.url == request.url => {
+				self.status_message = Some(String::from("Still connecting..."));
+			},
+			_ => {
+				self.status_message = Some(format!("Connecting to {}", request.url));
+				self.websocket = Some(WebSocketConnection::open(self.http_client.clone(), request));
+			},
+		}
+	}
+
+	fn update_websocket(&mut self) {
+		let Some(connection) = &mut self.websocket else {
+			return;
+		};
+
+		while let Some(entry) = connection.try_recv() {
+			let ends_connection = entry.ends_connection();
+			self.websocket_log.push(entry);
+			if ends_connection {
+				self.websocket = None;
+				return;
+			}
+		}
 	}
 
 	pub fn update(&mut self) {
-		while let Ok(result) = self.response_rx.try_recv() {
-			self.loading = false;
+		self.update_websocket();
+		if let Some(server) = &mut self.mock_server {
+			self.mock_log.extend(server.take_hits());
+		}
+		if let Some(proxy) = &mut self.proxy {
+			for capture in proxy.take_captures() {
+				let mut response = capture.response;
+				secrets::redact_response(&mut response);
+				if self.captured_requests.len() == MAX_CAPTURED_REQUESTS
+					&& let Some(oldest) = self.captured_requests.pop_front()
+				{
+					self.remove_responses_to(&oldest.id);
+				}
+				self.captured_requests.push_back(capture.request);
+				self.responses.push(response);
+			}
+		}
 
-			match result {
-				Ok(response) => {
-					self.responses.push(response);
-					self.selected_response = Some(self.responses.len() - 1);
-					self.error_message = None;
+		while let Ok(update) = self.response_rx.try_recv() {
+			match update {
+				ResponseUpdate::Progress(Progress::Head(response)) => {
+					self.pending_chunks.clear();
+					let mut response = *response;
+					self.stream_decoder = self.send_options.decompress.then(|| response.stream_decoder()).flatten();
+					secrets::redact_response(&mut response);
+					// A redirect's head is replaced by the next one
+					let streaming = self.streaming.take();
+					self.streaming = Some(response.id.clone());
+					self.show_response(streaming.as_deref(), response);
 				},
-				Err(error) => {
-					self.error_message = Some(error);
+				ResponseUpdate::Progress(Progress::Chunk(chunk)) => self.pending_chunks.push(chunk),
+				ResponseUpdate::Schema(Ok(schema)) => {
+					self.status_message = Some(format!("Loaded the GraphQL schema: {} types", schema.types.len()));
+					self.graphql_schema = Some(schema);
+				},
+				ResponseUpdate::Schema(Err(error)) | ResponseUpdate::Services(Err(error)) => self.error_message = Some(error),
+				ResponseUpdate::Variables(variables) => self.script_variables.extend(variables),
+				ResponseUpdate::Services(Ok(services)) => {
+					self.status_message = Some(format!("Loaded {} gRPC methods", services.method_paths().len()));
+					self.grpc_services = Some(services);
+				},
+				ResponseUpdate::Done(result) => {
+					self.append_pending_chunks();
+					self.loading = false;
+					self.stop_stream = None;
+					self.stream_decoder = None;
+					let streamed = self.streaming.take();
+
+					match *result {
+						Ok(mut response) => {
+							secrets::redact_response(&mut response);
+							self.show_response(streamed.as_deref(), response);
+							self.error_message = None;
+						},
+						Err(error) => {
+							// What arrived stays in the history, marked as incomplete
+							if let Some(response) = self.streamed_response_mut(streamed.as_deref()) {
+								response.error = Some(error.clone());
+							}
+							self.error_message = Some(error);
+						},
+					}
 				},
 			}
 		}
+		self.append_pending_chunks();
+	}
+
+	/// Selects `response`, in place of the one with the `replaced` id when that's still in the history.
+	fn show_response(&mut self, replaced: Option<&str>, response: HttpResponse) {
+		let replaced = replaced.and_then(|id| self.responses.iter().position(|response| response.id == id));
+		let index = if let Some(index) = replaced {
+			self.responses[index] = response;
+			index
+		} else {
+			self.responses.push(response);
+			self.responses.len() - 1
+		};
+		self.selected_response = Some(index);
+	}
+
+	fn streamed_response_mut(&mut self, id: Option<&str>) -> Option<&mut HttpResponse> {
+		let id = id?;
+		self.responses.iter_mut().find(|response| response.id == id)
+	}
+
+	/// Drops the History entries answering the request with `request_id`, keeping the same entry selected.
+	fn remove_responses_to(&mut self, request_id: &str) {
+		let selected_id = self.get_current_response().map(|response| response.id.clone());
+		self.responses.retain(|response| response.request_id != request_id);
+		self.selected_response = self.selected_response.and_then(|_| {
+			let selected_id = selected_id.as_deref()?;
+			self.responses.iter().position(|response| response.id == selected_id)
+		});
+	}
+
+	/// Adds the chunks received since the last update at once, so a fast stream is redrawn per frame rather than per
+	/// chunk.
+	fn append_pending_chunks(&mut self) {
+		let chunks = std::mem::take(&mut self.pending_chunks);
+		let streaming = self.streaming.as_ref().filter(|_| !chunks.is_empty());
+		let Some(response) = self.responses.iter_mut().find(|response| Some(&response.id) == streaming) else {
+			return;
+		};
+
+		let transferred = chunks.iter().map(Bytes::len).sum();
+		let mut body = chunks.concat();
+		match &mut self.stream_decoder {
+			Some(decoder) => match decoder.decode(&body) {
+				Ok(text) => body = text,
+				Err(error) => {
+					tracing::warn!("Stopped decoding the streaming body: {error}");
+					self.stream_decoder = None;
+					return;
+				},
+			},
+			// Decoding failed earlier; the rest shows once the response completes and is decoded in full
+			None if response.decoded => return,
+			None => {},
+		}
+		response.append_chunk(&body, transferred);
 	}
 
 	fn clear_response(&mut self) {
+		if self.current_request.is_websocket() {
+			self.websocket_log.clear();
+			return;
+		}
+
 		self.responses.clear();
+		self.captured_requests.clear();
 		self.selected_response = None;
+		self.streaming = None;
+		self.pending_chunks.clear();
+		self.stream_decoder = None;
+	}
+
+	/// Whether `response` is the one whose body is still streaming in.
+	pub fn is_streaming(&self, response: &HttpResponse) -> bool {
+		self.streaming.as_ref() == Some(&response.id)
 	}
 
 	pub fn get_current_response(&self) -> Option<&HttpResponse> {
@@ -598,6 +1699,14 @@ impl App {
 		&self.url_textarea
 	}
 
+	pub const fn get_name_textarea(&self) -> &TextArea<'static> {
+		&self.name_textarea
+	}
+
+	pub const fn get_save_path_textarea(&self) -> &TextArea<'static> {
+		&self.save_path_textarea
+	}
+
 	pub const fn get_headers_textarea(&self) -> &TextArea<'static> {
 		&self.headers_textarea
 	}
@@ -606,10 +1715,26 @@ impl App {
 		&self.body_textarea
 	}
 
+	pub const fn get_graphql_variables_textarea(&self) -> &TextArea<'static> {
+		&self.graphql_variables_textarea
+	}
+
 	pub const fn get_queries_textarea(&self) -> &TextArea<'static> {
 		&self.queries_textarea
 	}
 
+	pub const fn get_script_textarea(&self) -> &TextArea<'static> {
+		&self.script_textarea
+	}
+
+	pub const fn get_auth_textarea(&self) -> &TextArea<'static> {
+		&self.auth_textarea
+	}
+
+	pub const fn get_example_textarea(&self) -> &TextArea<'static> {
+		&self.example_textarea
+	}
+
 	pub const fn get_response_body_textarea(&self) -> &TextArea<'static> {
 		&self.response_body_textarea
 	}
@@ -618,3 +1743,14 @@ impl App {
 		&self.response_headers_textarea
 	}
 }
+
+/// Names the credentials typed into `requests` rather than referring to secret variables, since they're written in clear.
+fn plain_credentials_warning(requests: &[HttpRequest]) -> String {
+	let mut credentials: Vec<String> = requests.iter().flat_map(secrets::plain_credentials).collect();
+	credentials.sort();
+	credentials.dedup();
+	if credentials.is_empty() {
+		return String::new();
+	}
+	format!("; {} written in clear, use a secret variable to keep it out of the file", credentials.join(", "))
+}
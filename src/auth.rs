@@ -0,0 +1,610 @@
+// see https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_sigv-create-signed-request.html
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use hyper::HeaderMap;
+use hyper::header::{self, HeaderName, HeaderValue};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Sha512};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use url::{Position, Url};
+
+use crate::utils::dynamic_variables::{base64, hex, sha256_hex};
+
+/// Everything but the characters `SigV4` leaves unencoded: letters, digits and `-._~`.
+const AWS_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// Headers a proxy or the client may change after signing, as botocore leaves them out.
+const AWS_UNSIGNED_HEADERS: [&str; 4] = ["authorization", "user-agent", "expect", "x-amzn-trace-id"];
+
+const DEFAULT_HMAC_HEADER: &str = "X-Signature";
+const DEFAULT_HMAC_VALUE: &str = "{signature}";
+const DEFAULT_HMAC_MESSAGE: &str = "{method}\n{path}\n{timestamp}\n{body_sha256}";
+
+#[derive(Debug)]
+pub enum AuthError {
+	UnknownType(String),
+	UnknownField(String),
+	MissingField(&'static str),
+	InvalidValue { field: &'static str, value: String },
+	UnknownPlaceholder(String),
+	InvalidHeader(String),
+}
+
+impl std::fmt::Display for AuthError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UnknownType(kind) => write!(f, "Unknown auth type {kind:?}, expected aws-sigv4 or hmac"),
+			Self::UnknownField(field) => write!(f, "Unknown auth field: {field}"),
+			Self::MissingField(field) => write!(f, "Missing auth field: {field}"),
+			Self::InvalidValue { field, value } => write!(f, "Invalid {field}: {value}"),
+			Self::UnknownPlaceholder(name) => write!(f, "Unknown placeholder {{{name}}} in the HMAC template"),
+			Self::InvalidHeader(name) => write!(f, "Invalid signature header: {name}"),
+		}
+	}
+}
+
+impl std::error::Error for AuthError {}
+
+/// Signs the request as it's about to be sent, after variables, scripts and GraphQL have shaped it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Auth {
+	#[serde(rename = "aws-sigv4")]
+	AwsSigV4(AwsSigV4),
+	#[serde(rename = "hmac")]
+	Hmac(HmacAuth),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AwsSigV4 {
+	pub access_key: String,
+	pub secret_key: String,
+	/// For temporary credentials, sent as `X-Amz-Security-Token`
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub session_token: String,
+	pub region: String,
+	/// e.g. `execute-api` or `s3`
+	pub service: String,
+}
+
+/// A keyed hash of a message built from the request, sent in a header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HmacAuth {
+	pub secret: String,
+	#[serde(default)]
+	pub algorithm: HmacAlgorithm,
+	#[serde(default)]
+	pub encoding: SignatureEncoding,
+	/// Header the signature is sent in
+	#[serde(default = "default_hmac_header")]
+	pub header: String,
+	/// Template for the header value, e.g. `HMAC key-id:{signature}`
+	#[serde(default = "default_hmac_value")]
+	pub value: String,
+	/// Template for the signed message; see [`render`] for the placeholders
+	#[serde(default = "default_hmac_message")]
+	pub message: String,
+	/// Header to send `{timestamp}` in, so the server can check the message
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub timestamp_header: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HmacAlgorithm {
+	#[default]
+	Sha256,
+	Sha512,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureEncoding {
+	#[default]
+	Hex,
+	Base64,
+}
+
+fn default_hmac_header() -> String {
+	String::from(DEFAULT_HMAC_HEADER)
+}
+
+fn default_hmac_value() -> String {
+	String::from(DEFAULT_HMAC_VALUE)
+}
+
+fn default_hmac_message() -> String {
+	String::from(DEFAULT_HMAC_MESSAGE)
+}
+
+impl Auth {
+	/// Reads the `key: value` lines of the Auth tab; `None` when there are none. `\n` in values stands for a newline.
+	pub fn from_form(text: &str) -> anyhow::Result<Option<Self>, AuthError> {
+		let mut fields: HashMap<&str, String> = HashMap::new();
+		for line in text.lines().filter(|line| !line.trim().is_empty()) {
+			let (key, value) = line.split_once(':').ok_or_else(|| AuthError::UnknownField(line.trim().to_string()))?;
+			fields.insert(key.trim(), value.trim().replace("\\n", "\n"));
+		}
+		if fields.is_empty() {
+			return Ok(None);
+		}
+
+		let kind = fields.remove("type").ok_or(AuthError::MissingField("type"))?;
+		let mut take = |field: &'static str| fields.remove(field).filter(|value| !value.is_empty());
+		let auth = match kind.as_str() {
+			"aws-sigv4" => Self::AwsSigV4(AwsSigV4 {
+				access_key: take("access_key").ok_or(AuthError::MissingField("access_key"))?,
+				secret_key: take("secret_key").ok_or(AuthError::MissingField("secret_key"))?,
+				session_token: take("session_token").unwrap_or_default(),
+				region: take("region").ok_or(AuthError::MissingField("region"))?,
+				service: take("service").ok_or(AuthError::MissingField("service"))?,
+			}),
+			"hmac" => Self::Hmac(HmacAuth {
+				secret: take("secret").ok_or(AuthError::MissingField("secret"))?,
+				algorithm: match take("algorithm").as_deref() {
+					None | Some("sha256") => HmacAlgorithm::Sha256,
+					Some("sha512") => HmacAlgorithm::Sha512,
+					Some(other) => return Err(AuthError::InvalidValue { field: "algorithm", value: other.to_string() }),
+				},
+				encoding: match take("encoding").as_deref() {
+					None | Some("hex") => SignatureEncoding::Hex,
+					Some("base64") => SignatureEncoding::Base64,
+					Some(other) => return Err(AuthError::InvalidValue { field: "encoding", value: other.to_string() }),
+				},
+				header: take("header").unwrap_or_else(default_hmac_header),
+				value: take("value").unwrap_or_else(default_hmac_value),
+				message: take("message").unwrap_or_else(default_hmac_message),
+				timestamp_header: take("timestamp_header").unwrap_or_default(),
+			}),
+			other => return Err(AuthError::UnknownType(other.to_string())),
+		};
+
+		fields.into_keys().next().map_or(Ok(Some(auth)), |field| Err(AuthError::UnknownField(field.to_string())))
+	}
+
+	/// The `key: value` lines [`Self::from_form`] reads.
+	pub fn to_form(&self) -> String {
+		let fields: Vec<(&str, String)> = match self {
+			Self::AwsSigV4(aws) => vec![
+				("type", String::from("aws-sigv4")),
+				("access_key", aws.access_key.clone()),
+				("secret_key", aws.secret_key.clone()),
+				("session_token", aws.session_token.clone()),
+				("region", aws.region.clone()),
+				("service", aws.service.clone()),
+			],
+			Self::Hmac(hmac) => vec![
+				("type", String::from("hmac")),
+				("secret", hmac.secret.clone()),
+				("algorithm", String::from(if hmac.algorithm == HmacAlgorithm::Sha512 { "sha512" } else { "sha256" })),
+				("encoding", String::from(if hmac.encoding == SignatureEncoding::Base64 { "base64" } else { "hex" })),
+				("header", hmac.header.clone()),
+				("value", hmac.value.clone()),
+				("message", hmac.message.replace('\n', "\\n")),
+				("timestamp_header", hmac.timestamp_header.clone()),
+			],
+		};
+
+		fields
+			.into_iter()
+			.filter(|(key, value)| *key != "session_token" && *key != "timestamp_header" || !value.is_empty())
+			.map(|(key, value)| format!("{key}: {value}"))
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+
+	/// Returns a copy with `substitute` applied to every field, e.g. to resolve `{{variables}}`.
+	pub fn map_values(&self, substitute: impl Fn(&str) -> String) -> Self {
+		match self {
+			Self::AwsSigV4(aws) => Self::AwsSigV4(AwsSigV4 {
+				access_key: substitute(&aws.access_key),
+				secret_key: substitute(&aws.secret_key),
+				session_token: substitute(&aws.session_token),
+				region: substitute(&aws.region),
+				service: substitute(&aws.service),
+			}),
+			Self::Hmac(hmac) => Self::Hmac(HmacAuth {
+				secret: substitute(&hmac.secret),
+				header: substitute(&hmac.header),
+				value: substitute(&hmac.value),
+				message: substitute(&hmac.message),
+				timestamp_header: substitute(&hmac.timestamp_header),
+				..hmac.clone()
+			}),
+		}
+	}
+
+	/// Adds the signature headers for `method`, `url`, the headers so far and `body`, as of `now`.
+	pub fn sign(
+		&self,
+		method: &str,
+		url: &Url,
+		headers: &mut HeaderMap,
+		body: &[u8],
+		now: DateTime<Utc>,
+	) -> anyhow::Result<(), AuthError> {
+		match self {
+			Self::AwsSigV4(aws) => aws.sign(method, url, headers, body, now),
+			Self::Hmac(hmac) => hmac.sign(method, url, headers, body, now),
+		}
+	}
+}
+
+impl AwsSigV4 {
+	fn sign(
+		&self,
+		method: &str,
+		url: &Url,
+		headers: &mut HeaderMap,
+		body: &[u8],
+		now: DateTime<Utc>,
+	) -> anyhow::Result<(), AuthError> {
+		let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+		let date = now.format("%Y%m%d").to_string();
+		let payload_hash = sha256_hex(body);
+
+		insert_header(headers, "x-amz-date", &amz_date)?;
+		if !self.session_token.is_empty() {
+			insert_header(headers, "x-amz-security-token", &self.session_token)?;
+		}
+		if self.service == "s3" {
+			insert_header(headers, "x-amz-content-sha256", &payload_hash)?;
+		}
+
+		let canonical_headers = self.canonical_headers(url, headers);
+		let signed_headers = canonical_headers.keys().map(String::as_str).collect::<Vec<_>>().join(";");
+		let canonical_request = format!(
+			"{method}\n{}\n{}\n{}\n{signed_headers}\n{payload_hash}",
+			self.canonical_uri(url),
+			canonical_query(url),
+			canonical_headers.iter().fold(String::new(), |mut lines, (name, value)| {
+				let _ = writeln!(lines, "{name}:{value}");
+				lines
+			}),
+		);
+
+		let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+		let string_to_sign = format!("AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}", sha256_hex(canonical_request.as_bytes()));
+		let signing_key = [self.region.as_str(), self.service.as_str(), "aws4_request"]
+			.iter()
+			.fold(hmac_sha256(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes()), |key, part| {
+				hmac_sha256(&key, part.as_bytes())
+			});
+		let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+		insert_header(
+			headers,
+			header::AUTHORIZATION.as_str(),
+			&format!(
+				"AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+				self.access_key
+			),
+		)
+	}
+
+	/// Each path segment encoded again, except for S3, which signs the path as sent.
+	fn canonical_uri(&self, url: &Url) -> String {
+		let path = url.path();
+		if path.is_empty() {
+			return String::from("/");
+		}
+		if self.service == "s3" {
+			return path
+				.split('/')
+				.map(|segment| {
+					utf8_percent_encode(&percent_decode_str(segment).decode_utf8_lossy(), AWS_ENCODE_SET).to_string()
+				})
+				.collect::<Vec<_>>()
+				.join("/");
+		}
+		path
+			.split('/')
+			.map(|segment| utf8_percent_encode(segment, AWS_ENCODE_SET).to_string())
+			.collect::<Vec<_>>()
+			.join("/")
+	}
+
+	/// Lowercase names with trimmed values, sorted; `host` always comes from the URL as HTTP/2 sends no header.
+	fn canonical_headers(&self, url: &Url, headers: &HeaderMap) -> BTreeMap<String, String> {
+		let mut canonical =
+			BTreeMap::from([(String::from("host"), url[Position::BeforeHost..Position::BeforePath].to_string())]);
+		for name in headers.keys() {
+			if name == header::HOST || AWS_UNSIGNED_HEADERS.contains(&name.as_str()) {
+				continue;
+			}
+			let value = headers
+				.get_all(name)
+				.iter()
+				.map(|value| String::from_utf8_lossy(value.as_bytes()).split_whitespace().collect::<Vec<_>>().join(" "))
+				.collect::<Vec<_>>()
+				.join(",");
+			canonical.insert(name.as_str().to_string(), value);
+		}
+		canonical
+	}
+}
+
+/// Query parameters decoded, encoded the `SigV4` way and sorted by name, then value.
+fn canonical_query(url: &Url) -> String {
+	let encode =
+		|text: &str| utf8_percent_encode(&percent_decode_str(text).decode_utf8_lossy(), AWS_ENCODE_SET).to_string();
+	let mut parameters: Vec<(String, String)> = url
+		.query()
+		.unwrap_or_default()
+		.split('&')
+		.filter(|pair| !pair.is_empty())
+		.map(|pair| {
+			let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+			(encode(name), encode(value))
+		})
+		.collect();
+	parameters.sort();
+	parameters.iter().map(|(name, value)| format!("{name}={value}")).collect::<Vec<_>>().join("&")
+}
+
+impl HmacAuth {
+	fn sign(
+		&self,
+		method: &str,
+		url: &Url,
+		headers: &mut HeaderMap,
+		body: &[u8],
+		now: DateTime<Utc>,
+	) -> anyhow::Result<(), AuthError> {
+		let timestamp = now.timestamp().to_string();
+		if !self.timestamp_header.is_empty() {
+			insert_header(headers, &self.timestamp_header, &timestamp)?;
+		}
+
+		let request = TemplateRequest { method, url, headers, body, timestamp: &timestamp };
+		let message = render(&self.message, &request, None)?;
+		let signature = match self.algorithm {
+			HmacAlgorithm::Sha256 => hmac_sha256(self.secret.as_bytes(), message.as_bytes()),
+			HmacAlgorithm::Sha512 => {
+				let mut mac = Hmac::<Sha512>::new_from_slice(self.secret.as_bytes()).unwrap_or_else(|_| unreachable!());
+				mac.update(message.as_bytes());
+				mac.finalize().into_bytes().to_vec()
+			},
+		};
+		let signature = match self.encoding {
+			SignatureEncoding::Hex => hex(&signature),
+			SignatureEncoding::Base64 => base64(&signature),
+		};
+
+		let value = render(&self.value, &request, Some(&signature))?;
+		insert_header(headers, &self.header, &value)
+	}
+}
+
+struct TemplateRequest<'a> {
+	method: &'a str,
+	url: &'a Url,
+	headers: &'a HeaderMap,
+	body: &'a [u8],
+	timestamp: &'a str,
+}
+
+/// Fills in `{method}`, `{url}`, `{host}`, `{path}` (with the query), `{timestamp}` (Unix seconds), `{body}`,
+/// `{body_sha256}`, `{header:Name}` and, in the header value, `{signature}`.
+fn render(template: &str, request: &TemplateRequest, signature: Option<&str>) -> anyhow::Result<String, AuthError> {
+	let mut result = String::with_capacity(template.len());
+	let mut rest = template;
+
+	while let Some(start) = rest.find('{') {
+		let Some(end) = rest[start..].find('}') else {
+			break;
+		};
+		let name = &rest[start + 1..start + end];
+		let value = match name {
+			"method" => request.method.to_string(),
+			"url" => request.url.to_string(),
+			"host" => request.url[Position::BeforeHost..Position::BeforePath].to_string(),
+			"path" => request.url[Position::BeforePath..Position::AfterQuery].to_string(),
+			"timestamp" => request.timestamp.to_string(),
+			"body" => String::from_utf8_lossy(request.body).into_owned(),
+			"body_sha256" => sha256_hex(request.body),
+			"signature" if signature.is_some() => signature.unwrap_or_default().to_string(),
+			_ => match name.strip_prefix("header:") {
+				Some(header) => request
+					.headers
+					.get(header.trim())
+					.map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+					.unwrap_or_default(),
+				None => return Err(AuthError::UnknownPlaceholder(name.to_string())),
+			},
+		};
+
+		result.push_str(&rest[..start]);
+		result.push_str(&value);
+		rest = &rest[start + end + 1..];
+	}
+
+	result.push_str(rest);
+	Ok(result)
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+	// HMAC takes keys of any length
+	let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap_or_else(|_| unreachable!());
+	mac.update(data);
+	mac.finalize().into_bytes().to_vec()
+}
+
+fn insert_header(headers: &mut HeaderMap, name: &str, value: &str) -> anyhow::Result<(), AuthError> {
+	let invalid = || AuthError::InvalidHeader(name.to_string());
+	let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?;
+	let value = HeaderValue::from_str(value).map_err(|_| invalid())?;
+	headers.insert(name, value);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+	use crate::http_client::request_url;
+	use crate::request::HttpRequest;
+
+	/// Credentials and time of the AWS Signature Version 4 test suite.
+	fn aws(service: &str) -> Auth {
+		Auth::AwsSigV4(AwsSigV4 {
+			access_key: String::from("AKIDEXAMPLE"),
+			secret_key: String::from("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"),
+			session_token: String::new(),
+			region: String::from("us-east-1"),
+			service: service.to_string(),
+		})
+	}
+
+	fn test_suite_time() -> DateTime<Utc> {
+		Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap()
+	}
+
+	fn authorization(headers: &HeaderMap) -> &str {
+		headers[header::AUTHORIZATION].to_str().unwrap()
+	}
+
+	#[test]
+	fn test_aws_test_suite_vanilla_requests() {
+		let url = Url::parse("https://example.amazonaws.com/").unwrap();
+
+		let mut headers = HeaderMap::new();
+		aws("service").sign("GET", &url, &mut headers, b"", test_suite_time()).unwrap();
+		assert_eq!(headers["x-amz-date"], "20150830T123600Z");
+		assert_eq!(
+			authorization(&headers),
+			"AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+			 SignedHeaders=host;x-amz-date, Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+		);
+
+		let mut headers = HeaderMap::new();
+		aws("service").sign("POST", &url, &mut headers, b"", test_suite_time()).unwrap();
+		assert!(
+			authorization(&headers).ends_with("Signature=5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b")
+		);
+	}
+
+	#[test]
+	fn test_aws_documentation_example() {
+		let url = Url::parse("https://iam.amazonaws.com/?Version=2010-05-08&Action=ListUsers").unwrap();
+		let mut headers = HeaderMap::new();
+		headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/x-www-form-urlencoded; charset=utf-8"));
+		headers.insert(header::USER_AGENT, HeaderValue::from_static("resto"));
+
+		aws("iam").sign("GET", &url, &mut headers, b"", test_suite_time()).unwrap();
+
+		assert_eq!(
+			authorization(&headers),
+			"AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+			 SignedHeaders=content-type;host;x-amz-date, \
+			 Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+		);
+	}
+
+	#[test]
+	fn test_aws_session_token_and_s3() {
+		let Auth::AwsSigV4(mut credentials) = aws("s3") else { unreachable!() };
+		credentials.session_token = String::from("token");
+		let url = Url::parse("https://bucket.s3.amazonaws.com/my%20file.txt").unwrap();
+		let mut headers = HeaderMap::new();
+
+		Auth::AwsSigV4(credentials.clone()).sign("PUT", &url, &mut headers, b"hello", test_suite_time()).unwrap();
+
+		assert_eq!(headers["x-amz-security-token"], "token");
+		assert_eq!(headers["x-amz-content-sha256"], sha256_hex(b"hello"));
+		assert!(
+			authorization(&headers).contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date;x-amz-security-token,")
+		);
+		assert_eq!(credentials.canonical_uri(&url), "/my%20file.txt");
+		credentials.service = String::from("execute-api");
+		assert_eq!(credentials.canonical_uri(&url), "/my%2520file.txt");
+		assert_eq!(
+			canonical_query(&Url::parse("https://example.com/?b=2&a=x%20y&a=1&flag").unwrap()),
+			"a=1&a=x%20y&b=2&flag="
+		);
+	}
+
+	#[test]
+	fn test_aws_query_with_spaces() {
+		let mut request = HttpRequest::new();
+		request.url = String::from("https://example.amazonaws.com/?Param1=value1");
+		request.add_query(String::from("Param2"), String::from("value 2"));
+		let url = request_url(&request).unwrap();
+		assert_eq!(url.query(), Some("Param1=value1&Param2=value%202"));
+		assert_eq!(canonical_query(&url), "Param1=value1&Param2=value%202");
+
+		// Computed independently following the SigV4 steps, with the space canonicalized as `%20`
+		let mut headers = HeaderMap::new();
+		aws("service").sign("GET", &url, &mut headers, b"", test_suite_time()).unwrap();
+		assert!(
+			authorization(&headers).ends_with("Signature=a5ee08612741b49b526844b3c0c6966bf8685384dd254fe0d6260c1529fb0386")
+		);
+	}
+
+	#[test]
+	fn test_hmac_signatures() {
+		// RFC 4231 test case 2
+		let mut hmac = HmacAuth {
+			secret: String::from("Jefe"),
+			algorithm: HmacAlgorithm::Sha256,
+			encoding: SignatureEncoding::Hex,
+			header: default_hmac_header(),
+			value: default_hmac_value(),
+			message: String::from("what do ya want for nothing?"),
+			timestamp_header: String::new(),
+		};
+		let url = Url::parse("https://api.example.com/orders?page=2").unwrap();
+
+		let mut headers = HeaderMap::new();
+		Auth::Hmac(hmac.clone()).sign("POST", &url, &mut headers, b"{}", test_suite_time()).unwrap();
+		assert_eq!(headers["x-signature"], "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+
+		hmac.algorithm = HmacAlgorithm::Sha512;
+		let mut headers = HeaderMap::new();
+		Auth::Hmac(hmac.clone()).sign("POST", &url, &mut headers, b"{}", test_suite_time()).unwrap();
+		assert_eq!(
+			headers["x-signature"],
+			"164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+		);
+
+		hmac.algorithm = HmacAlgorithm::Sha256;
+		hmac.encoding = SignatureEncoding::Base64;
+		hmac.header = String::from("Authorization");
+		hmac.value = String::from("HMAC key-1:{timestamp}:{signature}");
+		hmac.message = String::from("{method} {path} {host}\n{timestamp}\n{header:x-timestamp}\n{body}");
+		hmac.timestamp_header = String::from("X-Timestamp");
+		let mut headers = HeaderMap::new();
+		Auth::Hmac(hmac.clone()).sign("POST", &url, &mut headers, b"{}", test_suite_time()).unwrap();
+		let message = "POST /orders?page=2 api.example.com\n1440938160\n1440938160\n{}";
+		assert_eq!(headers["x-timestamp"], "1440938160");
+		assert_eq!(
+			headers["authorization"],
+			format!("HMAC key-1:1440938160:{}", base64(&hmac_sha256(b"Jefe", message.as_bytes()))).as_str()
+		);
+
+		hmac.message = String::from("{nonce}");
+		assert!(Auth::Hmac(hmac).sign("GET", &url, &mut HeaderMap::new(), b"", test_suite_time()).is_err());
+	}
+
+	#[test]
+	fn test_form_round_trip() {
+		let form = "type: hmac\nsecret: {{hmac_secret}}\nalgorithm: sha512\nmessage: {method}\\n{path}";
+		let auth = Auth::from_form(form).unwrap().unwrap();
+		let Auth::Hmac(hmac) = &auth else { unreachable!() };
+		assert_eq!(hmac.message, "{method}\n{path}");
+		assert_eq!(hmac.header, "X-Signature");
+		assert_eq!(Auth::from_form(&auth.to_form()).unwrap(), Some(auth));
+
+		let auth = aws("execute-api");
+		assert_eq!(Auth::from_form(&auth.to_form()).unwrap(), Some(auth));
+		assert_eq!(Auth::from_form("\n  \n").unwrap(), None);
+
+		assert!(Auth::from_form("type: basic").is_err());
+		assert!(Auth::from_form("type: aws-sigv4\naccess_key: AKID").is_err());
+		assert!(Auth::from_form("type: hmac\nsecret: x\nsalt: y").is_err());
+	}
+}
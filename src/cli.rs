@@ -0,0 +1,75 @@
+use clap::{Parser, Subcommand};
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+use crate::mock::DEFAULT_PORT;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+	#[command(subcommand)]
+	pub command: Option<Command>,
+
+	/// `.http` / `.rest` files to load into the collection
+	#[arg(value_name = "FILE")]
+	pub http_files: Vec<PathBuf>,
+
+	/// Workspace directory to use instead of discovering `resto.toml` from the current directory
+	#[arg(long, value_name = "DIR")]
+	pub workspace: Option<PathBuf>,
+
+	/// Config file to use instead of `config.toml` in the config directory
+	#[arg(long, value_name = "FILE")]
+	pub config: Option<PathBuf>,
+
+	/// Directory for persistent data such as the global workspace (overrides `RESTO_DATA_DIR`)
+	#[arg(long, value_name = "DIR")]
+	pub data_dir: Option<PathBuf>,
+
+	/// Print the config, data, cache and state directories and exit
+	#[arg(long)]
+	pub print_paths: bool,
+
+	/// `OpenAPI` spec (JSON or YAML) to validate responses against
+	#[arg(long, value_name = "FILE")]
+	pub openapi: Option<PathBuf>,
+
+	/// `.proto` file describing the gRPC services to call; may be repeated
+	#[arg(long, value_name = "FILE")]
+	pub proto: Vec<PathBuf>,
+
+	/// Directory `--proto` files' imports are looked up in before the importing file's own; may be repeated
+	#[arg(long, value_name = "DIR")]
+	pub proto_path: Vec<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+	/// Manage the workspace's secret variables, kept encrypted in `secrets.enc`
+	#[command(subcommand)]
+	Secret(SecretCommand),
+	/// Serve the examples of the saved requests, matched by method and path, until interrupted
+	Mock {
+		/// `.http` / `.rest` files with more requests to serve
+		#[arg(value_name = "FILE")]
+		http_files: Vec<PathBuf>,
+
+		/// Port to listen on
+		#[arg(long, default_value_t = DEFAULT_PORT)]
+		port: u16,
+
+		/// Address to listen on, e.g. `0.0.0.0` for other devices
+		#[arg(long, default_value = "127.0.0.1")]
+		host: IpAddr,
+	},
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SecretCommand {
+	/// Set a secret variable, prompting for its value
+	Set { name: String },
+	/// Remove a secret variable
+	Remove { name: String },
+	/// List the names of the secret variables
+	List,
+}
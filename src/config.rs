@@ -0,0 +1,123 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::keymap::{KeyList, Keymap};
+use crate::paths;
+use crate::theme::{self, DEFAULT_THEME, Theme};
+
+pub const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug)]
+pub enum ConfigError {
+	InvalidFile(PathBuf, String),
+}
+
+impl std::fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::InvalidFile(path, message) => write!(f, "Invalid config file {}: {message}", path.display()),
+		}
+	}
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Contents of the user config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+	/// Action name to one key or a list of keys, e.g. `send_request = ["enter", "ctrl-s"]`
+	keys: HashMap<String, KeyList>,
+	/// Built-in or user theme name
+	theme: Option<String>,
+	/// User themes, e.g. `[themes.solarized]` with `base = "light"` and the colors to override
+	themes: toml::Table,
+	/// Port the mock server listens on when started from the TUI
+	mock_port: Option<u16>,
+	/// Port the capture proxy listens on
+	proxy_port: Option<u16>,
+	/// Largest response body downloaded, in bytes
+	max_body_size: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct Config {
+	pub keymap: Keymap,
+	pub theme: Theme,
+	pub mock_port: Option<u16>,
+	pub proxy_port: Option<u16>,
+	pub max_body_size: Option<usize>,
+}
+
+impl Config {
+	/// `config.toml` in the config directory.
+	pub fn default_path() -> PathBuf {
+		paths::config_dir().join(CONFIG_FILE)
+	}
+
+	/// Loads and validates the config file; a missing file gives the defaults.
+	/// `NO_COLOR` takes precedence over the configured theme.
+	pub fn load(path: &Path) -> anyhow::Result<Self> {
+		let content = if path.is_file() { std::fs::read_to_string(path)? } else { String::new() };
+		let mut config = Self::parse(&content).map_err(|message| ConfigError::InvalidFile(path.to_path_buf(), message))?;
+
+		if theme::no_color() {
+			config.theme = Theme::monochrome();
+		}
+
+		Ok(config)
+	}
+
+	fn parse(content: &str) -> anyhow::Result<Self, String> {
+		let file: ConfigFile = toml::from_str(content).map_err(|error| error.to_string())?;
+		let keymap = Keymap::new(&file.keys).map_err(|error| error.to_string())?;
+		let theme = Theme::resolve(file.theme.as_deref().unwrap_or(DEFAULT_THEME), &file.themes)
+			.map_err(|error| error.to_string())?;
+		Ok(Self {
+			keymap,
+			theme,
+			mock_port: file.mock_port,
+			proxy_port: file.proxy_port,
+			max_body_size: file.max_body_size,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+	use super::*;
+	use crate::keymap::Action;
+
+	#[test]
+	fn test_parse_keys() {
+		let config = Config::parse("[keys]\nquit = \"ctrl-q\"\nsend_request = [\"enter\", \"F5\"]\n").unwrap();
+
+		assert_eq!(config.keymap.action(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL)), Some(Action::Quit));
+		assert_eq!(config.keymap.action(KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE)), Some(Action::SendRequest));
+	}
+
+	#[test]
+	fn test_parse_theme() {
+		let config =
+			Config::parse("theme = \"mine\"\n\n[themes.mine]\nbase = \"high-contrast\"\nerror = \"red\"\n").unwrap();
+
+		assert_eq!(config.theme.error, ratatui::style::Color::Red);
+		assert_eq!(config.theme.accent, Theme::high_contrast().accent);
+	}
+
+	#[test]
+	fn test_parse_max_body_size() {
+		assert_eq!(Config::parse("max_body_size = 1_000_000\n").unwrap().max_body_size, Some(1_000_000));
+		assert_eq!(Config::parse("").unwrap().max_body_size, None);
+	}
+
+	#[test]
+	fn test_invalid_config() {
+		assert!(Config::parse("[keys]\nquit = \"e\"\n").unwrap_err().contains("quit, edit_request"));
+		assert!(Config::parse("colour = \"red\"\n").is_err());
+		assert!(Config::parse("theme = \"solarized\"\n").is_err());
+	}
+}
@@ -0,0 +1,167 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use x509_parser::prelude::{FromDer, GeneralName, X509Certificate};
+
+/// How a response was fetched: protocol, peer and TLS session.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+	/// e.g. `HTTP/1.1` or `HTTP/2`
+	pub http_version: String,
+	/// Resolved IP and port the request went to, the proxy's when there is one
+	pub remote_address: String,
+	/// `None` for plain HTTP
+	pub tls: Option<TlsInfo>,
+	/// The proxy it went through, from `HTTP_PROXY` and the like
+	#[serde(default)]
+	pub proxy: Option<String>,
+	/// Sent on a connection an earlier request opened, so nothing was spent connecting
+	#[serde(default)]
+	pub reused: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TlsInfo {
+	/// e.g. `TLS 1.3`
+	pub version: String,
+	/// e.g. `TLS13_AES_256_GCM_SHA384`
+	pub cipher: String,
+	/// Protocol agreed through ALPN, e.g. `h2`
+	pub alpn: Option<String>,
+	/// Server certificate first, then the intermediates it sent
+	pub certificates: Vec<CertificateInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertificateInfo {
+	pub subject: String,
+	pub issuer: String,
+	/// DNS names and IP addresses from the subject alternative name extension
+	pub subject_alt_names: Vec<String>,
+	pub not_before: DateTime<Utc>,
+	pub not_after: DateTime<Utc>,
+	/// Hex, colon separated
+	pub serial: String,
+}
+
+impl CertificateInfo {
+	/// Reads the fields we show from a DER encoded certificate.
+	pub fn parse(der: &[u8]) -> anyhow::Result<Self> {
+		let (_, certificate) = X509Certificate::from_der(der)?;
+
+		let subject_alt_names = certificate
+			.subject_alternative_name()?
+			.map(|extension| {
+				extension
+					.value
+					.general_names
+					.iter()
+					.filter_map(|name| match name {
+						GeneralName::DNSName(name) => Some((*name).to_string()),
+						GeneralName::IPAddress(bytes) => ip_address(bytes),
+						_ => None,
+					})
+					.collect()
+			})
+			.unwrap_or_default();
+
+		let validity = certificate.validity();
+		Ok(Self {
+			subject: certificate.subject().to_string(),
+			issuer: certificate.issuer().to_string(),
+			subject_alt_names,
+			not_before: DateTime::from_timestamp(validity.not_before.timestamp(), 0).unwrap_or_default(),
+			not_after: DateTime::from_timestamp(validity.not_after.timestamp(), 0).unwrap_or_default(),
+			serial: certificate.raw_serial_as_string(),
+		})
+	}
+
+	/// e.g. `expires in 42 days` or `expired 3 days ago`, relative to `now`.
+	pub fn expiry(&self, now: DateTime<Utc>) -> String {
+		let days = (self.not_after - now).num_days();
+		if now < self.not_before {
+			String::from("not valid yet")
+		} else if self.not_after < now {
+			format!("expired {} days ago", -days)
+		} else {
+			format!("expires in {days} days")
+		}
+	}
+}
+
+impl ConnectionInfo {
+	pub fn formatted(&self) -> String {
+		let mut info = format!("HTTP version   : {}\nRemote address : {}\n", self.http_version, self.remote_address);
+		if let Some(proxy) = &self.proxy {
+			let _ = writeln!(info, "Proxy          : {proxy}");
+		}
+		let _ = writeln!(info, "Connection     : {}", if self.reused { "reused" } else { "new" });
+
+		let Some(tls) = &self.tls else {
+			info.push_str("TLS            : none");
+			return info;
+		};
+
+		let _ = writeln!(info, "TLS version    : {}", tls.version);
+		let _ = writeln!(info, "Cipher         : {}", tls.cipher);
+		let _ = writeln!(info, "ALPN           : {}", tls.alpn.as_deref().unwrap_or("none"));
+
+		let now = Utc::now();
+		for (index, certificate) in tls.certificates.iter().enumerate() {
+			let _ = writeln!(info, "\nCertificate {}", index + 1);
+			let _ = writeln!(info, "  Subject    : {}", certificate.subject);
+			let _ = writeln!(info, "  Issuer     : {}", certificate.issuer);
+			if !certificate.subject_alt_names.is_empty() {
+				let _ = writeln!(info, "  SANs       : {}", certificate.subject_alt_names.join(", "));
+			}
+			let _ = writeln!(info, "  Not before : {}", certificate.not_before.format("%Y-%m-%d %H:%M:%S UTC"));
+			let _ = writeln!(
+				info,
+				"  Not after  : {} ({})",
+				certificate.not_after.format("%Y-%m-%d %H:%M:%S UTC"),
+				certificate.expiry(now)
+			);
+			let _ = writeln!(info, "  Serial     : {}", certificate.serial);
+		}
+
+		info.pop();
+		info
+	}
+}
+
+fn ip_address(bytes: &[u8]) -> Option<String> {
+	match bytes.len() {
+		4 => <[u8; 4]>::try_from(bytes).ok().map(|octets| std::net::Ipv4Addr::from(octets).to_string()),
+		16 => <[u8; 16]>::try_from(bytes).ok().map(|octets| std::net::Ipv6Addr::from(octets).to_string()),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_certificate() {
+		let certified =
+			rcgen::generate_simple_self_signed(vec![String::from("localhost"), String::from("127.0.0.1")]).unwrap();
+
+		let certificate = CertificateInfo::parse(certified.cert.der()).unwrap();
+
+		assert_eq!(certificate.subject, "CN=rcgen self signed cert");
+		assert_eq!(certificate.issuer, certificate.subject);
+		assert_eq!(certificate.subject_alt_names, vec!["localhost", "127.0.0.1"]);
+		assert!(certificate.not_before < certificate.not_after);
+		assert!(CertificateInfo::parse(b"not a certificate").is_err());
+	}
+
+	#[test]
+	fn test_expiry() {
+		let certified = rcgen::generate_simple_self_signed(vec![String::from("localhost")]).unwrap();
+		let certificate = CertificateInfo::parse(certified.cert.der()).unwrap();
+
+		assert_eq!(certificate.expiry(certificate.not_after - chrono::Duration::days(42)), "expires in 42 days");
+		assert_eq!(certificate.expiry(certificate.not_after + chrono::Duration::days(3)), "expired 3 days ago");
+		assert_eq!(certificate.expiry(certificate.not_before - chrono::Duration::days(1)), "not valid yet");
+	}
+}
@@ -1,4 +1,7 @@
-use crate::{app::HttpMethod, request::HttpRequest};
+use crate::{
+	app::{HttpMethod, HttpVersion},
+	request::HttpRequest,
+};
 
 #[derive(Debug)]
 pub enum CurlParseError {
@@ -74,28 +77,14 @@ pub fn parse_curl(input: &str) -> anyhow::Result<HttpRequest> {
 					request.set_method(HttpMethod::Post);
 				}
 			},
+			"--http1.1" => request.http_version = HttpVersion::Http1,
+			"--http2" | "--http2-prior-knowledge" => request.http_version = HttpVersion::Http2,
 			"--compressed" | "-L" | "--location" | "-k" | "--insecure" | "-s" | "--silent" | "-v" | "--verbose" => {
 				// Skip common curl flags that don't affect the HTTP request structure
 			},
 			_ => {
 				if token.starts_with("http") {
-					let url = token.clone();
-					if let Some(query_start) = url.find('?') {
-						let base_url = url[..query_start].to_string();
-						let query_str = &url[query_start + 1..];
-						for query_pair in query_str.split('&') {
-							if let Some(eq_pos) = query_pair.find('=') {
-								let key = query_pair[..eq_pos].to_string();
-								let value = query_pair[eq_pos + 1..].to_string();
-								request.add_query(key, value);
-							} else if !query_pair.is_empty() {
-								request.add_query(query_pair.to_string(), String::new());
-							}
-						}
-						request.set_url(&base_url);
-					} else {
-						request.set_url(&url);
-					}
+					request.set_url_with_queries(token);
 				}
 			},
 		}
@@ -104,6 +93,10 @@ pub fn parse_curl(input: &str) -> anyhow::Result<HttpRequest> {
 	if request.url.is_empty() {
 		return Err(CurlParseError::MissingUrl.into());
 	}
+	// curl sends `-d` data whatever the method, e.g. `-X DELETE -d ...`
+	if !request.body.is_empty() && !request.has_body() {
+		request.send_body = Some(true);
+	}
 	Ok(request)
 }
 
@@ -264,13 +257,13 @@ mod tests {
 
 		assert_eq!(
 			result.queries,
-			HashMap::from([
+			vec![
 				(String::from("name"), String::new()),
 				(String::from("ordering"), String::from("-index")),
 				(String::from("limit"), String::from("50")),
 				(String::from("offset"), String::from("0")),
 				(String::from("is_hidden"), String::from("false")),
-			])
+			]
 		);
 	}
 
@@ -298,4 +291,25 @@ mod tests {
 
 		assert_eq!(result.body, "");
 	}
+
+	#[test]
+	fn test_when_custom_method_with_body_passed() {
+		let result = parse_curl("curl -X PURGE https://cdn.example.com/assets/app.js").unwrap();
+		assert_eq!(result.method, HttpMethod::Custom(String::from("PURGE")));
+		assert!(parse_curl("curl -X 'GE(T' https://example.com").is_err());
+
+		let result = parse_curl(r#"curl -d '{"ids": [1, 2]}' -X DELETE https://api.example.com/items"#).unwrap();
+		assert_eq!(result.method, HttpMethod::Delete);
+		assert!(result.has_body());
+	}
+
+	#[test]
+	fn test_when_http_version_passed() {
+		assert_eq!(parse_curl("curl https://example.com").unwrap().http_version, HttpVersion::Negotiate);
+		assert_eq!(parse_curl("curl --http1.1 https://example.com").unwrap().http_version, HttpVersion::Http1);
+		assert_eq!(
+			parse_curl("curl --http2-prior-knowledge http://localhost:8080").unwrap().http_version,
+			HttpVersion::Http2
+		);
+	}
 }
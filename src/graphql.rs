@@ -0,0 +1,377 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+/// Asks for the root operation types and every type's fields with their (unwrapped) types.
+pub const INTROSPECTION_QUERY: &str = "query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      name
+      fields(includeDeprecated: true) { name type { ...TypeRef } }
+    }
+  }
+}
+
+fragment TypeRef on __Type {
+  name
+  ofType { name ofType { name ofType { name ofType { name } } } }
+}";
+
+/// GraphQL settings of a request; the query itself is the request body.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Graphql {
+	/// JSON object, empty for none
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub variables: String,
+	/// Operation to run when the query defines several
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub operation_name: Option<String>,
+}
+
+impl Graphql {
+	/// The `application/json` body for `query`: `{"query": ..., "variables": ..., "operationName": ...}`.
+	pub fn envelope(&self, query: &str) -> anyhow::Result<String> {
+		let mut envelope = json!({ "query": query });
+		if !self.variables.trim().is_empty() {
+			let variables: Value =
+				serde_json::from_str(&self.variables).map_err(|error| anyhow::anyhow!("Invalid GraphQL variables: {error}"))?;
+			envelope["variables"] = variables;
+		}
+		if let Some(operation_name) = &self.operation_name {
+			envelope["operationName"] = Value::String(operation_name.clone());
+		}
+		Ok(serde_json::to_string(&envelope)?)
+	}
+}
+
+/// Names of the operations defined in `query`, in order; anonymous operations have none.
+pub fn operation_names(query: &str) -> Vec<String> {
+	let tokens = tokenize(query);
+	let mut names = Vec::new();
+	let mut depth = 0;
+
+	for (index, token) in tokens.iter().enumerate() {
+		match token {
+			Token::Punctuator('{') => depth += 1,
+			Token::Punctuator('}') => depth -= 1,
+			Token::Name(keyword) if depth == 0 && matches!(*keyword, "query" | "mutation" | "subscription") => {
+				if let Some(Token::Name(name)) = tokens.get(index + 1) {
+					names.push((*name).to_string());
+				}
+			},
+			_ => {},
+		}
+	}
+
+	names
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+	Name(&'a str),
+	Punctuator(char),
+}
+
+/// Names and the punctuators that matter for completion; strings, comments, variables and numbers are dropped.
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+	let mut tokens = Vec::new();
+	let mut chars = text.char_indices().peekable();
+
+	while let Some((start, ch)) = chars.next() {
+		match ch {
+			'#' => while chars.next_if(|(_, ch)| *ch != '\n').is_some() {},
+			'"' => {
+				let mut escaped = false;
+				for (_, ch) in chars.by_ref() {
+					match ch {
+						'\\' if !escaped => escaped = true,
+						'"' if !escaped => break,
+						_ => escaped = false,
+					}
+				}
+			},
+			'$' | '@' | '0'..='9' | '-' => {
+				// Variables, directives and numbers aren't fields
+				while chars.next_if(|(_, ch)| ch.is_ascii_alphanumeric() || *ch == '_' || *ch == '.').is_some() {}
+			},
+			'{' | '}' | '(' | ')' | ':' => tokens.push(Token::Punctuator(ch)),
+			ch if ch.is_ascii_alphabetic() || ch == '_' => {
+				let mut end = start + 1;
+				while let Some((index, _)) = chars.next_if(|(_, ch)| ch.is_ascii_alphanumeric() || *ch == '_') {
+					end = index + 1;
+				}
+				tokens.push(Token::Name(&text[start..end]));
+			},
+			_ => {},
+		}
+	}
+
+	tokens
+}
+
+/// Object and interface types with their fields, from an introspection result.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+	pub query_type: Option<String>,
+	pub mutation_type: Option<String>,
+	pub subscription_type: Option<String>,
+	/// Type name → field name → the field's named type, with lists and non-null unwrapped
+	pub types: HashMap<String, HashMap<String, String>>,
+}
+
+impl Schema {
+	/// Reads the response to [`INTROSPECTION_QUERY`].
+	pub fn from_introspection(body: &str) -> anyhow::Result<Self> {
+		let result: Value = serde_json::from_str(body)?;
+		if let Some(error) = result["errors"].as_array().and_then(|errors| errors.first()) {
+			anyhow::bail!("Introspection failed: {}", error["message"].as_str().unwrap_or("unknown error"));
+		}
+		let schema = &result["data"]["__schema"];
+		let Some(types) = schema["types"].as_array() else {
+			anyhow::bail!("Introspection returned no schema");
+		};
+
+		let root_type = |name: &str| schema[name]["name"].as_str().map(ToString::to_string);
+		let types = types
+			.iter()
+			.filter_map(|schema_type| {
+				let fields = schema_type["fields"].as_array()?;
+				let fields = fields
+					.iter()
+					.filter_map(|field| Some((field["name"].as_str()?.to_string(), named_type(&field["type"])?)))
+					.collect();
+				Some((schema_type["name"].as_str()?.to_string(), fields))
+			})
+			.collect();
+
+		Ok(Self {
+			query_type: root_type("queryType"),
+			mutation_type: root_type("mutationType"),
+			subscription_type: root_type("subscriptionType"),
+			types,
+		})
+	}
+
+	/// Fields that complete the name being typed at byte offset `cursor` of `query`, sorted.
+	pub fn completions(&self, query: &str, cursor: usize) -> Vec<String> {
+		let before = &query[..cursor];
+		let prefix_start = before.trim_end_matches(|ch: char| ch.is_ascii_alphanumeric() || ch == '_').len();
+		let prefix = &before[prefix_start..];
+
+		let Some(fields) = self.type_at(&before[..prefix_start]).and_then(|type_name| self.types.get(type_name)) else {
+			return Vec::new();
+		};
+
+		let mut completions: Vec<String> = fields
+			.keys()
+			.map(String::as_str)
+			.chain(["__typename"])
+			.filter(|field| field.starts_with(prefix) && *field != prefix)
+			.map(ToString::to_string)
+			.collect();
+		completions.sort();
+		completions
+	}
+
+	/// The type whose selection set `text` ends inside, following fields from the operation's root type.
+	fn type_at(&self, text: &str) -> Option<&str> {
+		let tokens = tokenize(text);
+		let mut stack: Vec<Option<&str>> = Vec::new();
+		let mut keyword: Option<&str> = None;
+		let mut last_name: Option<&str> = None;
+		let mut after_on = false;
+		let mut type_condition: Option<&str> = None;
+		let mut paren_depth = 0;
+
+		for token in tokens {
+			match token {
+				Token::Punctuator('(') => paren_depth += 1,
+				Token::Punctuator(')') => paren_depth -= 1,
+				_ if paren_depth > 0 => {},
+				Token::Name(name) => {
+					if stack.is_empty() && keyword.is_none() {
+						keyword = Some(name);
+					}
+					if after_on {
+						type_condition = Some(name);
+					}
+					after_on = name == "on";
+					last_name = Some(name);
+				},
+				Token::Punctuator('{') => {
+					let next = match (type_condition.take(), stack.last()) {
+						// `fragment X on Type {` or `... on Type {`
+						(Some(type_name), _) => self.types.get_key_value(type_name).map(|(name, _)| name.as_str()),
+						(None, None) => match keyword {
+							Some("mutation") => self.mutation_type.as_deref(),
+							Some("subscription") => self.subscription_type.as_deref(),
+							_ => self.query_type.as_deref(),
+						},
+						(None, Some(current)) => last_name.zip(*current).and_then(|(field, current)| {
+							self.types.get(current).and_then(|fields| fields.get(field)).map(String::as_str)
+						}),
+					};
+					stack.push(next);
+					last_name = None;
+				},
+				Token::Punctuator('}') => {
+					stack.pop();
+					if stack.is_empty() {
+						keyword = None;
+					}
+					last_name = None;
+				},
+				Token::Punctuator(_) => {},
+			}
+		}
+
+		stack.last().copied().flatten()
+	}
+}
+
+/// `User` for `[User!]!`: the name at the bottom of `NON_NULL` and `LIST` wrappers.
+fn named_type(type_ref: &Value) -> Option<String> {
+	let mut type_ref = type_ref;
+	loop {
+		if let Some(name) = type_ref["name"].as_str() {
+			return Some(name.to_string());
+		}
+		type_ref = type_ref.get("ofType").filter(|of_type| !of_type.is_null())?;
+	}
+}
+
+/// An entry of a GraphQL response's `errors`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphqlError {
+	pub message: String,
+	/// e.g. `user.posts.0.title`
+	pub path: Option<String>,
+	/// `line:column` positions in the query
+	pub locations: Vec<String>,
+}
+
+/// The errors of a GraphQL response (`{"data": ..., "errors": [...]}`); `None` when `body` isn't one.
+pub fn response_errors(body: &str) -> Option<Vec<GraphqlError>> {
+	let result: Value = serde_json::from_str(body).ok()?;
+	let object = result.as_object()?;
+	let is_graphql = (object.contains_key("data") || object.contains_key("errors"))
+		&& object.keys().all(|key| matches!(key.as_str(), "data" | "errors" | "extensions"));
+	if !is_graphql {
+		return None;
+	}
+
+	let errors = object.get("errors").and_then(Value::as_array).map_or_else(Vec::new, |errors| {
+		errors
+			.iter()
+			.map(|error| GraphqlError {
+				message: error["message"].as_str().unwrap_or("unknown error").to_string(),
+				path: error["path"].as_array().map(|path| {
+					path
+						.iter()
+						.map(|segment| segment.as_str().map_or_else(|| segment.to_string(), ToString::to_string))
+						.collect::<Vec<_>>()
+						.join(".")
+				}),
+				locations: error["locations"].as_array().map_or_else(Vec::new, |locations| {
+					locations.iter().map(|location| format!("{}:{}", location["line"], location["column"])).collect()
+				}),
+			})
+			.collect()
+	});
+	Some(errors)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn schema() -> Schema {
+		let introspection = json!({
+			"data": {
+				"__schema": {
+					"queryType": { "name": "Query" },
+					"mutationType": { "name": "Mutation" },
+					"subscriptionType": null,
+					"types": [
+						{ "name": "Query", "fields": [
+							{ "name": "user", "type": { "name": "User", "ofType": null } },
+							{ "name": "users", "type": { "name": null, "ofType": { "name": null, "ofType": { "name": "User" } } } },
+						] },
+						{ "name": "Mutation", "fields": [{ "name": "createUser", "type": { "name": "User" } }] },
+						{ "name": "User", "fields": [
+							{ "name": "name", "type": { "name": "String" } },
+							{ "name": "nickname", "type": { "name": "String" } },
+							{ "name": "friends", "type": { "name": null, "ofType": { "name": "User" } } },
+						] },
+						{ "name": "String", "fields": null },
+					],
+				},
+			},
+		});
+		Schema::from_introspection(&introspection.to_string()).unwrap()
+	}
+
+	fn completions(schema: &Schema, query_with_cursor: &str) -> Vec<String> {
+		let cursor = query_with_cursor.find('|').unwrap();
+		schema.completions(&query_with_cursor.replace('|', ""), cursor)
+	}
+
+	#[test]
+	fn test_envelope() {
+		let graphql = Graphql { variables: String::from("{\"id\": 1}"), operation_name: Some(String::from("GetUser")) };
+		let envelope: Value = serde_json::from_str(&graphql.envelope("query GetUser { user { name } }").unwrap()).unwrap();
+		assert_eq!(
+			envelope,
+			json!({ "query": "query GetUser { user { name } }", "variables": { "id": 1 }, "operationName": "GetUser" })
+		);
+
+		assert_eq!(Graphql::default().envelope("{ a }").unwrap(), r#"{"query":"{ a }"}"#);
+		assert!(Graphql { variables: String::from("{"), ..Graphql::default() }.envelope("{ a }").is_err());
+	}
+
+	#[test]
+	fn test_operation_names() {
+		let query = "query GetUser($id: ID!) { user(id: $id) { name } }\n# query Commented\nmutation { a }\nsubscription OnEvent { b }";
+		assert_eq!(operation_names(query), vec!["GetUser", "OnEvent"]);
+	}
+
+	#[test]
+	fn test_completions_follow_the_selection_path() {
+		let schema = schema();
+		assert_eq!(schema.types["Query"]["users"], "User");
+
+		assert_eq!(completions(&schema, "{ us| }"), vec!["user", "users"]);
+		assert_eq!(
+			completions(&schema, "query Q($id: ID) { user(id: $id, name: \"{\") { n| } }"),
+			vec!["name", "nickname"]
+		);
+		assert_eq!(completions(&schema, "{ me: user { friends { name } friends { nick| } } }"), vec!["nickname"]);
+		assert_eq!(completions(&schema, "mutation { cr| }"), vec!["createUser"]);
+		assert_eq!(completions(&schema, "fragment F on User { __| }"), vec!["__typename"]);
+		assert!(completions(&schema, "{ unknown { na| } }").is_empty());
+		assert!(completions(&schema, "que|").is_empty());
+	}
+
+	#[test]
+	fn test_response_errors() {
+		let body = json!({
+			"data": { "user": null },
+			"errors": [{ "message": "Not found", "path": ["user", 0, "name"], "locations": [{ "line": 1, "column": 3 }] }],
+		});
+		assert_eq!(
+			response_errors(&body.to_string()),
+			Some(vec![GraphqlError {
+				message: String::from("Not found"),
+				path: Some(String::from("user.0.name")),
+				locations: vec![String::from("1:3")],
+			}])
+		);
+
+		assert_eq!(response_errors(r#"{"data": {"user": {"name": "Joe"}}}"#), Some(Vec::new()));
+		assert_eq!(response_errors(r#"{"data": 1, "id": 2}"#), None);
+		assert_eq!(response_errors("not json"), None);
+	}
+}
@@ -0,0 +1,918 @@
+// see https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-HTTP2.md and
+// https://github.com/grpc/grpc/blob/master/doc/server-reflection.md
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderName, HeaderValue};
+use hyper::{Request, Version};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage, MethodDescriptor, SerializeOptions};
+use prost_types::FileDescriptorProto;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Instant;
+use tokio::sync::Notify;
+use url::Url;
+
+use crate::app::HttpVersion;
+use crate::connection::{ConnectionInfo, TlsInfo};
+use crate::http_client::{HttpClient, HttpClientError, Progress, TIMEOUT, spawn_connection};
+use crate::request::HttpRequest;
+use crate::response::{HttpResponse, SentRequest};
+use crate::timing::Timings;
+
+const REFLECTION_SERVICES: [&str; 2] =
+	["grpc.reflection.v1.ServerReflection", "grpc.reflection.v1alpha.ServerReflection"];
+
+/// Names of the status codes, indexed by code.
+const STATUS_NAMES: [&str; 17] = [
+	"OK",
+	"CANCELLED",
+	"UNKNOWN",
+	"INVALID_ARGUMENT",
+	"DEADLINE_EXCEEDED",
+	"NOT_FOUND",
+	"ALREADY_EXISTS",
+	"PERMISSION_DENIED",
+	"RESOURCE_EXHAUSTED",
+	"FAILED_PRECONDITION",
+	"ABORTED",
+	"OUT_OF_RANGE",
+	"UNIMPLEMENTED",
+	"INTERNAL",
+	"UNAVAILABLE",
+	"DATA_LOSS",
+	"UNAUTHENTICATED",
+];
+const UNIMPLEMENTED: u32 = 12;
+
+#[derive(Debug)]
+pub enum GrpcError {
+	InvalidUrl(String),
+	NoServices,
+	UnknownMethod(String),
+	InvalidMessage(String),
+	CompressedMessage,
+	Status { code: u32, message: String },
+	Reflection(String),
+}
+
+impl std::fmt::Display for GrpcError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::InvalidUrl(url) => write!(f, "Invalid gRPC URL, expected grpc://host:port/package.Service/Method: {url}"),
+			Self::NoServices => write!(f, "No gRPC services loaded: start with --proto FILE or load them by reflection"),
+			Self::UnknownMethod(method) => write!(f, "Unknown gRPC method: {method}"),
+			Self::InvalidMessage(message) => write!(f, "Invalid request message: {message}"),
+			Self::CompressedMessage => write!(f, "The server sent a compressed message, which isn't supported"),
+			Self::Status { code, message } => write!(f, "{} {message}", status_name(*code)),
+			Self::Reflection(message) => write!(f, "Server reflection failed: {message}"),
+		}
+	}
+}
+
+impl std::error::Error for GrpcError {}
+
+/// Services and message types known from `.proto` files or server reflection.
+#[derive(Debug, Clone)]
+pub struct Services {
+	pool: DescriptorPool,
+}
+
+impl Services {
+	/// Parses the `.proto` files at `paths` and the files they import, which are looked up in `include_paths` and then
+	/// next to the files given, as `protoc -I` would; the well-known types are built in.
+	pub fn load_proto(paths: &[PathBuf], include_paths: &[PathBuf]) -> anyhow::Result<Self> {
+		let mut pool = DescriptorPool::global();
+		let parsed = protobuf_parse::Parser::new()
+			.pure()
+			.includes(include_paths)
+			.includes(paths.iter().filter_map(|path| path.parent()))
+			.inputs(paths)
+			.parse_and_typecheck()
+			.map_err(|error| anyhow::anyhow!("{error:#}"))?;
+
+		// The parser has its own descriptor types; the wire format is the same
+		let mut files = Vec::new();
+		for file in parsed.file_descriptors.iter().filter(|file| pool.get_file_by_name(file.name()).is_none()) {
+			files.push(FileDescriptorProto::decode(protobuf::Message::write_to_bytes(file)?.as_slice())?);
+		}
+		pool.add_file_descriptor_protos(files)?;
+		Ok(Self { pool })
+	}
+
+	/// Asks the server for its services and their files through the reflection service.
+	pub async fn from_reflection(http_client: &HttpClient, request: &HttpRequest) -> anyhow::Result<Self> {
+		let (url, _) = http_url(&request.url)?;
+
+		let answers = reflect(http_client, &url, &request.headers, &[ReflectionQuery::ListServices(String::new())]).await?;
+		let services: Vec<String> = answers
+			.into_iter()
+			.flat_map(|answer| match answer {
+				ReflectionAnswer::Services(list) => list.service.into_iter().map(|service| service.name).collect(),
+				_ => Vec::new(),
+			})
+			.filter(|service| !REFLECTION_SERVICES.contains(&service.as_str()))
+			.collect();
+		if services.is_empty() {
+			return Err(GrpcError::Reflection(String::from("the server lists no services")).into());
+		}
+
+		let mut pool = DescriptorPool::global();
+		let mut files: HashMap<String, FileDescriptorProto> = HashMap::new();
+		let mut requested: HashSet<String> = HashSet::new();
+		let mut queries: Vec<ReflectionQuery> = services.into_iter().map(ReflectionQuery::FileContainingSymbol).collect();
+		// Servers usually send the dependencies along; any that are missing are asked for by name, once each
+		while !queries.is_empty() {
+			let known = files.len();
+			for answer in reflect(http_client, &url, &request.headers, &queries).await? {
+				if let ReflectionAnswer::Files(response) = answer {
+					for bytes in response.file_descriptor_proto {
+						let file = FileDescriptorProto::decode(bytes.as_slice())?;
+						files.entry(file.name().to_string()).or_insert(file);
+					}
+				}
+			}
+			if files.len() == known {
+				return Err(GrpcError::Reflection(String::from("the server sent none of the files asked for")).into());
+			}
+
+			let missing: BTreeSet<String> = files
+				.values()
+				.flat_map(|file| file.dependency.iter())
+				.filter(|dependency| !files.contains_key(*dependency) && pool.get_file_by_name(dependency).is_none())
+				.cloned()
+				.collect();
+			if let Some(name) = missing.iter().find(|name| requested.contains(*name)) {
+				return Err(GrpcError::Reflection(format!("the server did not send {name}")).into());
+			}
+			requested.extend(missing.iter().cloned());
+			queries = missing.into_iter().map(ReflectionQuery::FileByFilename).collect();
+		}
+
+		let files: Vec<_> = files.into_values().filter(|file| pool.get_file_by_name(file.name()).is_none()).collect();
+		pool.add_file_descriptor_protos(files)?;
+		Ok(Self { pool })
+	}
+
+	/// `package.Service/Method` of every method that can be called, sorted.
+	pub fn method_paths(&self) -> Vec<String> {
+		let mut paths: Vec<String> = self
+			.pool
+			.services()
+			.filter(|service| !REFLECTION_SERVICES.contains(&service.full_name()))
+			.flat_map(|service| {
+				service
+					.methods()
+					.map(|method| format!("{}/{}", method.parent_service().full_name(), method.name()))
+					.collect::<Vec<_>>()
+			})
+			.collect();
+		paths.sort();
+		paths
+	}
+
+	fn method(&self, path: &str) -> anyhow::Result<MethodDescriptor, GrpcError> {
+		let (service, method) = path.split_once('/').ok_or_else(|| GrpcError::UnknownMethod(path.to_string()))?;
+		self
+			.pool
+			.get_service_by_name(service)
+			.and_then(|service| service.methods().find(|candidate| candidate.name() == method))
+			.ok_or_else(|| GrpcError::UnknownMethod(path.to_string()))
+	}
+
+	/// The request message of `path` with every field at its default, as a starting point for the body.
+	pub fn example_request(&self, path: &str) -> Option<String> {
+		let method = self.method(path).ok()?;
+		let message = DynamicMessage::new(method.input());
+		let json = message
+			.serialize_with_options(serde_json::value::Serializer, &SerializeOptions::new().skip_default_fields(false))
+			.ok()?;
+		let json = if method.is_client_streaming() { serde_json::Value::Array(vec![json]) } else { json };
+		serde_json::to_string_pretty(&json).ok()
+	}
+}
+
+/// `grpc://` and `grpcs://` (TLS) URLs call the method named by their path.
+pub fn is_grpc_url(url: &str) -> bool {
+	let url = url.to_lowercase();
+	url.starts_with("grpc://") || url.starts_with("grpcs://")
+}
+
+/// Splits a gRPC URL into the server part and the `package.Service/Method` path, if it has one.
+pub fn split_method(url: &str) -> (&str, Option<&str>) {
+	let authority_start = url.find("://").map_or(0, |index| index + 3);
+	url[authority_start..].find('/').map_or_else(
+		|| (url, None),
+		|index| {
+			let (base_url, path) = url.split_at(authority_start + index);
+			(base_url, Some(path.trim_matches('/')).filter(|path| !path.is_empty()))
+		},
+	)
+}
+
+/// The `http(s)://` URL to post to, and the `package.Service/Method` path.
+fn http_url(url: &str) -> anyhow::Result<(Url, String), GrpcError> {
+	let invalid = || GrpcError::InvalidUrl(url.to_string());
+	let (scheme, rest) = url.split_once("://").ok_or_else(invalid)?;
+	let scheme = if scheme.eq_ignore_ascii_case("grpcs") { "https" } else { "http" };
+
+	let url = Url::parse(&format!("{scheme}://{rest}")).map_err(|_| invalid())?;
+	let path = url.path().trim_matches('/').to_string();
+	Ok((url, path))
+}
+
+/// Calls the method the URL names with the body as its JSON request message, or a JSON array of them for client
+/// streaming. Each response message is reported to `progress` as JSON when it arrives; the gRPC status from the
+/// trailers becomes the status text and the trailers are listed with the headers.
+pub async fn call(
+	http_client: &HttpClient,
+	services: &Services,
+	request: &HttpRequest,
+	progress: &(dyn Fn(Progress) + Send + Sync),
+	stop: &Notify,
+) -> anyhow::Result<HttpResponse> {
+	let (url, path) = http_url(&request.url)?;
+	let method = services.method(&path)?;
+	let payload = encode_request(&method, &request.body)?;
+
+	let started = Instant::now();
+	let mut timings = Timings::default();
+	let (response, remote_address, tls) =
+		tokio::time::timeout(TIMEOUT, send(http_client, &url, &request.headers, payload, &mut timings))
+			.await
+			.map_err(|_| HttpClientError::Timeout)??;
+	let connection = ConnectionInfo {
+		http_version: String::from("HTTP/2"),
+		remote_address: remote_address.to_string(),
+		tls,
+		proxy: http_client.proxy_for(&url).map(ToString::to_string),
+		reused: false,
+	};
+	let sent_request = SentRequest {
+		request_line: format!("POST {} HTTP/2", url.path()),
+		headers: request_headers(&url, &request.headers),
+		body: request.body.clone(),
+	};
+
+	let (parts, mut body) = response.into_parts();
+	let mut headers = header_map(&parts.headers);
+	let status_code = parts.status.as_u16();
+
+	let mut head = HttpResponse::new(
+		request.id.clone(),
+		status_code,
+		status_text(&headers),
+		headers.clone(),
+		Vec::new(),
+		timings.total(),
+	)
+	.with_grpc_messages();
+	head.timings = timings;
+	head.sent_request = Some(sent_request.clone());
+	head.connection = Some(connection.clone());
+	progress(Progress::Head(Box::new(head)));
+
+	let downloading = Instant::now();
+	let mut messages: Vec<String> = Vec::new();
+	let mut on_message = |bytes: &[u8]| -> anyhow::Result<()> {
+		let message = DynamicMessage::decode(method.output(), bytes)?;
+		let json = serde_json::to_string_pretty(&message)?;
+		let chunk = if messages.is_empty() { json.clone() } else { format!("\n\n{json}") };
+		progress(Progress::Chunk(Bytes::from(chunk)));
+		messages.push(json);
+		Ok(())
+	};
+	let (trailers, stopped) = read_messages(&mut body, &mut on_message, stop).await?;
+	timings.download = downloading.elapsed();
+	headers.extend(trailers);
+
+	let mut response = HttpResponse::new(
+		request.id.clone(),
+		status_code,
+		status_text(&headers),
+		headers,
+		messages.join("\n\n").into_bytes(),
+		started.elapsed(),
+	)
+	.with_grpc_messages();
+	response.timings = timings;
+	response.sent_request = Some(sent_request);
+	response.connection = Some(connection);
+	response.stopped = stopped;
+	Ok(response)
+}
+
+fn encode_request(method: &MethodDescriptor, body: &str) -> anyhow::Result<Bytes, GrpcError> {
+	let body = if body.trim().is_empty() { "{}" } else { body };
+	let json: serde_json::Value =
+		serde_json::from_str(body).map_err(|error| GrpcError::InvalidMessage(error.to_string()))?;
+	let messages = match json {
+		serde_json::Value::Array(messages) if method.is_client_streaming() => messages,
+		json => vec![json],
+	};
+
+	let mut payload = Vec::new();
+	for json in messages {
+		let message = DynamicMessage::deserialize(method.input(), json)
+			.map_err(|error| GrpcError::InvalidMessage(error.to_string()))?;
+		frame(&mut payload, &message.encode_to_vec());
+	}
+	Ok(Bytes::from(payload))
+}
+
+/// Appends `message` with its length prefix: an uncompressed flag and the length as a big-endian `u32`.
+fn frame(payload: &mut Vec<u8>, message: &[u8]) {
+	payload.push(0);
+	payload.extend_from_slice(&(message.len() as u32).to_be_bytes());
+	payload.extend_from_slice(message);
+}
+
+/// Takes the first complete length-prefixed message off `buffer`.
+fn take_message(buffer: &mut Vec<u8>) -> anyhow::Result<Option<Vec<u8>>, GrpcError> {
+	let Some(prefix) = buffer.get(..5) else {
+		return Ok(None);
+	};
+	if prefix[0] != 0 {
+		return Err(GrpcError::CompressedMessage);
+	}
+	let length = u32::from_be_bytes([prefix[1], prefix[2], prefix[3], prefix[4]]) as usize;
+	if buffer.len() < 5 + length {
+		return Ok(None);
+	}
+
+	let message = buffer[5..5 + length].to_vec();
+	buffer.drain(..5 + length);
+	Ok(Some(message))
+}
+
+/// Opens an HTTP/2 connection and posts the length-prefixed `payload`, timing each phase up to the response head.
+async fn send(
+	http_client: &HttpClient,
+	url: &Url,
+	metadata: &HashMap<String, String>,
+	payload: Bytes,
+	timings: &mut Timings,
+) -> anyhow::Result<(hyper::Response<Incoming>, SocketAddr, Option<TlsInfo>)> {
+	let secure = url.scheme() == "https";
+	let (connection, remote_address, tls) = http_client.open(url, secure, HttpVersion::Http2, timings).await?;
+	if tls.as_ref().is_some_and(|tls| tls.alpn.as_deref() != Some("h2")) {
+		return Err(HttpClientError::Http2NotNegotiated.into());
+	}
+
+	let mut outgoing = Request::post(url.as_str()).version(Version::HTTP_2).body(Full::new(payload))?;
+	let headers = outgoing.headers_mut();
+	headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+	headers.insert(header::TE, HeaderValue::from_static("trailers"));
+	headers.insert(header::USER_AGENT, HeaderValue::from_str(&format!("{} gRPC Client/1.0", env!("CARGO_PKG_NAME")))?);
+	for (key, value) in metadata {
+		let name = HeaderName::from_bytes(key.as_bytes()).map_err(|_| HttpClientError::InvalidHeader(key.clone()))?;
+		let value = HeaderValue::from_str(value).map_err(|_| HttpClientError::InvalidHeader(key.clone()))?;
+		headers.insert(name, value);
+	}
+
+	let started = Instant::now();
+	let (mut sender, connection) =
+		hyper::client::conn::http2::handshake(TokioExecutor::new(), TokioIo::new(connection)).await?;
+	spawn_connection(connection);
+	let response = sender.send_request(outgoing).await?;
+	timings.ttfb = started.elapsed();
+
+	Ok((response, remote_address, tls))
+}
+
+/// Reads the response messages into `on_message` until the trailers, which are returned, or until `stop`.
+async fn read_messages(
+	body: &mut Incoming,
+	on_message: &mut (dyn FnMut(&[u8]) -> anyhow::Result<()> + Send),
+	stop: &Notify,
+) -> anyhow::Result<(HashMap<String, String>, bool)> {
+	let mut buffer = Vec::new();
+	let mut trailers = HashMap::new();
+
+	loop {
+		let frame = tokio::select! {
+			frame = body.frame() => frame,
+			() = stop.notified() => return Ok((trailers, true)),
+		};
+		let Some(frame) = frame else {
+			return Ok((trailers, false));
+		};
+
+		match frame?.into_data() {
+			Ok(data) => {
+				buffer.extend_from_slice(&data);
+				while let Some(message) = take_message(&mut buffer)? {
+					on_message(&message)?;
+				}
+			},
+			Err(frame) => {
+				if let Ok(frame_trailers) = frame.into_trailers() {
+					trailers.extend(header_map(&frame_trailers));
+				}
+			},
+		}
+	}
+}
+
+fn header_map(headers: &hyper::HeaderMap) -> HashMap<String, String> {
+	headers.iter().filter_map(|(key, value)| Some((key.to_string(), value.to_str().ok()?.to_string()))).collect()
+}
+
+fn request_headers(url: &Url, metadata: &HashMap<String, String>) -> Vec<(String, String)> {
+	let mut headers = vec![
+		(String::from(":authority"), url[url::Position::BeforeHost..url::Position::BeforePath].to_string()),
+		(String::from(":scheme"), url.scheme().to_string()),
+		(String::from("content-type"), String::from("application/grpc")),
+		(String::from("te"), String::from("trailers")),
+	];
+	let mut metadata: Vec<_> = metadata.iter().map(|(key, value)| (key.to_lowercase(), value.clone())).collect();
+	metadata.sort();
+	headers.extend(metadata);
+	headers
+}
+
+/// `grpc-status` and `grpc-message` from the trailers, or from the headers of a trailers-only response.
+fn grpc_status(headers: &HashMap<String, String>) -> Option<(u32, String)> {
+	let code = headers.get("grpc-status")?.trim().parse().ok()?;
+	let message = headers.get("grpc-message").map_or_else(String::new, |message| percent_decode(message));
+	Some((code, message))
+}
+
+fn status_text(headers: &HashMap<String, String>) -> String {
+	match grpc_status(headers) {
+		None => String::from("gRPC"),
+		Some((code, message)) if message.is_empty() => format!("gRPC {}", status_name(code)),
+		Some((code, message)) => format!("gRPC {}: {message}", status_name(code)),
+	}
+}
+
+fn status_name(code: u32) -> String {
+	STATUS_NAMES.get(code as usize).map_or_else(|| format!("status {code}"), ToString::to_string)
+}
+
+/// `grpc-message` is percent-encoded UTF-8.
+fn percent_decode(value: &str) -> String {
+	let bytes = value.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut index = 0;
+	while index < bytes.len() {
+		let hex =
+			bytes.get(index + 1..index + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+		match (bytes[index], hex) {
+			(b'%', Some(byte)) => {
+				decoded.push(byte);
+				index += 3;
+			},
+			(byte, _) => {
+				decoded.push(byte);
+				index += 1;
+			},
+		}
+	}
+	String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ReflectionRequest {
+	#[prost(string, tag = "1")]
+	host: String,
+	#[prost(oneof = "ReflectionQuery", tags = "3, 4, 7")]
+	query: Option<ReflectionQuery>,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Oneof)]
+enum ReflectionQuery {
+	#[prost(string, tag = "3")]
+	FileByFilename(String),
+	#[prost(string, tag = "4")]
+	FileContainingSymbol(String),
+	#[prost(string, tag = "7")]
+	ListServices(String),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ReflectionResponse {
+	#[prost(oneof = "ReflectionAnswer", tags = "4, 6, 7")]
+	answer: Option<ReflectionAnswer>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum ReflectionAnswer {
+	#[prost(message, tag = "4")]
+	Files(FileDescriptorResponse),
+	#[prost(message, tag = "6")]
+	Services(ListServiceResponse),
+	#[prost(message, tag = "7")]
+	Error(ErrorResponse),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FileDescriptorResponse {
+	#[prost(bytes = "vec", repeated, tag = "1")]
+	file_descriptor_proto: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ListServiceResponse {
+	#[prost(message, repeated, tag = "1")]
+	service: Vec<ServiceResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ServiceResponse {
+	#[prost(string, tag = "1")]
+	name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ErrorResponse {
+	#[prost(int32, tag = "1")]
+	error_code: i32,
+	#[prost(string, tag = "2")]
+	error_message: String,
+}
+
+/// Sends `queries` on one reflection stream, trying v1 and then v1alpha, and returns the answers in order.
+async fn reflect(
+	http_client: &HttpClient,
+	url: &Url,
+	metadata: &HashMap<String, String>,
+	queries: &[ReflectionQuery],
+) -> anyhow::Result<Vec<ReflectionAnswer>> {
+	let host = url.host_str().unwrap_or_default().to_string();
+	let mut payload = Vec::new();
+	for query in queries {
+		frame(&mut payload, &ReflectionRequest { host: host.clone(), query: Some(query.clone()) }.encode_to_vec());
+	}
+
+	for service in REFLECTION_SERVICES {
+		let url = url.join(&format!("/{service}/ServerReflectionInfo"))?;
+		let (response, _, _) = tokio::time::timeout(
+			TIMEOUT,
+			send(http_client, &url, metadata, Bytes::from(payload.clone()), &mut Timings::default()),
+		)
+		.await
+		.map_err(|_| HttpClientError::Timeout)??;
+
+		let (parts, mut body) = response.into_parts();
+		let mut answers = Vec::new();
+		let mut on_message = |bytes: &[u8]| -> anyhow::Result<()> {
+			answers.extend(ReflectionResponse::decode(bytes)?.answer);
+			Ok(())
+		};
+		let (trailers, _) = tokio::time::timeout(TIMEOUT, read_messages(&mut body, &mut on_message, &Notify::new()))
+			.await
+			.map_err(|_| HttpClientError::Timeout)??;
+
+		let mut headers = header_map(&parts.headers);
+		headers.extend(trailers);
+		match grpc_status(&headers) {
+			Some((UNIMPLEMENTED, _)) => continue,
+			Some((0, _)) => {},
+			Some((code, message)) => return Err(GrpcError::Status { code, message }.into()),
+			None => return Err(GrpcError::Reflection(format!("HTTP {} without a gRPC status", parts.status)).into()),
+		}
+
+		if let Some(ReflectionAnswer::Error(error)) =
+			answers.iter().find(|answer| matches!(answer, ReflectionAnswer::Error(_)))
+		{
+			return Err(GrpcError::Reflection(error.error_message.clone()).into());
+		}
+		return Ok(answers);
+	}
+
+	Err(GrpcError::Reflection(String::from("the server doesn't offer the reflection service")).into())
+}
+
+#[cfg(test)]
+mod tests {
+	use http_body_util::StreamBody;
+	use hyper::body::Frame;
+	use prost_reflect::Value;
+	use std::convert::Infallible;
+	use std::sync::LazyLock;
+	use tokio::net::TcpListener;
+
+	use super::*;
+
+	const GREETER: &str = r#"
+syntax = "proto3";
+package helloworld;
+
+service Greeter {
+  rpc SayHello (HelloRequest) returns (HelloReply);
+  rpc StreamHellos (HelloRequest) returns (stream HelloReply);
+  rpc Fail (HelloRequest) returns (HelloReply);
+}
+
+message HelloRequest {
+  string name = 1;
+  int32 times = 2;
+}
+
+message HelloReply {
+  string message = 1;
+}
+"#;
+
+	/// Writes `files` to a fresh directory, returning it.
+	fn proto_directory(files: &[(&str, &str)]) -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("resto-proto-{}", uuid::Uuid::new_v4()));
+		for (name, source) in files {
+			let path = directory.join(name);
+			std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+			std::fs::write(path, source).unwrap();
+		}
+		directory
+	}
+
+	static GREETER_POOL: LazyLock<DescriptorPool> = LazyLock::new(|| {
+		let directory = proto_directory(&[("greeter.proto", GREETER)]);
+		let services = Services::load_proto(&[directory.join("greeter.proto")], &[]).unwrap();
+		std::fs::remove_dir_all(directory).unwrap();
+		services.pool
+	});
+
+	fn greeter_pool() -> DescriptorPool {
+		GREETER_POOL.clone()
+	}
+
+	type ResponseBody = StreamBody<futures_util::stream::Iter<std::vec::IntoIter<Result<Frame<Bytes>, Infallible>>>>;
+
+	fn grpc_response(messages: &[Vec<u8>], status: u32, message: &str) -> hyper::Response<ResponseBody> {
+		let mut frames: Vec<Result<Frame<Bytes>, Infallible>> = messages
+			.iter()
+			.map(|message| {
+				let mut payload = Vec::new();
+				frame(&mut payload, message);
+				Ok(Frame::data(Bytes::from(payload)))
+			})
+			.collect();
+		let mut trailers = hyper::HeaderMap::new();
+		trailers.insert("grpc-status", HeaderValue::from(status));
+		if !message.is_empty() {
+			trailers.insert("grpc-message", HeaderValue::from_str(message).unwrap());
+		}
+		frames.push(Ok(Frame::trailers(trailers)));
+
+		hyper::Response::builder()
+			.header("content-type", "application/grpc")
+			.body(StreamBody::new(futures_util::stream::iter(frames)))
+			.unwrap()
+	}
+
+	/// A greeter with server reflection: `SayHello` greets, `StreamHellos` greets `times` times and `Fail` fails.
+	async fn handle(request: hyper::Request<Incoming>) -> Result<hyper::Response<ResponseBody>, Infallible> {
+		let pool = greeter_pool();
+		let path = request.uri().path().to_string();
+		let greeting = request.headers().get("x-greeting").map_or("Hello", |value| value.to_str().unwrap()).to_string();
+		// Reflection then names an import it never sends
+		let missing_import = request.headers().contains_key("x-missing-import");
+		let mut buffer = request.into_body().collect().await.unwrap().to_bytes().to_vec();
+		let mut messages = Vec::new();
+		while let Some(message) = take_message(&mut buffer).unwrap() {
+			messages.push(message);
+		}
+
+		let reply = |text: String| {
+			let mut reply = DynamicMessage::new(pool.get_message_by_name("helloworld.HelloReply").unwrap());
+			reply.set_field_by_name("message", Value::String(text));
+			reply.encode_to_vec()
+		};
+		let hello = || {
+			DynamicMessage::decode(pool.get_message_by_name("helloworld.HelloRequest").unwrap(), messages[0].as_slice())
+				.unwrap()
+		};
+
+		let response = match path.as_str() {
+			"/helloworld.Greeter/SayHello" => {
+				let name = hello().get_field_by_name("name").unwrap().as_str().unwrap().to_string();
+				grpc_response(&[reply(format!("{greeting} {name}"))], 0, "")
+			},
+			"/helloworld.Greeter/StreamHellos" => {
+				let times = hello().get_field_by_name("times").unwrap().as_i32().unwrap();
+				let replies: Vec<_> = (1..=times).map(|time| reply(format!("{greeting} #{time}"))).collect();
+				grpc_response(&replies, 0, "")
+			},
+			"/helloworld.Greeter/Fail" => grpc_response(&[], 5, "no%20such user"),
+			"/grpc.reflection.v1.ServerReflection/ServerReflectionInfo" => {
+				let answers: Vec<_> = messages
+					.iter()
+					.map(|message| {
+						let answer = match ReflectionRequest::decode(message.as_slice()).unwrap().query.unwrap() {
+							ReflectionQuery::ListServices(_) => ReflectionAnswer::Services(ListServiceResponse {
+								service: vec![
+									ServiceResponse { name: String::from("helloworld.Greeter") },
+									ServiceResponse { name: String::from(REFLECTION_SERVICES[0]) },
+								],
+							}),
+							ReflectionQuery::FileContainingSymbol(_) | ReflectionQuery::FileByFilename(_) if missing_import => {
+								let mut file = pool.get_file_by_name("greeter.proto").unwrap().file_descriptor_proto().clone();
+								file.dependency.push(String::from("missing.proto"));
+								ReflectionAnswer::Files(FileDescriptorResponse { file_descriptor_proto: vec![file.encode_to_vec()] })
+							},
+							ReflectionQuery::FileContainingSymbol(_) => ReflectionAnswer::Files(FileDescriptorResponse {
+								file_descriptor_proto: vec![
+									pool.get_file_by_name("greeter.proto").unwrap().file_descriptor_proto().encode_to_vec(),
+								],
+							}),
+							ReflectionQuery::FileByFilename(name) => {
+								ReflectionAnswer::Error(ErrorResponse { error_code: 5, error_message: format!("{name} not found") })
+							},
+						};
+						ReflectionResponse { answer: Some(answer) }.encode_to_vec()
+					})
+					.collect();
+				grpc_response(&answers, 0, "")
+			},
+			_ => grpc_response(&[], UNIMPLEMENTED, ""),
+		};
+		Ok(response)
+	}
+
+	async fn serve() -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let address = listener.local_addr().unwrap();
+
+		tokio::spawn(async move {
+			loop {
+				let (stream, _) = listener.accept().await.unwrap();
+				tokio::spawn(
+					hyper::server::conn::http2::Builder::new(TokioExecutor::new())
+						.serve_connection(TokioIo::new(stream), hyper::service::service_fn(handle)),
+				);
+			}
+		});
+
+		format!("grpc://{address}")
+	}
+
+	fn request(url: &str, body: &str) -> HttpRequest {
+		let mut request = HttpRequest::new();
+		request.set_url(url);
+		request.body = body.to_string();
+		request
+	}
+
+	#[tokio::test]
+	async fn test_unary_call_with_metadata() {
+		let base_url = serve().await;
+		let services = Services { pool: greeter_pool() };
+		let mut request = request(&format!("{base_url}/helloworld.Greeter/SayHello"), r#"{"name": "Joe"}"#);
+		request.add_header(String::from("x-greeting"), String::from("Hi"));
+
+		let response = call(&HttpClient::new(), &services, &request, &|_| {}, &Notify::new()).await.unwrap();
+
+		assert_eq!(response.status_code, 200);
+		assert_eq!(response.status_text, "gRPC OK");
+		assert_eq!(response.body, "{\n  \"message\": \"Hi Joe\"\n}");
+		assert_eq!(response.headers.get("grpc-status").map(String::as_str), Some("0"));
+		assert_eq!(response.sent_request.unwrap().request_line, "POST /helloworld.Greeter/SayHello HTTP/2");
+	}
+
+	#[tokio::test]
+	async fn test_server_streaming_reports_each_message() {
+		let base_url = serve().await;
+		let services = Services { pool: greeter_pool() };
+		let request = request(&format!("{base_url}/helloworld.Greeter/StreamHellos"), r#"{"name": "Joe", "times": 3}"#);
+
+		let chunks = std::sync::Mutex::new(Vec::new());
+		let progress = |progress| {
+			if let Progress::Chunk(chunk) = progress {
+				chunks.lock().unwrap().push(String::from_utf8(chunk.to_vec()).unwrap());
+			}
+		};
+		let response = call(&HttpClient::new(), &services, &request, &progress, &Notify::new()).await.unwrap();
+
+		let chunks = chunks.into_inner().unwrap();
+		assert_eq!(chunks.len(), 3);
+		assert_eq!(chunks.concat(), response.body);
+		assert!(response.body.ends_with("\"Hello #3\"\n}"));
+	}
+
+	#[tokio::test]
+	async fn test_error_status_and_invalid_requests() {
+		let base_url = serve().await;
+		let services = Services { pool: greeter_pool() };
+		let client = HttpClient::new();
+
+		let failed = request(&format!("{base_url}/helloworld.Greeter/Fail"), "");
+		let response = call(&client, &services, &failed, &|_| {}, &Notify::new()).await.unwrap();
+		assert_eq!(response.status_text, "gRPC NOT_FOUND: no such user");
+		assert_eq!(response.body, "");
+
+		let unknown = request(&format!("{base_url}/helloworld.Greeter/Missing"), "{}");
+		assert!(call(&client, &services, &unknown, &|_| {}, &Notify::new()).await.is_err());
+		let invalid = request(&format!("{base_url}/helloworld.Greeter/SayHello"), r#"{"nmae": "Joe"}"#);
+		assert!(call(&client, &services, &invalid, &|_| {}, &Notify::new()).await.is_err());
+	}
+
+	#[tokio::test]
+	async fn test_services_from_reflection() {
+		let base_url = serve().await;
+
+		let services = Services::from_reflection(&HttpClient::new(), &request(&base_url, "")).await.unwrap();
+
+		assert_eq!(
+			services.method_paths(),
+			vec!["helloworld.Greeter/Fail", "helloworld.Greeter/SayHello", "helloworld.Greeter/StreamHellos"]
+		);
+		assert_eq!(
+			services.example_request("helloworld.Greeter/SayHello").unwrap(),
+			"{\n  \"name\": \"\",\n  \"times\": 0\n}"
+		);
+
+		let mut broken = request(&base_url, "");
+		broken.headers.insert(String::from("x-missing-import"), String::from("1"));
+		let error = Services::from_reflection(&HttpClient::new(), &broken).await.unwrap_err();
+		assert_eq!(error.to_string(), "Server reflection failed: the server sent none of the files asked for");
+	}
+
+	#[test]
+	fn test_load_proto_with_imports() {
+		let directory = proto_directory(&[
+			("common.proto", "syntax = \"proto3\";\npackage common;\nmessage Id { string value = 1; }\n"),
+			(
+				"users.proto",
+				"syntax = \"proto3\";\npackage users;\nimport \"common.proto\";\nimport \"google/protobuf/empty.proto\";\n\
+				 service Users {\n  rpc Get (common.Id) returns (google.protobuf.Empty);\n}\n",
+			),
+			("include/shared/page.proto", "syntax = \"proto3\";\npackage shared;\nmessage Page { int32 size = 1; }\n"),
+			(
+				"api/orders.proto",
+				"syntax = \"proto3\";\npackage orders;\nimport \"shared/page.proto\";\n\
+				 service Orders {\n  rpc List (shared.Page) returns (shared.Page);\n}\n",
+			),
+		]);
+
+		let services = Services::load_proto(&[directory.join("users.proto")], &[]).unwrap();
+		assert_eq!(services.method_paths(), vec!["users.Users/Get"]);
+		assert!(Services::load_proto(&[directory.join("missing.proto")], &[]).is_err());
+
+		// Imports are looked up in the include roots, not only next to the importing file
+		assert!(Services::load_proto(&[directory.join("api/orders.proto")], &[]).is_err());
+		let services = Services::load_proto(&[directory.join("api/orders.proto")], &[directory.join("include")]).unwrap();
+		assert_eq!(services.method_paths(), vec!["orders.Orders/List"]);
+
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+
+	#[test]
+	fn test_load_proto_features() {
+		let source = r#"
+syntax = "proto3";
+package shop;
+import "google/protobuf/timestamp.proto";
+
+message Item {
+  optional int32 count = 1 [json_name = "qty"];
+  map<string, Label> labels = 2;
+  oneof target {
+    string email = 3;
+    int64 user_id = 4;
+  }
+  enum Label {
+    LABEL_UNSPECIFIED = 0;
+  }
+  google.protobuf.Timestamp created_at = 5;
+}
+"#;
+		let directory = proto_directory(&[("shop.proto", source)]);
+		let pool = Services::load_proto(&[directory.join("shop.proto")], &[]).unwrap().pool;
+		std::fs::remove_dir_all(directory).unwrap();
+
+		let item = pool.get_message_by_name("shop.Item").unwrap();
+		assert_eq!(item.get_field_by_name("count").unwrap().json_name(), "qty");
+		assert!(item.get_field_by_name("count").unwrap().supports_presence());
+		assert!(item.get_field_by_name("labels").unwrap().is_map());
+		let mut oneofs: Vec<_> = item.oneofs().map(|oneof| oneof.name().to_string()).collect();
+		oneofs.sort();
+		assert_eq!(oneofs, vec!["_count", "target"]);
+		assert_eq!(
+			item.get_field_by_name("created_at").unwrap().kind().as_message().unwrap().full_name(),
+			"google.protobuf.Timestamp"
+		);
+	}
+
+	#[test]
+	fn test_urls_and_status() {
+		let (url, path) = http_url("grpcs://api.example.com:8443/pkg.Service/Method").unwrap();
+		assert_eq!(url.as_str(), "https://api.example.com:8443/pkg.Service/Method");
+		assert_eq!(path, "pkg.Service/Method");
+		assert_eq!(
+			split_method("grpc://localhost:50051/pkg.Service/Method"),
+			("grpc://localhost:50051", Some("pkg.Service/Method"))
+		);
+		assert_eq!(split_method("grpc://localhost:50051/"), ("grpc://localhost:50051", None));
+		assert!(is_grpc_url("grpc://localhost:50051"));
+		assert!(!is_grpc_url("https://localhost"));
+
+		assert_eq!(percent_decode("caf%C3%A9 100%"), "café 100%");
+		assert_eq!(status_name(16), "UNAUTHENTICATED");
+		assert_eq!(status_name(42), "status 42");
+	}
+}
@@ -0,0 +1,545 @@
+// see https://github.com/Huachao/vscode-restclient and
+// https://www.jetbrains.com/help/idea/exploring-http-syntax.html
+
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::{
+	app::{HttpMethod, HttpVersion},
+	auth::{Auth, AwsSigV4},
+	graphql::Graphql,
+	request::HttpRequest,
+};
+
+/// What can't appear as is in a query name or value of a request line; `{{variable}}` references stay readable.
+const QUERY_ENCODE_SET: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'#').add(b'%').add(b'&').add(b'+').add(b'=');
+
+#[derive(Debug)]
+pub enum HttpFileParseError {
+	RequestLine(String),
+	Method(String),
+	Header(String),
+}
+
+impl std::fmt::Display for HttpFileParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::RequestLine(line) => write!(f, "Invalid request line: {line}"),
+			Self::Method(method) => write!(f, "Invalid HTTP method: {method}"),
+			Self::Header(header) => write!(f, "Invalid header format: {header}"),
+		}
+	}
+}
+
+impl std::error::Error for HttpFileParseError {}
+
+/// REST Client's marker for GraphQL requests, whose body is the query then, after a blank line, the variables.
+const GRAPHQL_REQUEST_TYPE: (&str, &str) = ("X-REQUEST-TYPE", "GraphQL");
+
+#[derive(Debug, Default)]
+pub struct HttpFile {
+	/// `@name = value` definitions as written, possibly referring to each other
+	pub variables: HashMap<String, String>,
+	pub requests: Vec<HttpRequest>,
+}
+
+impl HttpFile {
+	/// Parses `path`, marking its requests as loaded from it.
+	pub fn load(path: &Path) -> anyhow::Result<Self> {
+		let mut http_file = parse_http_file(&std::fs::read_to_string(path)?)?;
+		for request in &mut http_file.requests {
+			request.source = Some(path.to_path_buf());
+		}
+		Ok(http_file)
+	}
+}
+
+/// The files an export writes: each request goes back to the file it was loaded from, the others to `default`.
+/// Files and the requests in them keep the collection's order.
+pub fn group_by_source(requests: &[HttpRequest], default: &Path) -> Vec<(PathBuf, Vec<HttpRequest>)> {
+	let mut files: Vec<(PathBuf, Vec<HttpRequest>)> = Vec::new();
+	for request in requests {
+		let path = request.source.as_deref().unwrap_or(default);
+		match files.iter_mut().find(|(file, _)| file == path) {
+			Some((_, file_requests)) => file_requests.push(request.clone()),
+			None => files.push((path.to_path_buf(), vec![request.clone()])),
+		}
+	}
+	files
+}
+
+pub fn parse_http_file(input: &str) -> anyhow::Result<HttpFile> {
+	let mut http_file = HttpFile::default();
+	let mut block_name = String::new();
+	let mut block_lines = Vec::new();
+
+	for line in input.lines() {
+		if let Some(separator) = line.strip_prefix("###") {
+			if let Some(request) = parse_request_block(&block_name, &block_lines, &mut http_file.variables)? {
+				http_file.requests.push(request);
+			}
+			block_name = separator.trim().to_string();
+			block_lines.clear();
+		} else {
+			block_lines.push(line);
+		}
+	}
+
+	if let Some(request) = parse_request_block(&block_name, &block_lines, &mut http_file.variables)? {
+		http_file.requests.push(request);
+	}
+
+	Ok(http_file)
+}
+
+fn parse_request_block(
+	block_name: &str,
+	lines: &[&str],
+	variables: &mut HashMap<String, String>,
+) -> anyhow::Result<Option<HttpRequest>> {
+	let mut request = HttpRequest::new();
+	request.name = block_name.to_string();
+
+	let mut lines = lines.iter().peekable();
+
+	// Variables, comments and the request line
+	let request_line = loop {
+		let Some(line) = lines.next() else {
+			return Ok(None);
+		};
+		let line = line.trim();
+
+		if line.is_empty() {
+			continue;
+		}
+
+		if let Some(comment) = line.strip_prefix('#').or_else(|| line.strip_prefix("//")) {
+			if let Some(name) = comment.trim().strip_prefix("@name") {
+				request.name = name.trim_start_matches([' ', '=']).trim().to_string();
+			}
+			continue;
+		}
+
+		if let Some(definition) = line.strip_prefix('@') {
+			// Kept as written so an export writes the references back; they're resolved when sending
+			if let Some((name, value)) = definition.split_once('=') {
+				variables.insert(name.trim().to_string(), value.trim().to_string());
+			}
+			continue;
+		}
+
+		break line.to_string();
+	};
+
+	// Multi-line query strings continue on indented lines starting with `?` or `&`
+	let mut request_line = request_line;
+	while let Some(continuation) = lines.peek().map(|line| line.trim()).filter(|line| line.starts_with(['?', '&'])) {
+		request_line.push_str(continuation);
+		lines.next();
+	}
+
+	let mut parts = request_line.split_whitespace();
+	let (method, url) = match (parts.next(), parts.next()) {
+		(Some(url), None) => (HttpMethod::Get, url),
+		(Some(method), Some(url)) => {
+			let method = method.parse::<HttpMethod>().map_err(|_| HttpFileParseError::Method(method.to_string()))?;
+			(method, url)
+		},
+		_ => return Err(HttpFileParseError::RequestLine(request_line.clone()).into()),
+	};
+
+	// e.g. `HTTP/1.1`, `HTTP/2` or JetBrains' `HTTP/2 (Prior Knowledge)`; versions we can't force are negotiated
+	let version = parts.collect::<Vec<_>>().join(" ");
+	if !version.is_empty() {
+		if !version.starts_with("HTTP/") {
+			return Err(HttpFileParseError::RequestLine(request_line.clone()).into());
+		}
+		request.http_version = version.parse::<HttpVersion>().unwrap_or_default();
+	}
+
+	request.set_method(method);
+	request.set_url_with_queries(url);
+
+	for line in lines.by_ref() {
+		let line = line.trim();
+		if line.is_empty() {
+			break;
+		}
+		if line.starts_with('#') || line.starts_with("//") {
+			continue;
+		}
+
+		let (key, value) = line.split_once(':').ok_or_else(|| HttpFileParseError::Header(line.to_string()))?;
+		if key.trim().eq_ignore_ascii_case("authorization")
+			&& let Some(aws) = parse_aws_authorization(value.trim(), &request.url)
+		{
+			request.auth = Some(Auth::AwsSigV4(aws));
+			continue;
+		}
+		request.add_header(key.trim().to_string(), value.trim().to_string());
+	}
+
+	// Response handlers (`> {% ... %}`) and response redirects (`>> file`) are not part of the body
+	let body_lines: Vec<&str> = lines.copied().take_while(|line| !line.starts_with('>')).collect();
+	let body = body_lines.join("\n").trim().to_string();
+
+	let request_type = request.headers.keys().find(|key| key.eq_ignore_ascii_case(GRAPHQL_REQUEST_TYPE.0)).cloned();
+	if let Some(key) = request_type
+		&& request.headers[&key].eq_ignore_ascii_case(GRAPHQL_REQUEST_TYPE.1)
+	{
+		request.headers.remove(&key);
+		let (query, variables) = match body.rsplit_once("\n\n") {
+			Some((query, variables)) if serde_json::from_str::<serde_json::Value>(variables).is_ok() => {
+				(query.trim_end(), variables)
+			},
+			_ => (body.as_str(), ""),
+		};
+		request.body = query.to_string();
+		request.graphql = Some(Graphql { variables: variables.to_string(), operation_name: None });
+		return Ok(Some(request));
+	}
+
+	// Kept as written, so exporting doesn't reformat hand-written bodies
+	request.body = body;
+	if !request.body.is_empty() && !request.has_body() {
+		request.send_body = Some(true);
+	}
+
+	Ok(Some(request))
+}
+
+/// REST Client's `AWS <accessId> <accessKey> [token:<sessionToken>] [region:<regionName>] [service:<serviceName>]`,
+/// which takes the service and region from a `<service>.<region>.amazonaws.com` host when they're left out.
+fn parse_aws_authorization(value: &str, url: &str) -> Option<AwsSigV4> {
+	let mut parts = value.split_whitespace();
+	if parts.next() != Some("AWS") {
+		return None;
+	}
+
+	let mut aws = AwsSigV4 {
+		access_key: parts.next()?.to_string(),
+		secret_key: parts.next()?.to_string(),
+		session_token: String::new(),
+		region: String::new(),
+		service: String::new(),
+	};
+	for option in parts {
+		match option.split_once(':')? {
+			("token", token) => aws.session_token = token.to_string(),
+			("region", region) => aws.region = region.to_string(),
+			("service", service) => aws.service = service.to_string(),
+			_ => return None,
+		}
+	}
+
+	if aws.service.is_empty() || aws.region.is_empty() {
+		let host = url::Url::parse(url).ok()?.host_str()?.to_string();
+		let labels: Vec<&str> = host.split('.').collect();
+		if aws.service.is_empty() {
+			aws.service = labels.first()?.to_string();
+		}
+		if aws.region.is_empty() {
+			aws.region = match labels.get(1) {
+				Some(region) if labels.len() > 3 => region.to_string(),
+				_ => String::from("us-east-1"),
+			};
+		}
+	}
+	Some(aws)
+}
+
+fn format_aws_authorization(aws: &AwsSigV4) -> String {
+	let mut value = format!("AWS {} {}", aws.access_key, aws.secret_key);
+	for (name, option) in [("token", &aws.session_token), ("region", &aws.region), ("service", &aws.service)] {
+		if !option.is_empty() {
+			let _ = write!(value, " {name}:{option}");
+		}
+	}
+	value
+}
+
+pub fn to_http_file(http_file: &HttpFile) -> String {
+	let mut lines = Vec::new();
+
+	let mut variables: Vec<_> = http_file.variables.iter().collect();
+	variables.sort();
+	for (name, value) in variables {
+		lines.push(format!("@{name} = {value}"));
+	}
+
+	for request in &http_file.requests {
+		if !lines.is_empty() {
+			lines.push(String::new());
+		}
+
+		if request.name.is_empty() {
+			lines.push(String::from("###"));
+		} else {
+			lines.push(format!("### {}", request.name));
+		}
+
+		lines.push(format_request(request));
+	}
+
+	lines.push(String::new());
+	lines.join("\n")
+}
+
+/// One request in `.http` form: request line, headers, then the body after a blank line.
+pub fn format_request(request: &HttpRequest) -> String {
+	let mut lines = Vec::new();
+
+	let encode = |text: &str| utf8_percent_encode(text, QUERY_ENCODE_SET).to_string();
+	let query_string = request
+		.queries
+		.iter()
+		.map(|(key, value)| if value.is_empty() { encode(key) } else { format!("{}={}", encode(key), encode(value)) })
+		.collect::<Vec<_>>()
+		.join("&");
+
+	let mut request_line = format!("{} {}", request.method.as_str(), request.url);
+	if !query_string.is_empty() {
+		request_line.push('?');
+		request_line.push_str(&query_string);
+	}
+	if !request.http_version.is_negotiate() {
+		request_line.push(' ');
+		request_line.push_str(request.http_version.as_str());
+	}
+	lines.push(request_line);
+
+	let mut headers: Vec<_> = request.headers.iter().collect();
+	headers.sort();
+	for (key, value) in headers {
+		lines.push(format!("{key}: {value}"));
+	}
+	if let Some(Auth::AwsSigV4(aws)) = &request.auth {
+		lines.push(format!("Authorization: {}", format_aws_authorization(aws)));
+	}
+	if request.is_graphql() {
+		lines.push(format!("{}: {}", GRAPHQL_REQUEST_TYPE.0, GRAPHQL_REQUEST_TYPE.1));
+	}
+
+	if !request.body.is_empty() {
+		lines.push(String::new());
+		lines.push(request.body.clone());
+	}
+	if let Some(graphql) = request.graphql.as_ref().filter(|graphql| !graphql.variables.is_empty()) {
+		lines.push(String::new());
+		lines.push(graphql.variables.clone());
+	}
+
+	lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::variables::resolve_references;
+
+	#[test]
+	fn test_parse_requests_with_variables_and_separators() {
+		let input = r#"
+@host = api.example.com
+@base = https://{{host}}/v1
+
+### List users
+GET {{base}}/users?limit=10
+Accept: application/json
+
+###
+# @name createUser
+POST {{base}}/users HTTP/1.1
+Content-Type: application/json
+Authorization: Bearer {{token}}
+
+{"name": "Joe"}
+"#;
+
+		let http_file = parse_http_file(input).unwrap();
+
+		assert_eq!(
+			http_file.variables,
+			HashMap::from([
+				(String::from("host"), String::from("api.example.com")),
+				(String::from("base"), String::from("https://{{host}}/v1")),
+			])
+		);
+		assert_eq!(http_file.requests.len(), 2);
+
+		let list_users = &http_file.requests[0];
+		assert_eq!(list_users.name, "List users");
+		assert_eq!(list_users.method, HttpMethod::Get);
+		assert_eq!(list_users.url, "{{base}}/users");
+		assert_eq!(list_users.queries, vec![(String::from("limit"), String::from("10"))]);
+		assert_eq!(list_users.headers, HashMap::from([(String::from("Accept"), String::from("application/json"))]));
+
+		let create_user = &http_file.requests[1];
+		assert_eq!(create_user.name, "createUser");
+		assert_eq!(create_user.method, HttpMethod::Post);
+		assert_eq!(create_user.http_version, HttpVersion::Http1);
+		assert_eq!(list_users.http_version, HttpVersion::Negotiate);
+		assert_eq!(create_user.headers.get("Authorization").unwrap(), "Bearer {{token}}");
+		assert_eq!(create_user.body, r#"{"name": "Joe"}"#);
+
+		let resolved = create_user.with_variables(&resolve_references(&http_file.variables));
+		assert_eq!(resolved.url, "https://api.example.com/v1/users");
+	}
+
+	#[test]
+	fn test_parse_multiline_query_and_response_handler() {
+		let input = r"
+GET https://example.com/search
+    ?q=rust
+    &page=2
+
+> {% client.global.set('x', response.body.x) %}
+";
+
+		let http_file = parse_http_file(input).unwrap();
+		let request = &http_file.requests[0];
+
+		assert_eq!(request.url, "https://example.com/search");
+		assert_eq!(
+			request.queries,
+			vec![(String::from("q"), String::from("rust")), (String::from("page"), String::from("2"))]
+		);
+		assert_eq!(request.body, "");
+	}
+
+	#[test]
+	fn test_parse_errors() {
+		assert!(parse_http_file("FET(CH https://example.com").is_err());
+		assert!(parse_http_file("GET https://example.com\nnot a header").is_err());
+	}
+
+	#[test]
+	fn test_export_round_trip() {
+		let mut request = HttpRequest::new();
+		request.name = String::from("Update");
+		request.set_method(HttpMethod::Put);
+		request.set_url("{{base}}/users/1");
+		request.add_query(String::from("notify"), String::from("true"));
+		request.add_query(String::from("q"), String::from("a b&c"));
+		request.add_query(String::from("q"), String::from("{{term}}"));
+		request.add_header(String::from("Content-Type"), String::from("text/plain"));
+		request.body = String::from("hello");
+
+		let http_file = HttpFile {
+			variables: HashMap::from([(String::from("base"), String::from("http://localhost"))]),
+			requests: vec![request],
+		};
+
+		let exported = to_http_file(&http_file);
+		assert_eq!(
+			exported,
+			"@base = http://localhost\n\n### Update\nPUT {{base}}/users/1?notify=true&q=a%20b%26c&q={{term}}\nContent-Type: text/plain\n\nhello\n"
+		);
+
+		let parsed = parse_http_file(&exported).unwrap();
+		assert_eq!(parsed.variables, http_file.variables);
+		assert_eq!(parsed.requests[0].name, "Update");
+		assert_eq!(parsed.requests[0].url, "{{base}}/users/1");
+		assert_eq!(parsed.requests[0].queries, http_file.requests[0].queries);
+		assert_eq!(parsed.requests[0].body, "hello");
+	}
+
+	#[test]
+	fn test_export_keeps_bodies_and_valueless_queries_as_written() {
+		let input = "### Create\nPOST https://example.com/users?dry_run&limit=1\nContent-Type: application/json\n\n{\"name\":   \"Joe\",\n  \"tags\": [] }\n";
+
+		let http_file = parse_http_file(input).unwrap();
+		assert_eq!(http_file.requests[0].queries[0], (String::from("dry_run"), String::new()));
+		assert_eq!(to_http_file(&http_file), input);
+	}
+
+	#[test]
+	fn test_group_by_source() {
+		let sourced = |name: &str, source: Option<&str>| {
+			let mut request = HttpRequest::new();
+			request.name = name.to_string();
+			request.source = source.map(PathBuf::from);
+			request
+		};
+		let requests = vec![
+			sourced("a1", Some("a.http")),
+			sourced("saved", None),
+			sourced("b1", Some("b.http")),
+			sourced("a2", Some("a.http")),
+		];
+
+		let files = group_by_source(&requests, Path::new("resto.http"));
+		let names: Vec<(&Path, Vec<&str>)> = files
+			.iter()
+			.map(|(path, requests)| (path.as_path(), requests.iter().map(|request| request.name.as_str()).collect()))
+			.collect();
+		assert_eq!(
+			names,
+			vec![
+				(Path::new("a.http"), vec!["a1", "a2"]),
+				(Path::new("resto.http"), vec!["saved"]),
+				(Path::new("b.http"), vec!["b1"]),
+			]
+		);
+	}
+
+	#[test]
+	fn test_aws_authorization() {
+		let input = "GET https://execute-api.eu-west-1.amazonaws.com/prod/items\n\
+			Authorization: AWS {{key}} {{secret}} token:{{token}}\n\n\
+			###\n\
+			GET https://iam.amazonaws.com/?Action=ListUsers\n\
+			Authorization: AWS AKID SECRET region:us-east-1 service:iam\n";
+		let http_file = parse_http_file(input).unwrap();
+
+		let Some(Auth::AwsSigV4(aws)) = &http_file.requests[0].auth else { unreachable!() };
+		assert_eq!((aws.access_key.as_str(), aws.secret_key.as_str()), ("{{key}}", "{{secret}}"));
+		assert_eq!(aws.session_token, "{{token}}");
+		assert_eq!((aws.service.as_str(), aws.region.as_str()), ("execute-api", "eu-west-1"));
+		assert!(http_file.requests[0].headers.is_empty());
+
+		let exported = to_http_file(&http_file);
+		assert!(exported.contains("Authorization: AWS AKID SECRET region:us-east-1 service:iam\n"));
+		assert_eq!(parse_http_file(&exported).unwrap().requests[1].auth, http_file.requests[1].auth);
+
+		let bearer = parse_http_file("GET https://example.com\nAuthorization: Bearer token\n").unwrap();
+		assert_eq!(bearer.requests[0].auth, None);
+		assert_eq!(bearer.requests[0].headers["Authorization"], "Bearer token");
+	}
+
+	#[test]
+	fn test_http_version() {
+		let http_file = parse_http_file("GET http://localhost:8080/health HTTP/2 (Prior Knowledge)\n").unwrap();
+		assert_eq!(http_file.requests[0].http_version, HttpVersion::Http2);
+		assert_eq!(to_http_file(&http_file), "###\nGET http://localhost:8080/health HTTP/2\n");
+
+		let http_file = parse_http_file("GET https://example.com HTTP/3\n").unwrap();
+		assert_eq!(http_file.requests[0].http_version, HttpVersion::Negotiate);
+		assert!(parse_http_file("GET https://example.com SPDY/3\n").is_err());
+	}
+
+	#[test]
+	fn test_graphql_requests() {
+		let input = "POST https://api.example.com/graphql\nX-Request-Type: GraphQL\n\nquery GetUser($id: ID!) {\n\n  user(id: $id) { name }\n}\n\n{\"id\": 1}\n";
+
+		let http_file = parse_http_file(input).unwrap();
+		let request = &http_file.requests[0];
+
+		assert!(request.headers.is_empty());
+		assert_eq!(request.body, "query GetUser($id: ID!) {\n\n  user(id: $id) { name }\n}");
+		assert_eq!(request.graphql.as_ref().unwrap().variables, "{\"id\": 1}");
+
+		let exported = to_http_file(&http_file);
+		assert!(exported.contains("X-REQUEST-TYPE: GraphQL\n\nquery GetUser($id: ID!) {"));
+		let reparsed = parse_http_file(&exported).unwrap();
+		assert_eq!(reparsed.requests[0].body, request.body);
+		assert_eq!(reparsed.requests[0].graphql, request.graphql);
+
+		let without_variables =
+			parse_http_file("POST https://api.example.com/graphql\nX-REQUEST-TYPE: GraphQL\n\n{ me { name } }\n").unwrap();
+		assert_eq!(without_variables.requests[0].body, "{ me { name } }");
+		assert_eq!(without_variables.requests[0].graphql, Some(Graphql::default()));
+	}
+}
@@ -0,0 +1,572 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum KeymapError {
+	UnknownAction(String),
+	InvalidKey(String),
+	Conflict { key: String, actions: Vec<String> },
+}
+
+impl fmt::Display for KeymapError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::UnknownAction(action) => write!(f, "Unknown action in keymap: {action}"),
+			Self::InvalidKey(key) => write!(f, "Invalid key in keymap: {key}"),
+			Self::Conflict { key, actions } => write!(f, "Key {key} is bound to several actions: {}", actions.join(", ")),
+		}
+	}
+}
+
+impl std::error::Error for KeymapError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+	Quit,
+	Help,
+	NextTab,
+	PreviousTab,
+	NextRequestTab,
+	PreviousRequestTab,
+	NextResponseTab,
+	PreviousResponseTab,
+	SelectPrevious,
+	SelectNext,
+	EditUrl,
+	EditName,
+	EditRequest,
+	InspectResponse,
+	SaveResponseBody,
+	ToggleDecompression,
+	ToggleRedirects,
+	NextMethod,
+	PreviousMethod,
+	ToggleBody,
+	NextHttpVersion,
+	NextMessageKind,
+	CloseWebSocket,
+	ToggleGraphql,
+	NextOperation,
+	IntrospectSchema,
+	PreviewRequest,
+	SendRequest,
+	StopStream,
+	ClearResponses,
+	SaveRequest,
+	AddToCollection,
+	ExportCollection,
+	NextEnvironment,
+	SaveExample,
+	ToggleMockServer,
+	ToggleProxy,
+}
+
+impl Action {
+	pub const ALL: &'static [Self] = &[
+		Self::Quit,
+		Self::Help,
+		Self::NextTab,
+		Self::PreviousTab,
+		Self::NextRequestTab,
+		Self::PreviousRequestTab,
+		Self::NextResponseTab,
+		Self::PreviousResponseTab,
+		Self::SelectPrevious,
+		Self::SelectNext,
+		Self::EditUrl,
+		Self::EditName,
+		Self::EditRequest,
+		Self::InspectResponse,
+		Self::SaveResponseBody,
+		Self::ToggleDecompression,
+		Self::ToggleRedirects,
+		Self::NextMethod,
+		Self::PreviousMethod,
+		Self::ToggleBody,
+		Self::NextHttpVersion,
+		Self::NextMessageKind,
+		Self::CloseWebSocket,
+		Self::ToggleGraphql,
+		Self::NextOperation,
+		Self::IntrospectSchema,
+		Self::PreviewRequest,
+		Self::SendRequest,
+		Self::StopStream,
+		Self::ClearResponses,
+		Self::SaveRequest,
+		Self::AddToCollection,
+		Self::ExportCollection,
+		Self::NextEnvironment,
+		Self::SaveExample,
+		Self::ToggleMockServer,
+		Self::ToggleProxy,
+	];
+
+	/// Name used for the action in the config file.
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Quit => "quit",
+			Self::Help => "help",
+			Self::NextTab => "next_tab",
+			Self::PreviousTab => "previous_tab",
+			Self::NextRequestTab => "next_request_tab",
+			Self::PreviousRequestTab => "previous_request_tab",
+			Self::NextResponseTab => "next_response_tab",
+			Self::PreviousResponseTab => "previous_response_tab",
+			Self::SelectPrevious => "select_previous",
+			Self::SelectNext => "select_next",
+			Self::EditUrl => "edit_url",
+			Self::EditName => "edit_name",
+			Self::EditRequest => "edit_request",
+			Self::InspectResponse => "inspect_response",
+			Self::SaveResponseBody => "save_response_body",
+			Self::ToggleDecompression => "toggle_decompression",
+			Self::ToggleRedirects => "toggle_redirects",
+			Self::NextMethod => "next_method",
+			Self::PreviousMethod => "previous_method",
+			Self::ToggleBody => "toggle_body",
+			Self::NextHttpVersion => "next_http_version",
+			Self::NextMessageKind => "next_message_kind",
+			Self::CloseWebSocket => "close_websocket",
+			Self::ToggleGraphql => "toggle_graphql",
+			Self::NextOperation => "next_operation",
+			Self::IntrospectSchema => "introspect_schema",
+			Self::PreviewRequest => "preview_request",
+			Self::SendRequest => "send_request",
+			Self::StopStream => "stop_stream",
+			Self::ClearResponses => "clear_responses",
+			Self::SaveRequest => "save_request",
+			Self::AddToCollection => "add_to_collection",
+			Self::ExportCollection => "export_collection",
+			Self::NextEnvironment => "next_environment",
+			Self::SaveExample => "save_example",
+			Self::ToggleMockServer => "toggle_mock_server",
+			Self::ToggleProxy => "toggle_proxy",
+		}
+	}
+
+	pub const fn description(self) -> &'static str {
+		match self {
+			Self::Quit => "Quit application",
+			Self::Help => "Show this help screen",
+			Self::NextTab => "Next tab",
+			Self::PreviousTab => "Previous tab",
+			Self::NextRequestTab => "Next request tab",
+			Self::PreviousRequestTab => "Previous request tab",
+			Self::NextResponseTab => "Next response tab",
+			Self::PreviousResponseTab => "Previous response tab",
+			Self::SelectPrevious => "Select previous item (Collection/History)",
+			Self::SelectNext => "Select next item (Collection/History)",
+			Self::EditUrl => "Edit URL",
+			Self::EditName => "Edit request name",
+			Self::EditRequest => "Edit focused request headers/body ..etc",
+			Self::InspectResponse => "Inspect focused response headers/body ..etc",
+			Self::SaveResponseBody => "Save response body to a file",
+			Self::ToggleDecompression => "Toggle decompressing gzip/br/deflate/zstd responses",
+			Self::ToggleRedirects => "Toggle following redirects (off stops at the first one)",
+			Self::NextMethod => "Next HTTP method",
+			Self::PreviousMethod => "Previous HTTP method",
+			Self::ToggleBody => "Toggle sending the body (e.g. DELETE with a body)",
+			Self::NextHttpVersion => "Cycle the HTTP version: negotiate, HTTP/1.1, HTTP/2",
+			Self::NextMessageKind => "Cycle the WebSocket message type: text, JSON, binary (hex)",
+			Self::CloseWebSocket => "Close the WebSocket connection",
+			Self::ToggleGraphql => "Toggle GraphQL mode: the body is the query, with a Variables tab",
+			Self::NextOperation => "Cycle the GraphQL operation, or the gRPC method, to run",
+			Self::IntrospectSchema => {
+				"Load the GraphQL schema for field completion (Tab while editing), or gRPC services by reflection"
+			},
+			Self::PreviewRequest => "Preview the request with variables and {{$functions}} resolved",
+			Self::SendRequest => "Send request (open selected in Collection)",
+			Self::StopStream => "Stop a streaming response, keeping what arrived",
+			Self::ClearResponses => "Clear response history",
+			Self::SaveRequest => "Save current request to its own file",
+			Self::AddToCollection => "Add current request to collection",
+			Self::ExportCollection => "Export collection to .http file",
+			Self::NextEnvironment => "Switch workspace environment",
+			Self::SaveExample => "Use the response as the request's example for the mock server",
+			Self::ToggleMockServer => "Start/stop the mock server serving the collection's examples",
+			Self::ToggleProxy => "Start/stop the proxy recording the traffic sent through it into the history",
+		}
+	}
+
+	pub const fn group(self) -> &'static str {
+		match self {
+			Self::Quit
+			| Self::Help
+			| Self::NextTab
+			| Self::PreviousTab
+			| Self::NextRequestTab
+			| Self::PreviousRequestTab
+			| Self::NextResponseTab
+			| Self::PreviousResponseTab
+			| Self::SelectPrevious
+			| Self::SelectNext => "Navigation",
+			Self::EditUrl
+			| Self::EditName
+			| Self::EditRequest
+			| Self::InspectResponse
+			| Self::SaveResponseBody
+			| Self::ToggleDecompression
+			| Self::ToggleRedirects
+			| Self::NextMethod
+			| Self::PreviousMethod
+			| Self::ToggleBody
+			| Self::NextHttpVersion
+			| Self::NextMessageKind
+			| Self::CloseWebSocket
+			| Self::ToggleGraphql
+			| Self::NextOperation
+			| Self::IntrospectSchema
+			| Self::PreviewRequest
+			| Self::SendRequest
+			| Self::StopStream
+			| Self::ClearResponses => "Request Building",
+			Self::SaveRequest
+			| Self::AddToCollection
+			| Self::ExportCollection
+			| Self::NextEnvironment
+			| Self::SaveExample
+			| Self::ToggleMockServer
+			| Self::ToggleProxy => "Collection",
+		}
+	}
+
+	const fn default_keys(self) -> &'static [&'static str] {
+		match self {
+			Self::Quit => &["q"],
+			Self::Help => &["?"],
+			Self::NextTab => &["tab"],
+			Self::PreviousTab => &["backtab"],
+			Self::NextRequestTab => &["]"],
+			Self::PreviousRequestTab => &["["],
+			Self::NextResponseTab => &["}"],
+			Self::PreviousResponseTab => &["{"],
+			Self::SelectPrevious => &["up"],
+			Self::SelectNext => &["down"],
+			Self::EditUrl => &["u"],
+			Self::EditName => &["n"],
+			Self::EditRequest => &["e"],
+			Self::InspectResponse => &["r"],
+			Self::SaveResponseBody => &["w"],
+			Self::ToggleDecompression => &["z"],
+			Self::ToggleRedirects => &["f"],
+			Self::NextMethod => &["m"],
+			Self::PreviousMethod => &["M"],
+			Self::ToggleBody => &["b"],
+			Self::NextHttpVersion => &["v"],
+			Self::NextMessageKind => &["t"],
+			Self::CloseWebSocket => &["d"],
+			Self::ToggleGraphql => &["g"],
+			Self::NextOperation => &["o"],
+			Self::IntrospectSchema => &["i"],
+			Self::PreviewRequest => &["p"],
+			Self::SendRequest => &["enter"],
+			Self::StopStream => &["ctrl-x"],
+			Self::ClearResponses => &["ctrl-c"],
+			Self::SaveRequest => &["s"],
+			Self::AddToCollection => &["a"],
+			Self::ExportCollection => &["x"],
+			Self::NextEnvironment => &["E"],
+			Self::SaveExample => &["y"],
+			Self::ToggleMockServer => &["K"],
+			Self::ToggleProxy => &["P"],
+		}
+	}
+}
+
+impl std::str::FromStr for Action {
+	type Err = KeymapError;
+
+	fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+		Self::ALL
+			.iter()
+			.copied()
+			.find(|action| action.as_str() == s)
+			.ok_or_else(|| KeymapError::UnknownAction(s.to_string()))
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+	pub code: KeyCode,
+	pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+	/// Shift is implied by the character itself (`M` vs `m`), so it is dropped for character keys.
+	/// Terminals report Ctrl and Alt chords with the lowercase letter, and Shift-Tab as `BackTab`.
+	fn normalized(code: KeyCode, modifiers: KeyModifiers) -> Self {
+		let code = match code {
+			KeyCode::Char(ch) if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+				KeyCode::Char(ch.to_ascii_lowercase())
+			},
+			KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+			code => code,
+		};
+		let modifiers = match code {
+			KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+			_ => modifiers,
+		};
+		Self { code, modifiers }
+	}
+}
+
+impl From<KeyEvent> for KeyBinding {
+	fn from(key: KeyEvent) -> Self {
+		Self::normalized(key.code, key.modifiers)
+	}
+}
+
+impl std::str::FromStr for KeyBinding {
+	type Err = KeymapError;
+
+	fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+		let invalid = || KeymapError::InvalidKey(s.to_string());
+
+		let mut modifiers = KeyModifiers::NONE;
+		let mut rest = s;
+		loop {
+			let lower = rest.to_lowercase();
+			let (modifier, length) = if lower.starts_with("ctrl-") {
+				(KeyModifiers::CONTROL, 5)
+			} else if lower.starts_with("alt-") {
+				(KeyModifiers::ALT, 4)
+			} else if lower.starts_with("shift-") {
+				(KeyModifiers::SHIFT, 6)
+			} else {
+				break;
+			};
+			modifiers |= modifier;
+			rest = &rest[length..];
+		}
+
+		let mut chars = rest.chars();
+		let code = match (chars.next(), chars.next()) {
+			(Some(ch), None) => KeyCode::Char(ch),
+			_ => match rest.to_lowercase().as_str() {
+				"enter" => KeyCode::Enter,
+				"esc" => KeyCode::Esc,
+				"tab" => KeyCode::Tab,
+				"backtab" => KeyCode::BackTab,
+				"space" => KeyCode::Char(' '),
+				"backspace" => KeyCode::Backspace,
+				"delete" => KeyCode::Delete,
+				"up" => KeyCode::Up,
+				"down" => KeyCode::Down,
+				"left" => KeyCode::Left,
+				"right" => KeyCode::Right,
+				"home" => KeyCode::Home,
+				"end" => KeyCode::End,
+				"pageup" => KeyCode::PageUp,
+				"pagedown" => KeyCode::PageDown,
+				function if function.starts_with('f') => {
+					KeyCode::F(function[1..].parse().ok().filter(|number| (1..=12).contains(number)).ok_or_else(invalid)?)
+				},
+				_ => return Err(invalid()),
+			},
+		};
+
+		Ok(Self::normalized(code, modifiers))
+	}
+}
+
+impl fmt::Display for KeyBinding {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.modifiers.contains(KeyModifiers::CONTROL) {
+			write!(f, "Ctrl-")?;
+		}
+		if self.modifiers.contains(KeyModifiers::ALT) {
+			write!(f, "Alt-")?;
+		}
+		if self.modifiers.contains(KeyModifiers::SHIFT) {
+			write!(f, "Shift-")?;
+		}
+
+		match self.code {
+			KeyCode::Char(' ') => write!(f, "Space"),
+			KeyCode::Char(ch) => write!(f, "{ch}"),
+			KeyCode::BackTab => write!(f, "Shift-Tab"),
+			KeyCode::F(number) => write!(f, "F{number}"),
+			code => write!(f, "{code}"),
+		}
+	}
+}
+
+/// One key or a list of keys for an action in the `[keys]` section of the config file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+	One(String),
+	Many(Vec<String>),
+}
+
+impl KeyList {
+	fn keys(&self) -> Vec<&str> {
+		match self {
+			Self::One(key) => vec![key.as_str()],
+			Self::Many(keys) => keys.iter().map(String::as_str).collect(),
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+	bindings: HashMap<KeyBinding, Action>,
+	keys: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Keymap {
+	/// Builds the keymap from the defaults with `overrides` replacing the keys of the actions they name.
+	pub fn new(overrides: &HashMap<String, KeyList>) -> anyhow::Result<Self, KeymapError> {
+		let mut keys: HashMap<Action, Vec<KeyBinding>> = HashMap::new();
+
+		for action in Action::ALL {
+			let bindings = action.default_keys().iter().map(|key| key.parse()).collect::<anyhow::Result<Vec<_>, _>>()?;
+			keys.insert(*action, bindings);
+		}
+
+		for (name, key_list) in overrides {
+			let action: Action = name.parse()?;
+			let bindings = key_list.keys().iter().map(|key| key.parse()).collect::<anyhow::Result<Vec<_>, _>>()?;
+			keys.insert(action, bindings);
+		}
+
+		let mut bindings = HashMap::new();
+		let mut conflicts: HashMap<KeyBinding, Vec<Action>> = HashMap::new();
+		for action in Action::ALL {
+			for binding in &keys[action] {
+				if let Some(existing) = bindings.insert(*binding, *action) {
+					conflicts.entry(*binding).or_insert_with(|| vec![existing]).push(*action);
+				}
+			}
+		}
+
+		if let Some((binding, actions)) = conflicts.into_iter().min_by_key(|(binding, _)| binding.to_string()) {
+			return Err(KeymapError::Conflict {
+				key: binding.to_string(),
+				actions: actions.iter().map(|action| action.as_str().to_string()).collect(),
+			});
+		}
+
+		Ok(Self { bindings, keys })
+	}
+
+	pub fn action(&self, key: KeyEvent) -> Option<Action> {
+		self.bindings.get(&KeyBinding::from(key)).copied()
+	}
+
+	/// Keys bound to `action` joined for display, e.g. `Enter` or `u/U`.
+	pub fn keys_for(&self, action: Action) -> String {
+		self
+			.keys
+			.get(&action)
+			.map_or_else(String::new, |bindings| bindings.iter().map(ToString::to_string).collect::<Vec<_>>().join("/"))
+	}
+
+	pub fn help_lines(&self) -> Vec<String> {
+		let key_width = Action::ALL.iter().map(|action| self.keys_for(*action).chars().count()).max().unwrap_or(0);
+
+		let mut lines = Vec::new();
+		let mut current_group = "";
+		for action in Action::ALL {
+			if action.group() != current_group {
+				if !current_group.is_empty() {
+					lines.push(String::new());
+				}
+				current_group = action.group();
+				lines.push(format!("{current_group}:"));
+			}
+
+			let keys = self.keys_for(*action);
+			if !keys.is_empty() {
+				lines.push(format!("  {keys:<key_width$} - {}", action.description()));
+			}
+		}
+		lines
+	}
+
+	pub fn footer_hints(&self) -> String {
+		[
+			("Help", vec![Action::Help]),
+			("Switch tabs", vec![Action::NextTab]),
+			("Change method", vec![Action::NextMethod, Action::PreviousMethod]),
+			("Send request", vec![Action::SendRequest]),
+		]
+		.iter()
+		.map(|(label, actions)| {
+			let keys = actions.iter().map(|action| self.keys_for(*action)).collect::<Vec<_>>().join("/");
+			format!("{label}: {keys}")
+		})
+		.collect::<Vec<_>>()
+		.join(" | ")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+		KeyEvent::new(code, modifiers)
+	}
+
+	#[test]
+	fn test_parse_key_bindings() {
+		assert_eq!(
+			"q".parse::<KeyBinding>().unwrap(),
+			KeyBinding { code: KeyCode::Char('q'), modifiers: KeyModifiers::NONE }
+		);
+		assert_eq!(
+			"Ctrl-C".parse::<KeyBinding>().unwrap(),
+			KeyBinding { code: KeyCode::Char('c'), modifiers: KeyModifiers::CONTROL }
+		);
+		assert_eq!(
+			"shift-tab".parse::<KeyBinding>().unwrap(),
+			KeyBinding { code: KeyCode::BackTab, modifiers: KeyModifiers::NONE }
+		);
+		assert_eq!("alt-X".parse::<KeyBinding>().unwrap().code, KeyCode::Char('x'));
+		assert_eq!("enter".parse::<KeyBinding>().unwrap().code, KeyCode::Enter);
+		assert_eq!("F5".parse::<KeyBinding>().unwrap().code, KeyCode::F(5));
+		assert!("hyper-x".parse::<KeyBinding>().is_err());
+		assert!("f13".parse::<KeyBinding>().is_err());
+	}
+
+	#[test]
+	fn test_default_keymap() {
+		let keymap = Keymap::new(&HashMap::new()).unwrap();
+
+		assert_eq!(keymap.action(key(KeyCode::Char('q'), KeyModifiers::NONE)), Some(Action::Quit));
+		assert_eq!(keymap.action(key(KeyCode::Char('M'), KeyModifiers::SHIFT)), Some(Action::PreviousMethod));
+		assert_eq!(keymap.action(key(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Action::ClearResponses));
+		assert_eq!(keymap.action(key(KeyCode::Char('c'), KeyModifiers::NONE)), None);
+		assert_eq!(keymap.action(key(KeyCode::BackTab, KeyModifiers::SHIFT)), Some(Action::PreviousTab));
+		assert_eq!(keymap.footer_hints(), "Help: ? | Switch tabs: Tab | Change method: m/M | Send request: Enter");
+	}
+
+	#[test]
+	fn test_overrides_replace_default_keys() {
+		let overrides = HashMap::from([
+			(String::from("send_request"), KeyList::Many(vec![String::from("ctrl-s"), String::from("F5")])),
+			(String::from("quit"), KeyList::One(String::from("ctrl-q"))),
+		]);
+		let keymap = Keymap::new(&overrides).unwrap();
+
+		assert_eq!(keymap.action(key(KeyCode::Enter, KeyModifiers::NONE)), None);
+		assert_eq!(keymap.action(key(KeyCode::F(5), KeyModifiers::NONE)), Some(Action::SendRequest));
+		assert_eq!(keymap.action(key(KeyCode::Char('q'), KeyModifiers::NONE)), None);
+		assert_eq!(keymap.keys_for(Action::SendRequest), "Ctrl-s/F5");
+		assert!(keymap.help_lines().contains(&String::from("  Ctrl-s/F5 - Send request (open selected in Collection)")));
+	}
+
+	#[test]
+	fn test_conflicts_and_unknown_actions_are_rejected() {
+		let overrides = HashMap::from([(String::from("save_request"), KeyList::One(String::from("u")))]);
+		let error = Keymap::new(&overrides).unwrap_err();
+		assert_eq!(error.to_string(), "Key u is bound to several actions: edit_url, save_request");
+
+		let overrides = HashMap::from([(String::from("launch_rockets"), KeyList::One(String::from("l")))]);
+		assert!(matches!(Keymap::new(&overrides), Err(KeymapError::UnknownAction(_))));
+	}
+}
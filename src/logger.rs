@@ -1,22 +1,17 @@
-use std::path::PathBuf;
+use std::sync::LazyLock;
 
-use lazy_static::lazy_static;
 use tracing_error::ErrorLayer;
 use tracing_subscriber::{self, Layer, layer::SubscriberExt, util::SubscriberInitExt};
 
-lazy_static! {
-	pub static ref PROJECT_NAME: String = env!("CARGO_CRATE_NAME").to_uppercase().to_string();
-	pub static ref LOG_ENV: String = format!("{}_LOGLEVEL", PROJECT_NAME.clone());
-	pub static ref LOG_FILE: String = format!("{}.log", env!("CARGO_PKG_NAME"));
-}
+use crate::paths;
 
-pub fn get_data_dir() -> PathBuf {
-	PathBuf::from(".").join(".data")
-}
+pub static PROJECT_NAME: LazyLock<String> = LazyLock::new(|| env!("CARGO_CRATE_NAME").to_uppercase());
+pub static LOG_ENV: LazyLock<String> = LazyLock::new(|| format!("{}_LOGLEVEL", *PROJECT_NAME));
+pub static LOG_FILE: LazyLock<String> = LazyLock::new(|| format!("{}.log", env!("CARGO_PKG_NAME")));
 
 pub fn initialize_logging() -> anyhow::Result<()> {
-	let directory = get_data_dir();
-	std::fs::create_dir_all(directory.clone())?;
+	let directory = paths::state_dir();
+	std::fs::create_dir_all(&directory)?;
 	let log_path = directory.join(LOG_FILE.clone());
 	let log_file = std::fs::File::create(log_path)?;
 
@@ -26,7 +21,10 @@ pub fn initialize_logging() -> anyhow::Result<()> {
 		.with_writer(log_file)
 		.with_target(false)
 		.with_ansi(false)
-		.with_filter(tracing_subscriber::filter::EnvFilter::from_default_env());
+		.with_filter(std::env::var(LOG_ENV.as_str()).map_or_else(
+			|_| tracing_subscriber::filter::EnvFilter::from_default_env(),
+			tracing_subscriber::filter::EnvFilter::new,
+		));
 	tracing_subscriber::registry().with(file_subscriber).with(ErrorLayer::default()).init();
 	Ok(())
 }
@@ -36,7 +34,8 @@ macro_rules! trace_dbg {
 	(target: $target:expr, level: $level:expr, $ex:expr) => {{
 		match $ex {
 			value => {
-				tracing::event!(target: $target, $level, ?value, stringify!($ex));
+				// Secret variables never reach the log file
+				tracing::event!(target: $target, $level, value = %$crate::secrets::mask(&format!("{value:?}")), stringify!($ex));
 				value
 			}
 		}
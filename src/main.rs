@@ -1,3 +1,4 @@
+use clap::Parser;
 use ratatui::{
 	Terminal,
 	backend::CrosstermBackend,
@@ -8,30 +9,108 @@ use ratatui::{
 	},
 };
 use std::io;
+use std::net::SocketAddr;
 use std::time::Duration;
 
 mod app;
+mod auth;
+mod cli;
+mod config;
+mod connection;
 mod curl;
+mod graphql;
+mod grpc;
 mod http_client;
+mod http_file;
+mod keymap;
 mod logger;
+mod mock;
+mod openapi;
+mod paths;
+mod pool;
+mod proxy;
 mod request;
 mod response;
+mod script;
+mod secrets;
+mod sse;
+mod storage;
+mod system_proxy;
+mod theme;
+mod timing;
 mod ui;
 mod utils;
 mod vim;
+mod websocket;
+mod workspace;
 
 use app::App;
+use cli::{Cli, Command};
+use config::Config;
+use grpc::Services;
+use mock::{MockServer, Route};
+use openapi::OpenApiSpec;
+use secrets::SecretStore;
+use workspace::Workspace;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+	let cli = Cli::parse();
+	if let Some(data_dir) = &cli.data_dir {
+		paths::set_data_dir(data_dir.clone());
+	}
+	if cli.print_paths {
+		println!("config: {}", paths::config_dir().display());
+		println!("data:   {}", paths::data_dir().display());
+		println!("cache:  {}", paths::cache_dir().display());
+		println!("state:  {}", paths::state_dir().display());
+		return Ok(());
+	}
 	logger::initialize_logging()?;
+
+	let workspace = match &cli.workspace {
+		Some(root) => Workspace::open(root)?,
+		None => Workspace::discover(&std::env::current_dir()?)?,
+	};
+
+	if let Some(Command::Secret(command)) = &cli.command {
+		return secrets::run_command(command, &workspace.secrets_path());
+	}
+
+	let config_path = cli.config.clone().unwrap_or_else(Config::default_path);
+	let config = Config::load(&config_path)?;
+
+	let mut app = App::new(workspace, config);
+	app.load_workspace()?;
+	if let Some(Command::Mock { http_files, port, host }) = &cli.command {
+		for path in http_files {
+			app.load_http_file(path)?;
+		}
+		return serve_mocks(app.mock_routes(), SocketAddr::new(*host, *port)).await;
+	}
+
+	let secrets_path = app.workspace.secrets_path();
+	if secrets_path.is_file() {
+		let store = SecretStore::unlock(&secrets_path)?;
+		secrets::register(store.secrets());
+		app.secret_variables = store.secrets().clone().into_iter().collect();
+	}
+	if let Some(path) = &cli.openapi {
+		app.set_openapi_spec(OpenApiSpec::load(path)?);
+	}
+	if !cli.proto.is_empty() {
+		app.grpc_services = Some(Services::load_proto(&cli.proto, &cli.proto_path)?);
+	}
+	for path in &cli.http_files {
+		app.load_http_file(path)?;
+	}
+
 	enable_raw_mode()?;
 	let mut stdout = io::stdout();
 	execute!(stdout, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
 	let backend = CrosstermBackend::new(stdout);
 	let mut terminal = Terminal::new(backend)?;
 
-	let mut app = App::new();
 	let res = run_app(&mut terminal, &mut app);
 
 	disable_raw_mode()?;
@@ -45,24 +124,40 @@ async fn main() -> anyhow::Result<()> {
 	Ok(())
 }
 
+/// Runs `resto mock`, printing each hit until interrupted.
+async fn serve_mocks(routes: Vec<Route>, address: SocketAddr) -> anyhow::Result<()> {
+	let mut server = MockServer::start(address, routes)?;
+	println!("Serving {} mocks on http://{}", server.route_count(), server.address);
+
+	loop {
+		tokio::select! {
+			_ = tokio::signal::ctrl_c() => return Ok(()),
+			hit = server.next_hit() => match hit {
+				Some(hit) => println!("{}", hit.formatted()),
+				None => return Ok(()),
+			},
+		}
+	}
+}
+
 fn run_app(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>, app: &mut App) -> anyhow::Result<()> {
 	loop {
 		terminal.draw(|frame| ui::draw(frame, app))?;
 
-		if poll(Duration::from_millis(50))? {
-			if let Ok(event) = read() {
-				match event {
-					Event::Key(key) => {
-						let should_quit = app.handle_key_event(key)?;
-						if should_quit {
-							return Ok(());
-						}
-					},
-					Event::Paste(text) => {
-						app.handle_paste(&text)?;
-					},
-					_ => {},
-				}
+		if poll(Duration::from_millis(50))?
+			&& let Ok(event) = read()
+		{
+			match event {
+				Event::Key(key) => {
+					let should_quit = app.handle_key_event(key)?;
+					if should_quit {
+						return Ok(());
+					}
+				},
+				Event::Paste(text) => {
+					app.handle_paste(&text);
+				},
+				_ => {},
 			}
 		}
 
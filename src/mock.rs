@@ -0,0 +1,452 @@
+use bytes::Bytes;
+use chrono::{DateTime, Local};
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::header::{HeaderName, HeaderValue};
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::request::HttpRequest;
+use crate::response::HttpResponse;
+use crate::utils::variables::substitute_static_variables;
+
+pub const DEFAULT_PORT: u16 = 8080;
+/// Pseudo-header of the Example tab holding the delay in milliseconds
+const DELAY_HEADER: &str = "X-Mock-Delay";
+/// Describe the stored body or the connection it came over, not a reply built from the example
+const SKIPPED_HEADERS: [&str; 5] =
+	["content-length", "content-encoding", "transfer-encoding", "connection", "keep-alive"];
+
+#[derive(Debug)]
+pub enum MockError {
+	StatusLine(String),
+	Header(String),
+	Delay(String),
+}
+
+impl std::fmt::Display for MockError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::StatusLine(line) => write!(f, "Invalid status line: {line}"),
+			Self::Header(header) => write!(f, "Invalid header format: {header}"),
+			Self::Delay(delay) => write!(f, "Invalid {DELAY_HEADER}, expected milliseconds: {delay}"),
+		}
+	}
+}
+
+impl std::error::Error for MockError {}
+
+/// What the mock server replies to a request matching the saved one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Example {
+	pub status: u16,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub body: String,
+	/// Milliseconds to wait before replying, e.g. to try out loading states
+	#[serde(default, skip_serializing_if = "is_zero")]
+	pub delay_ms: u64,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub headers: BTreeMap<String, String>,
+}
+
+// By reference for `skip_serializing_if`
+#[allow(clippy::trivially_copy_pass_by_ref)]
+const fn is_zero(value: &u64) -> bool {
+	*value == 0
+}
+
+impl Example {
+	pub fn from_response(response: &HttpResponse) -> Self {
+		Self {
+			status: response.status_code,
+			body: String::from_utf8_lossy(&response.bytes).into_owned(),
+			delay_ms: 0,
+			headers: response
+				.headers
+				.iter()
+				.filter(|(key, _)| !SKIPPED_HEADERS.iter().any(|skipped| key.eq_ignore_ascii_case(skipped)))
+				.map(|(key, value)| (key.clone(), value.clone()))
+				.collect(),
+		}
+	}
+
+	/// The Example tab's text: a status line such as `200 OK`, headers with an optional `X-Mock-Delay`, then the
+	/// body after a blank line.
+	pub fn to_text(&self) -> String {
+		let reason = StatusCode::from_u16(self.status).ok().and_then(|status| status.canonical_reason()).unwrap_or("");
+		let mut lines = vec![format!("{} {reason}", self.status).trim_end().to_string()];
+		if self.delay_ms > 0 {
+			lines.push(format!("{DELAY_HEADER}: {}", self.delay_ms));
+		}
+		lines.extend(self.headers.iter().map(|(key, value)| format!("{key}: {value}")));
+		if !self.body.is_empty() {
+			lines.push(String::new());
+			lines.push(self.body.clone());
+		}
+		lines.join("\n")
+	}
+
+	/// Reads [`Self::to_text`]; `None` when the text is blank. The status line may start with `HTTP/1.1`.
+	pub fn from_text(text: &str) -> anyhow::Result<Option<Self>, MockError> {
+		let mut lines = text.lines().skip_while(|line| line.trim().is_empty());
+		let Some(status_line) = lines.next() else {
+			return Ok(None);
+		};
+
+		let mut parts = status_line.split_whitespace().skip_while(|part| part.starts_with("HTTP/"));
+		let status = parts
+			.next()
+			.and_then(|status| status.parse::<u16>().ok())
+			.filter(|status| StatusCode::from_u16(*status).is_ok())
+			.ok_or_else(|| MockError::StatusLine(status_line.to_string()))?;
+
+		let mut example = Self { status, body: String::new(), delay_ms: 0, headers: BTreeMap::new() };
+		for line in lines.by_ref() {
+			if line.trim().is_empty() {
+				break;
+			}
+			let (key, value) = line.split_once(':').ok_or_else(|| MockError::Header(line.to_string()))?;
+			let (key, value) = (key.trim(), value.trim());
+			if key.eq_ignore_ascii_case(DELAY_HEADER) {
+				example.delay_ms = value.parse().map_err(|_| MockError::Delay(value.to_string()))?;
+			} else {
+				example.headers.insert(key.to_string(), value.to_string());
+			}
+		}
+		example.body = lines.collect::<Vec<_>>().join("\n").trim_end().to_string();
+
+		Ok(Some(example))
+	}
+}
+
+/// A saved request with an example, matched by method and path.
+#[derive(Debug, Clone)]
+pub struct Route {
+	pub name: String,
+	method: String,
+	/// `None` for path parameters, which match any segment
+	segments: Vec<Option<String>>,
+	example: Example,
+}
+
+impl Route {
+	/// How well `method` and `path` match, the number of literal segments, so `/users/me` beats `/users/{{id}}`.
+	fn score(&self, method: &str, path: &str) -> Option<usize> {
+		if !self.method.eq_ignore_ascii_case(method) {
+			return None;
+		}
+		let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+		if segments.len() != self.segments.len() {
+			return None;
+		}
+		segments.iter().zip(&self.segments).try_fold(0, |score, (segment, pattern)| match pattern {
+			Some(literal) if literal == segment => Some(score + 1),
+			Some(_) => None,
+			None => Some(score),
+		})
+	}
+}
+
+/// Routes for the requests that have an example; `variables` fill in the URLs, whose scheme and host are ignored.
+pub fn routes(requests: &[HttpRequest], variables: &HashMap<String, String>) -> Vec<Route> {
+	requests
+		.iter()
+		.filter_map(|request| {
+			let example = request.example.clone()?;
+			let url = substitute_static_variables(&request.url, variables);
+			let name = if request.name.is_empty() {
+				format!("{} {}", request.method.as_str(), request.url)
+			} else {
+				request.name.clone()
+			};
+			Some(Route { name, method: request.method.as_str().to_string(), segments: path_pattern(&url), example })
+		})
+		.collect()
+}
+
+/// Path segments of `url`, with `{{name}}`, `:name`, `{name}` and `*` segments as parameters.
+fn path_pattern(url: &str) -> Vec<Option<String>> {
+	let url = url.split(['?', '#']).next().unwrap_or_default();
+	// An unresolved `{{base}}` stands for the scheme and host
+	let path = url.strip_prefix("{{").map_or_else(
+		|| {
+			let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+			if url.starts_with('/') { url } else { without_scheme.find('/').map_or("", |start| &without_scheme[start..]) }
+		},
+		|rest| rest.split_once("}}").map_or("", |(_, path)| path),
+	);
+
+	path
+		.split('/')
+		.filter(|segment| !segment.is_empty())
+		.map(|segment| {
+			let parameter = segment == "*"
+				|| segment.starts_with(':')
+				|| (segment.starts_with("{{") && segment.ends_with("}}"))
+				|| (segment.starts_with('{') && segment.ends_with('}'));
+			(!parameter).then(|| segment.to_string())
+		})
+		.collect()
+}
+
+/// A request the mock server answered.
+#[derive(Debug, Clone)]
+pub struct Hit {
+	pub time: DateTime<Local>,
+	pub method: String,
+	/// With the query string
+	pub path: String,
+	pub status: u16,
+	/// Name of the matching route; `None` when nothing matched
+	pub route: Option<String>,
+}
+
+impl Hit {
+	pub fn formatted(&self) -> String {
+		format!(
+			"{} {} {} → {} {}",
+			self.time.format("%H:%M:%S"),
+			self.method,
+			self.path,
+			self.status,
+			self.route.as_deref().unwrap_or("(no mock)")
+		)
+	}
+}
+
+/// Serves the routes' examples until dropped.
+pub struct MockServer {
+	pub address: SocketAddr,
+	routes: Arc<RwLock<Vec<Route>>>,
+	hits: mpsc::UnboundedReceiver<Hit>,
+	task: JoinHandle<()>,
+}
+
+impl MockServer {
+	/// Listens on `address` right away; must be called within the Tokio runtime.
+	pub fn start(address: SocketAddr, routes: Vec<Route>) -> anyhow::Result<Self> {
+		let listener = std::net::TcpListener::bind(address)?;
+		listener.set_nonblocking(true)?;
+		let listener = TcpListener::from_std(listener)?;
+		let address = listener.local_addr()?;
+		let routes = Arc::new(RwLock::new(routes));
+		let (hits_tx, hits) = mpsc::unbounded_channel();
+
+		let shared = Arc::clone(&routes);
+		let task = tokio::spawn(async move {
+			loop {
+				let Ok((stream, _)) = listener.accept().await else {
+					continue;
+				};
+				let routes = Arc::clone(&shared);
+				let hits_tx = hits_tx.clone();
+				let service = hyper::service::service_fn(move |request| respond(request, Arc::clone(&routes), hits_tx.clone()));
+				tokio::spawn(async move {
+					if let Err(error) =
+						hyper::server::conn::http1::Builder::new().serve_connection(TokioIo::new(stream), service).await
+					{
+						tracing::debug!("Mock connection closed: {error}");
+					}
+				});
+			}
+		});
+
+		Ok(Self { address, routes, hits, task })
+	}
+
+	pub fn route_count(&self) -> usize {
+		self.routes.read().map_or(0, |routes| routes.len())
+	}
+
+	/// Serves `routes` from the next request on, e.g. after an example changed.
+	pub fn set_routes(&self, routes: Vec<Route>) {
+		if let Ok(mut current) = self.routes.write() {
+			*current = routes;
+		}
+	}
+
+	/// Hits since the last call.
+	pub fn take_hits(&mut self) -> Vec<Hit> {
+		std::iter::from_fn(|| self.hits.try_recv().ok()).collect()
+	}
+
+	pub async fn next_hit(&mut self) -> Option<Hit> {
+		self.hits.recv().await
+	}
+}
+
+impl Drop for MockServer {
+	fn drop(&mut self) {
+		self.task.abort();
+	}
+}
+
+async fn respond(
+	request: Request<Incoming>,
+	routes: Arc<RwLock<Vec<Route>>>,
+	hits: mpsc::UnboundedSender<Hit>,
+) -> anyhow::Result<Response<Full<Bytes>>, Infallible> {
+	let method = request.method().as_str().to_string();
+	let path = request.uri().path_and_query().map_or_else(|| String::from("/"), ToString::to_string);
+
+	let route = routes.read().ok().and_then(|routes| {
+		routes
+			.iter()
+			.filter_map(|route| route.score(&method, request.uri().path()).map(|score| (score, route)))
+			.max_by_key(|(score, _)| *score)
+			.map(|(_, route)| route.clone())
+	});
+
+	let mut response = match &route {
+		Some(route) => {
+			if route.example.delay_ms > 0 {
+				tokio::time::sleep(Duration::from_millis(route.example.delay_ms)).await;
+			}
+			example_response(&route.example)
+		},
+		// Lets browsers call the mocks from another origin
+		None if request.method() == Method::OPTIONS => {
+			let mut response = Response::new(Full::new(Bytes::new()));
+			*response.status_mut() = StatusCode::NO_CONTENT;
+			for (name, value) in [("access-control-allow-methods", "*"), ("access-control-allow-headers", "*")] {
+				response.headers_mut().insert(name, HeaderValue::from_static(value));
+			}
+			response
+		},
+		None => {
+			let body = serde_json::json!({ "error": format!("No mock for {method} {}", request.uri().path()) });
+			let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+			*response.status_mut() = StatusCode::NOT_FOUND;
+			response.headers_mut().insert("content-type", HeaderValue::from_static("application/json"));
+			response
+		},
+	};
+	response.headers_mut().entry("access-control-allow-origin").or_insert(HeaderValue::from_static("*"));
+
+	let _ = hits.send(Hit {
+		time: Local::now(),
+		method,
+		path,
+		status: response.status().as_u16(),
+		route: route.map(|route| route.name),
+	});
+	Ok(response)
+}
+
+fn example_response(example: &Example) -> Response<Full<Bytes>> {
+	let mut response = Response::new(Full::new(Bytes::from(example.body.clone())));
+	*response.status_mut() = StatusCode::from_u16(example.status).unwrap_or(StatusCode::OK);
+	for (key, value) in &example.headers {
+		if SKIPPED_HEADERS.iter().any(|skipped| key.eq_ignore_ascii_case(skipped)) {
+			continue;
+		}
+		if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(value)) {
+			response.headers_mut().append(name, value);
+		}
+	}
+	response
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::HttpMethod;
+	use crate::http_client::{HttpClient, SendOptions};
+
+	fn request(method: HttpMethod, url: &str, status: u16, body: &str) -> HttpRequest {
+		let mut request = HttpRequest::new();
+		request.method = method;
+		request.url = url.to_string();
+		request.example = Some(Example { status, body: body.to_string(), delay_ms: 0, headers: BTreeMap::new() });
+		request
+	}
+
+	#[test]
+	fn test_path_patterns() {
+		assert_eq!(path_pattern("https://api.example.com/users/{{id}}?page=1"), vec![Some(String::from("users")), None]);
+		assert_eq!(
+			path_pattern("{{base}}/users/:id/posts"),
+			vec![Some(String::from("users")), None, Some(String::from("posts"))]
+		);
+		assert_eq!(path_pattern("localhost:3000/health/"), vec![Some(String::from("health"))]);
+		assert_eq!(path_pattern("/orders/{id}"), vec![Some(String::from("orders")), None]);
+		assert!(path_pattern("https://example.com").is_empty());
+	}
+
+	#[test]
+	fn test_example_text_round_trip() {
+		let text = "HTTP/1.1 201 Created\nX-Mock-Delay: 250\nContent-Type: application/json\n\n{\n  \"id\": 1\n}\n";
+		let example = Example::from_text(text).unwrap().unwrap();
+		assert_eq!(example.status, 201);
+		assert_eq!(example.delay_ms, 250);
+		assert_eq!(example.headers["Content-Type"], "application/json");
+		assert_eq!(example.body, "{\n  \"id\": 1\n}");
+		assert_eq!(
+			example.to_text(),
+			"201 Created\nX-Mock-Delay: 250\nContent-Type: application/json\n\n{\n  \"id\": 1\n}"
+		);
+		assert_eq!(Example::from_text(&example.to_text()).unwrap(), Some(example));
+
+		assert_eq!(Example::from_text("\n \n").unwrap(), None);
+		assert!(Example::from_text("OK").is_err());
+		assert!(Example::from_text("200\nX-Mock-Delay: soon").is_err());
+	}
+
+	#[tokio::test]
+	async fn test_serves_examples() {
+		let mut with_header = request(HttpMethod::Get, "{{base}}/users/me", 200, "{\"name\":\"me\"}");
+		with_header.name = String::from("Current user");
+		if let Some(example) = &mut with_header.example {
+			example.headers.insert(String::from("Content-Type"), String::from("application/json"));
+			example.headers.insert(String::from("Content-Length"), String::from("999"));
+		}
+		let requests = vec![
+			request(HttpMethod::Get, "{{base}}/users/{{id}}", 200, "a user"),
+			with_header,
+			request(HttpMethod::Post, "{{base}}/users", 201, "created"),
+			HttpRequest::new(),
+		];
+		let mut server =
+			MockServer::start(SocketAddr::from(([127, 0, 0, 1], 0)), routes(&requests, &HashMap::new())).unwrap();
+		assert_eq!(server.route_count(), 3);
+		let base = format!("http://{}", server.address);
+
+		let client = HttpClient::new();
+		let send = |method: HttpMethod, path: &str| {
+			let mut request = HttpRequest::new();
+			request.method = method;
+			request.url = format!("{base}{path}");
+			let client = &client;
+			async move { client.send_request(&request, SendOptions::default()).await.unwrap() }
+		};
+
+		let response = send(HttpMethod::Get, "/users/me").await;
+		assert_eq!((response.status_code, response.body.as_str()), (200, "{\"name\":\"me\"}"));
+		assert_eq!(response.headers["content-type"], "application/json");
+		assert_eq!(response.headers["content-length"], "13");
+		assert_eq!(response.headers["access-control-allow-origin"], "*");
+
+		assert_eq!(send(HttpMethod::Get, "/users/42?full=true").await.body, "a user");
+		assert_eq!(send(HttpMethod::Post, "/users").await.status_code, 201);
+		assert_eq!(send(HttpMethod::Delete, "/users").await.status_code, 404);
+
+		let hits = server.take_hits();
+		assert_eq!(hits.len(), 4);
+		assert_eq!(hits[0].route.as_deref(), Some("Current user"));
+		assert_eq!(hits[1].path, "/users/42?full=true");
+		assert_eq!(hits[3].route, None);
+		assert!(hits[3].formatted().ends_with("DELETE /users → 404 (no mock)"));
+
+		server.set_routes(Vec::new());
+		assert_eq!(send(HttpMethod::Get, "/users/me").await.status_code, 404);
+	}
+}
@@ -0,0 +1,508 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+use crate::{app::HttpMethod, response::HttpResponse};
+
+const MAX_REF_DEPTH: usize = 32;
+
+#[derive(Debug)]
+pub enum OpenApiError {
+	InvalidDocument(String),
+	MissingPaths,
+}
+
+impl std::fmt::Display for OpenApiError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::InvalidDocument(message) => write!(f, "Invalid OpenAPI document: {message}"),
+			Self::MissingPaths => write!(f, "OpenAPI document has no `paths` object"),
+		}
+	}
+}
+
+impl std::error::Error for OpenApiError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Violation {
+	pub path: String,
+	pub message: String,
+}
+
+impl Violation {
+	fn new(path: &str, message: String) -> Self {
+		Self { path: path.to_string(), message }
+	}
+}
+
+impl std::fmt::Display for Violation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}: {}", self.path, self.message)
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenApiSpec {
+	document: Value,
+}
+
+impl OpenApiSpec {
+	pub fn load(path: &Path) -> anyhow::Result<Self> {
+		let content = std::fs::read_to_string(path)?;
+		let document: Value = if path.extension().is_some_and(|extension| extension == "json") {
+			serde_json::from_str(&content).map_err(|error| OpenApiError::InvalidDocument(error.to_string()))?
+		} else {
+			serde_yaml::from_str(&content).map_err(|error| OpenApiError::InvalidDocument(error.to_string()))?
+		};
+
+		Ok(Self::from_value(document)?)
+	}
+
+	pub fn from_value(document: Value) -> Result<Self, OpenApiError> {
+		if !document.get("paths").is_some_and(Value::is_object) {
+			return Err(OpenApiError::MissingPaths);
+		}
+
+		Ok(Self { document })
+	}
+
+	pub fn validate(&self, method: &HttpMethod, url: &str, response: &HttpResponse) -> Vec<Violation> {
+		let request_path = url_path(url);
+
+		let Some((template, path_item)) = self.find_path(&request_path) else {
+			return vec![Violation::new("path", format!("{request_path} is not declared in the spec"))];
+		};
+
+		let Some(operation) = path_item.get(method.as_str().to_lowercase()).map(|operation| self.resolve(operation)) else {
+			return vec![Violation::new("method", format!("{} {template} is not declared in the spec", method.as_str()))];
+		};
+
+		let Some(declared) = self.find_response(operation, response.status_code) else {
+			return vec![Violation::new(
+				"status",
+				format!("status {} is not declared for {} {template}", response.status_code, method.as_str()),
+			)];
+		};
+
+		let Some(content) = declared.get("content").and_then(Value::as_object).filter(|content| !content.is_empty()) else {
+			return Vec::new();
+		};
+
+		let Some(content_type) = response.content_type().map(|value| media_type(value)) else {
+			if response.bytes.is_empty() {
+				return Vec::new();
+			}
+			return vec![Violation::new("content-type", "response has a body but no content type".to_string())];
+		};
+
+		let Some(media) = find_media(content, &content_type) else {
+			let declared_types = content.keys().cloned().collect::<Vec<_>>().join(", ");
+			return vec![Violation::new(
+				"content-type",
+				format!("{content_type} is not declared (expected one of: {declared_types})"),
+			)];
+		};
+
+		let Some(schema) = media.get("schema") else {
+			return Vec::new();
+		};
+
+		if !content_type.contains("json") {
+			return Vec::new();
+		}
+
+		match serde_json::from_slice::<Value>(&response.bytes) {
+			Ok(body) => {
+				let mut violations = Vec::new();
+				self.validate_schema(schema, &body, "$", &mut violations);
+				violations
+			},
+			Err(error) => vec![Violation::new("$", format!("body is not valid JSON: {error}"))],
+		}
+	}
+
+	fn find_path(&self, request_path: &str) -> Option<(&str, &Value)> {
+		let paths = self.document.get("paths")?.as_object()?;
+
+		let mut candidates = vec![request_path.to_string()];
+		for base_path in self.server_base_paths() {
+			if let Some(stripped) = request_path.strip_prefix(&base_path)
+				&& (stripped.is_empty() || stripped.starts_with('/'))
+			{
+				candidates.push(if stripped.is_empty() { String::from("/") } else { stripped.to_string() });
+			}
+		}
+
+		candidates
+			.iter()
+			.flat_map(|candidate| {
+				paths.iter().filter_map(move |(template, item)| {
+					match_template(template, candidate).map(|literal_segments| (literal_segments, template.as_str(), item))
+				})
+			})
+			.max_by_key(|(literal_segments, ..)| *literal_segments)
+			.map(|(_, template, item)| (template, self.resolve(item)))
+	}
+
+	fn server_base_paths(&self) -> Vec<String> {
+		self
+			.document
+			.get("servers")
+			.and_then(Value::as_array)
+			.map(|servers| {
+				servers
+					.iter()
+					.filter_map(|server| server.get("url").and_then(Value::as_str))
+					.map(|server_url| {
+						let path = if server_url.contains("://") { url_path(server_url) } else { server_url.to_string() };
+						path.trim_end_matches('/').to_string()
+					})
+					.filter(|path| !path.is_empty())
+					.collect()
+			})
+			.unwrap_or_default()
+	}
+
+	fn find_response<'a>(&'a self, operation: &'a Value, status_code: u16) -> Option<&'a Value> {
+		let responses = operation.get("responses")?.as_object()?;
+		let status_range = format!("{}XX", status_code / 100);
+
+		responses
+			.get(&status_code.to_string())
+			.or_else(|| responses.iter().find(|(key, _)| key.eq_ignore_ascii_case(&status_range)).map(|(_, value)| value))
+			.or_else(|| responses.get("default"))
+			.map(|response| self.resolve(response))
+	}
+
+	fn resolve<'a>(&'a self, mut value: &'a Value) -> &'a Value {
+		for _ in 0..MAX_REF_DEPTH {
+			let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+				break;
+			};
+			let Some(target) = reference.strip_prefix('#').and_then(|pointer| self.document.pointer(pointer)) else {
+				break;
+			};
+			value = target;
+		}
+		value
+	}
+
+	fn validate_schema(&self, schema: &Value, instance: &Value, path: &str, violations: &mut Vec<Violation>) {
+		let schema = self.resolve(schema);
+
+		if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+			for sub_schema in all_of {
+				self.validate_schema(sub_schema, instance, path, violations);
+			}
+		}
+
+		if let Some(any_of) = schema.get("anyOf").and_then(Value::as_array)
+			&& self.count_matching(any_of, instance, path) == 0
+		{
+			violations.push(Violation::new(path, "does not match any of the `anyOf` schemas".to_string()));
+		}
+
+		if let Some(one_of) = schema.get("oneOf").and_then(Value::as_array) {
+			let matching = self.count_matching(one_of, instance, path);
+			if matching != 1 {
+				violations
+					.push(Violation::new(path, format!("matches {matching} of the `oneOf` schemas, expected exactly one")));
+			}
+		}
+
+		if instance.is_null() && is_nullable(schema) {
+			return;
+		}
+
+		if let Some(expected) = schema.get("type") {
+			let expected_types: Vec<&str> = match expected {
+				Value::String(expected_type) => vec![expected_type.as_str()],
+				Value::Array(expected_types) => expected_types.iter().filter_map(Value::as_str).collect(),
+				_ => Vec::new(),
+			};
+
+			if !expected_types.is_empty() && !expected_types.iter().any(|expected_type| matches_type(expected_type, instance))
+			{
+				violations.push(Violation::new(
+					path,
+					format!("expected {}, found {}", expected_types.join(" or "), json_type_name(instance)),
+				));
+				return;
+			}
+		}
+
+		if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+			&& !allowed.contains(instance)
+		{
+			violations.push(Violation::new(path, format!("{instance} is not one of the allowed values")));
+		}
+
+		match instance {
+			Value::Object(object) => {
+				if let Some(required) = schema.get("required").and_then(Value::as_array) {
+					for field in required.iter().filter_map(Value::as_str) {
+						if !object.contains_key(field) {
+							violations.push(Violation::new(path, format!("missing required field `{field}`")));
+						}
+					}
+				}
+
+				let properties = schema.get("properties").and_then(Value::as_object);
+				for (key, value) in object {
+					let field_path = format!("{path}.{key}");
+					if let Some(property_schema) = properties.and_then(|properties| properties.get(key)) {
+						self.validate_schema(property_schema, value, &field_path, violations);
+					} else {
+						match schema.get("additionalProperties") {
+							Some(Value::Bool(false)) => {
+								violations.push(Violation::new(path, format!("unexpected field `{key}`")));
+							},
+							Some(additional_schema @ Value::Object(_)) => {
+								self.validate_schema(additional_schema, value, &field_path, violations);
+							},
+							_ => {},
+						}
+					}
+				}
+			},
+			Value::Array(items) => {
+				if let Some(item_schema) = schema.get("items") {
+					for (index, item) in items.iter().enumerate() {
+						self.validate_schema(item_schema, item, &format!("{path}[{index}]"), violations);
+					}
+				}
+			},
+			_ => {},
+		}
+	}
+
+	fn count_matching(&self, schemas: &[Value], instance: &Value, path: &str) -> usize {
+		schemas
+			.iter()
+			.filter(|sub_schema| {
+				let mut sub_violations = Vec::new();
+				self.validate_schema(sub_schema, instance, path, &mut sub_violations);
+				sub_violations.is_empty()
+			})
+			.count()
+	}
+}
+
+fn url_path(url: &str) -> String {
+	let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+	let path = without_scheme.find('/').map_or("/", |index| &without_scheme[index..]);
+	let path = path.split(['?', '#']).next().unwrap_or("/");
+
+	if path.len() > 1 { path.trim_end_matches('/').to_string() } else { path.to_string() }
+}
+
+/// Returns the number of literal (non-templated) segments when `path` matches `template`.
+fn match_template(template: &str, path: &str) -> Option<usize> {
+	let template_segments: Vec<&str> = template.trim_end_matches('/').split('/').collect();
+	let path_segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+
+	if template_segments.len() != path_segments.len() {
+		return None;
+	}
+
+	let mut literal_segments = 0;
+	for (template_segment, path_segment) in template_segments.iter().zip(&path_segments) {
+		if template_segment.starts_with('{') && template_segment.ends_with('}') {
+			if path_segment.is_empty() {
+				return None;
+			}
+		} else if template_segment == path_segment {
+			literal_segments += 1;
+		} else {
+			return None;
+		}
+	}
+
+	Some(literal_segments)
+}
+
+fn media_type(content_type: &str) -> String {
+	content_type.split(';').next().unwrap_or_default().trim().to_lowercase()
+}
+
+fn find_media<'a>(content: &'a serde_json::Map<String, Value>, content_type: &str) -> Option<&'a Value> {
+	let wildcard = content_type.split_once('/').map(|(kind, _)| format!("{kind}/*"));
+
+	content
+		.iter()
+		.find(|(declared, _)| media_type(declared) == content_type)
+		.or_else(|| wildcard.and_then(|wildcard| content.iter().find(|(declared, _)| media_type(declared) == wildcard)))
+		.or_else(|| content.iter().find(|(declared, _)| declared.as_str() == "*/*"))
+		.map(|(_, media)| media)
+}
+
+fn is_nullable(schema: &Value) -> bool {
+	schema.get("nullable").and_then(Value::as_bool).unwrap_or(false)
+		|| match schema.get("type") {
+			Some(Value::Array(types)) => types.iter().any(|expected_type| expected_type == "null"),
+			Some(Value::String(expected_type)) => expected_type == "null",
+			_ => false,
+		}
+}
+
+fn matches_type(expected_type: &str, instance: &Value) -> bool {
+	match expected_type {
+		"string" => instance.is_string(),
+		"number" => instance.is_number(),
+		"integer" => {
+			instance.is_i64() || instance.is_u64() || instance.as_f64().is_some_and(|number| number.fract() == 0.0)
+		},
+		"boolean" => instance.is_boolean(),
+		"array" => instance.is_array(),
+		"object" => instance.is_object(),
+		"null" => instance.is_null(),
+		_ => true,
+	}
+}
+
+const fn json_type_name(instance: &Value) -> &'static str {
+	match instance {
+		Value::Null => "null",
+		Value::Bool(_) => "boolean",
+		Value::Number(_) => "number",
+		Value::String(_) => "string",
+		Value::Array(_) => "array",
+		Value::Object(_) => "object",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::time::Duration;
+
+	use serde_json::json;
+
+	use super::*;
+
+	fn spec() -> OpenApiSpec {
+		OpenApiSpec::from_value(json!({
+			"openapi": "3.0.3",
+			"servers": [{ "url": "https://api.example.com/v1" }],
+			"paths": {
+				"/pets/{id}": {
+					"get": {
+						"responses": {
+							"200": {
+								"description": "A pet",
+								"content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } }
+							},
+							"4XX": { "description": "Client error" }
+						}
+					}
+				},
+				"/pets/mine": {
+					"get": {
+						"responses": {
+							"200": {
+								"description": "Pets",
+								"content": {
+									"application/json": { "schema": { "type": "array", "items": { "$ref": "#/components/schemas/Pet" } } }
+								}
+							}
+						}
+					}
+				}
+			},
+			"components": {
+				"schemas": {
+					"Pet": {
+						"type": "object",
+						"required": ["id", "name"],
+						"properties": {
+							"id": { "type": "integer" },
+							"name": { "type": "string" },
+							"tag": { "type": "string", "nullable": true }
+						}
+					}
+				}
+			}
+		}))
+		.unwrap()
+	}
+
+	fn response(status_code: u16, body: &str) -> HttpResponse {
+		let headers = HashMap::from([(String::from("content-type"), String::from("application/json; charset=utf-8"))]);
+		HttpResponse::new(
+			String::new(),
+			status_code,
+			String::new(),
+			headers,
+			body.as_bytes().to_vec(),
+			Duration::from_millis(1),
+		)
+	}
+
+	#[test]
+	fn test_valid_response_has_no_violations() {
+		let violations = spec().validate(
+			&HttpMethod::Get,
+			"https://api.example.com/v1/pets/42?verbose=true",
+			&response(200, r#"{"id": 42, "name": "Rex", "tag": null}"#),
+		);
+
+		assert_eq!(violations, Vec::new());
+	}
+
+	#[test]
+	fn test_missing_required_field_and_wrong_type() {
+		let violations =
+			spec().validate(&HttpMethod::Get, "https://api.example.com/v1/pets/42", &response(200, r#"{"id": "42"}"#));
+
+		assert_eq!(
+			violations,
+			vec![
+				Violation::new("$", String::from("missing required field `name`")),
+				Violation::new("$.id", String::from("expected integer, found string")),
+			]
+		);
+	}
+
+	#[test]
+	fn test_literal_path_is_preferred_over_template() {
+		let violations = spec().validate(
+			&HttpMethod::Get,
+			"https://api.example.com/v1/pets/mine",
+			&response(200, r#"[{"id": 1, "name": "Rex"}, {"id": 2}]"#),
+		);
+
+		assert_eq!(violations, vec![Violation::new("$[1]", String::from("missing required field `name`"))]);
+	}
+
+	#[test]
+	fn test_status_range_and_undeclared_status() {
+		let spec = spec();
+
+		assert!(spec.validate(&HttpMethod::Get, "https://api.example.com/v1/pets/1", &response(404, "")).is_empty());
+
+		let violations = spec.validate(&HttpMethod::Get, "https://api.example.com/v1/pets/1", &response(500, ""));
+		assert_eq!(
+			violations,
+			vec![Violation::new("status", String::from("status 500 is not declared for GET /pets/{id}"))]
+		);
+	}
+
+	#[test]
+	fn test_undeclared_method_and_path() {
+		let spec = spec();
+
+		let violations = spec.validate(&HttpMethod::Delete, "https://api.example.com/v1/pets/1", &response(200, "{}"));
+		assert_eq!(
+			violations,
+			vec![Violation::new("method", String::from("DELETE /pets/{id} is not declared in the spec"))]
+		);
+
+		let violations = spec.validate(&HttpMethod::Get, "https://api.example.com/v1/owners", &response(200, "{}"));
+		assert_eq!(violations, vec![Violation::new("path", String::from("/v1/owners is not declared in the spec"))]);
+	}
+
+	#[test]
+	fn test_document_without_paths_is_rejected() {
+		assert!(matches!(OpenApiSpec::from_value(json!({ "openapi": "3.0.3" })), Err(OpenApiError::MissingPaths)));
+	}
+}
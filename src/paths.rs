@@ -0,0 +1,80 @@
+use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, OnceLock};
+
+use crate::logger::PROJECT_NAME;
+
+// Platform directories already honor XDG_CONFIG_HOME, XDG_DATA_HOME, XDG_CACHE_HOME and XDG_STATE_HOME
+static PROJECT_DIRS: LazyLock<Option<ProjectDirs>> =
+	LazyLock::new(|| ProjectDirs::from("", "", env!("CARGO_PKG_NAME")));
+
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Fallback when the platform directories cannot be determined (e.g. no home directory).
+fn fallback_dir() -> PathBuf {
+	PathBuf::from(".").join(".data")
+}
+
+/// Picks the first of: explicit override, environment variable, platform directory, fallback.
+fn resolve_dir(explicit: Option<&Path>, env_value: Option<String>, platform: Option<&Path>) -> PathBuf {
+	explicit
+		.map(Path::to_path_buf)
+		.or_else(|| env_value.filter(|value| !value.is_empty()).map(PathBuf::from))
+		.or_else(|| platform.map(Path::to_path_buf))
+		.unwrap_or_else(fallback_dir)
+}
+
+fn env_override(kind: &str) -> Option<String> {
+	std::env::var(format!("{}_{kind}_DIR", *PROJECT_NAME)).ok()
+}
+
+/// Overrides the data directory for this process, as set by `--data-dir`.
+pub fn set_data_dir(path: PathBuf) {
+	let _ = DATA_DIR_OVERRIDE.set(path);
+}
+
+/// User configuration, `$RESTO_CONFIG_DIR` or e.g. `~/.config/resto`.
+pub fn config_dir() -> PathBuf {
+	resolve_dir(None, env_override("CONFIG"), PROJECT_DIRS.as_ref().map(ProjectDirs::config_dir))
+}
+
+/// Persistent user data such as the global workspace, `--data-dir`, `$RESTO_DATA_DIR` or e.g. `~/.local/share/resto`.
+pub fn data_dir() -> PathBuf {
+	resolve_dir(
+		DATA_DIR_OVERRIDE.get().map(PathBuf::as_path),
+		env_override("DATA"),
+		PROJECT_DIRS.as_ref().map(ProjectDirs::data_dir),
+	)
+}
+
+/// Disposable cached data, `$RESTO_CACHE_DIR` or e.g. `~/.cache/resto`.
+pub fn cache_dir() -> PathBuf {
+	resolve_dir(None, env_override("CACHE"), PROJECT_DIRS.as_ref().map(ProjectDirs::cache_dir))
+}
+
+/// Logs and other state worth keeping between runs, `$RESTO_STATE_DIR` or e.g. `~/.local/state/resto`.
+/// Platforms without a state directory use the data directory.
+pub fn state_dir() -> PathBuf {
+	let platform = PROJECT_DIRS.as_ref().and_then(ProjectDirs::state_dir);
+	match (env_override("STATE"), platform) {
+		(Some(value), _) if !value.is_empty() => PathBuf::from(value),
+		(_, Some(platform)) if DATA_DIR_OVERRIDE.get().is_none() => platform.to_path_buf(),
+		_ => data_dir(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_resolve_dir_precedence() {
+		let explicit = Path::new("/explicit");
+		let platform = Path::new("/platform");
+
+		assert_eq!(resolve_dir(Some(explicit), Some(String::from("/env")), Some(platform)), explicit);
+		assert_eq!(resolve_dir(None, Some(String::from("/env")), Some(platform)), Path::new("/env"));
+		assert_eq!(resolve_dir(None, Some(String::new()), Some(platform)), platform);
+		assert_eq!(resolve_dir(None, None, None), fallback_dir());
+	}
+}
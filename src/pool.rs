@@ -0,0 +1,193 @@
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::client::conn::{http1, http2};
+use hyper::{Request, Response};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::connection::TlsInfo;
+
+/// Idle connections are closed after this long, before servers tend to drop them.
+const IDLE_TIMEOUT: Duration = Duration::from_mins(1);
+/// Idle HTTP/1.1 connections kept per origin.
+const MAX_IDLE_PER_ORIGIN: usize = 4;
+
+/// The sending half of an open HTTP connection.
+pub enum Sender {
+	Http1(http1::SendRequest<Full<Bytes>>),
+	/// Multiplexed, so shared by every request to its origin
+	Http2(http2::SendRequest<Full<Bytes>>),
+}
+
+/// Why a send failed, and whether the request can go out on another connection instead.
+pub struct SendError {
+	pub error: hyper::Error,
+	/// The connection closed before any of the request was written
+	pub unsent: bool,
+}
+
+impl Sender {
+	pub const fn is_http2(&self) -> bool {
+		matches!(self, Self::Http2(_))
+	}
+
+	fn is_closed(&self) -> bool {
+		match self {
+			Self::Http1(sender) => sender.is_closed(),
+			Self::Http2(sender) => sender.is_closed(),
+		}
+	}
+
+	/// Waits until the connection can take a request, failing if it closed meanwhile.
+	pub async fn ready(&mut self) -> hyper::Result<()> {
+		match self {
+			Self::Http1(sender) => sender.ready().await,
+			Self::Http2(sender) => sender.ready().await,
+		}
+	}
+
+	pub async fn send(&mut self, request: Request<Full<Bytes>>) -> Result<Response<Incoming>, SendError> {
+		let result = match self {
+			Self::Http1(sender) => sender.try_send_request(request).await,
+			Self::Http2(sender) => sender.try_send_request(request).await,
+		};
+		result.map_err(|mut error| {
+			let unsent = error.take_message().is_some();
+			SendError { error: error.into_error(), unsent }
+		})
+	}
+}
+
+/// A connection along with what's shown about it on every response it carries.
+pub struct Pooled {
+	pub sender: Sender,
+	pub remote_address: SocketAddr,
+	pub tls: Option<TlsInfo>,
+}
+
+impl Pooled {
+	fn shared(&self) -> Option<Self> {
+		let Sender::Http2(sender) = &self.sender else {
+			return None;
+		};
+		Some(Self { sender: Sender::Http2(sender.clone()), remote_address: self.remote_address, tls: self.tls.clone() })
+	}
+}
+
+/// Open connections by origin, reused instead of connecting again.
+///
+/// An HTTP/1.1 connection is taken out while it carries a request and put back once its response is read;
+/// an HTTP/2 one stays in and is shared.
+#[derive(Default)]
+pub struct Pool {
+	idle: Mutex<HashMap<String, Vec<(Pooled, Instant)>>>,
+}
+
+impl Pool {
+	/// A connection to `origin` that's still open, if there is one.
+	pub fn take(&self, origin: &str) -> Option<Pooled> {
+		let mut idle = self.idle.lock().ok()?;
+		let connections = idle.get_mut(origin)?;
+		connections.retain(|(pooled, since)| !pooled.sender.is_closed() && since.elapsed() < IDLE_TIMEOUT);
+
+		let shared = connections.iter().find_map(|(pooled, _)| pooled.shared());
+		let pooled = shared.or_else(|| connections.pop().map(|(pooled, _)| pooled));
+		if connections.is_empty() {
+			idle.remove(origin);
+		}
+		pooled
+	}
+
+	/// Keeps `pooled` for the next request to `origin`.
+	pub fn put(&self, origin: &str, pooled: Pooled) {
+		let Ok(mut idle) = self.idle.lock() else {
+			return;
+		};
+		let connections = idle.entry(origin.to_string()).or_default();
+		if pooled.sender.is_http2() {
+			// One is enough, and replaces one that went away
+			connections.retain(|(pooled, _)| !pooled.sender.is_http2());
+		} else if connections.len() >= MAX_IDLE_PER_ORIGIN {
+			connections.remove(0);
+		}
+		connections.push((pooled, Instant::now()));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hyper_util::rt::{TokioExecutor, TokioIo};
+
+	use super::*;
+
+	/// A connection over an in-memory pipe whose other end stays open, told apart by its `port`.
+	async fn connection(http2: bool, port: u16) -> Pooled {
+		let (client, server) = tokio::io::duplex(64 * 1024);
+		tokio::spawn(async move {
+			let _server = server;
+			std::future::pending::<()>().await;
+		});
+
+		let sender = if http2 {
+			let (sender, connection) = http2::handshake(TokioExecutor::new(), TokioIo::new(client)).await.unwrap();
+			tokio::spawn(connection);
+			Sender::Http2(sender)
+		} else {
+			let (sender, connection) = http1::handshake(TokioIo::new(client)).await.unwrap();
+			tokio::spawn(connection);
+			Sender::Http1(sender)
+		};
+		Pooled { sender, remote_address: SocketAddr::from(([127, 0, 0, 1], port)), tls: None }
+	}
+
+	/// Which connection `take` gave out, by its port.
+	fn take_port(pool: &Pool, origin: &str) -> Option<u16> {
+		pool.take(origin).map(|pooled| pooled.remote_address.port())
+	}
+
+	#[tokio::test]
+	async fn test_keeps_the_latest_http1_connections_per_origin() {
+		let pool = Pool::default();
+		for port in 1..=5 {
+			pool.put("http://a.test", connection(false, port).await);
+		}
+		pool.put("http://b.test", connection(false, 6).await);
+
+		// The oldest went over the limit of 4; the newest is taken first
+		let taken: Vec<Option<u16>> = (0..5).map(|_| take_port(&pool, "http://a.test")).collect();
+		assert_eq!(taken, vec![Some(5), Some(4), Some(3), Some(2), None]);
+		assert_eq!(take_port(&pool, "http://b.test"), Some(6));
+		assert_eq!(take_port(&pool, "http://c.test"), None);
+	}
+
+	#[tokio::test]
+	async fn test_shares_one_http2_connection_per_origin() {
+		let pool = Pool::default();
+		pool.put("https://a.test", connection(true, 1).await);
+
+		assert_eq!(take_port(&pool, "https://a.test"), Some(1));
+		assert_eq!(take_port(&pool, "https://a.test"), Some(1));
+
+		// A new one replaces it rather than adding to it
+		pool.put("https://a.test", connection(true, 2).await);
+		assert_eq!(take_port(&pool, "https://a.test"), Some(2));
+		assert_eq!(pool.idle.lock().unwrap()["https://a.test"].len(), 1);
+	}
+
+	#[tokio::test]
+	async fn test_drops_connections_idle_for_too_long() {
+		let pool = Pool::default();
+		pool.put("http://a.test", connection(false, 1).await);
+		pool.put("http://a.test", connection(false, 2).await);
+
+		let expired = Instant::now().checked_sub(IDLE_TIMEOUT).unwrap();
+		pool.idle.lock().unwrap().get_mut("http://a.test").unwrap()[1].1 = expired;
+
+		assert_eq!(take_port(&pool, "http://a.test"), Some(1));
+		assert_eq!(take_port(&pool, "http://a.test"), None);
+		assert!(pool.idle.lock().unwrap().is_empty());
+	}
+}
@@ -0,0 +1,320 @@
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::{HeaderName, HeaderValue};
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::http_client::{HttpClient, SendOptions};
+use crate::request::HttpRequest;
+use crate::response::HttpResponse;
+
+pub const DEFAULT_PORT: u16 = 8888;
+/// Describe a single connection rather than the message, so they aren't forwarded or recorded
+const HOP_BY_HOP_HEADERS: [&str; 11] = [
+	"connection",
+	"keep-alive",
+	"proxy-connection",
+	"proxy-authenticate",
+	"proxy-authorization",
+	"te",
+	"trailer",
+	"transfer-encoding",
+	"upgrade",
+	"host",
+	"content-length",
+];
+
+/// A request the proxy forwarded and the response it got back.
+#[derive(Debug, Clone)]
+pub struct Capture {
+	pub request: HttpRequest,
+	/// Decoded for display; the client got the body as transferred
+	pub response: HttpResponse,
+}
+
+/// Forwards plain HTTP requests, recording each exchange, and tunnels `CONNECT`s unrecorded until dropped.
+pub struct ProxyServer {
+	pub address: SocketAddr,
+	captures: mpsc::UnboundedReceiver<Capture>,
+	task: JoinHandle<()>,
+}
+
+impl ProxyServer {
+	/// Listens on `address` right away; must be called within the Tokio runtime.
+	pub fn start(address: SocketAddr, client: HttpClient) -> anyhow::Result<Self> {
+		let listener = std::net::TcpListener::bind(address)?;
+		listener.set_nonblocking(true)?;
+		let listener = TcpListener::from_std(listener)?;
+		let address = listener.local_addr()?;
+		let (captures_tx, captures) = mpsc::unbounded_channel();
+
+		let task = tokio::spawn(async move {
+			loop {
+				let Ok((stream, _)) = listener.accept().await else {
+					continue;
+				};
+				let client = client.clone();
+				let captures_tx = captures_tx.clone();
+				let service = hyper::service::service_fn(move |request| forward(request, client.clone(), captures_tx.clone()));
+				tokio::spawn(async move {
+					if let Err(error) = hyper::server::conn::http1::Builder::new()
+						.serve_connection(TokioIo::new(stream), service)
+						.with_upgrades()
+						.await
+					{
+						tracing::debug!("Proxy connection closed: {error}");
+					}
+				});
+			}
+		});
+
+		Ok(Self { address, captures, task })
+	}
+
+	/// Exchanges since the last call.
+	pub fn take_captures(&mut self) -> Vec<Capture> {
+		std::iter::from_fn(|| self.captures.try_recv().ok()).collect()
+	}
+
+	#[cfg(test)]
+	async fn next_capture(&mut self) -> Option<Capture> {
+		self.captures.recv().await
+	}
+}
+
+impl Drop for ProxyServer {
+	fn drop(&mut self) {
+		self.task.abort();
+	}
+}
+
+async fn forward(
+	request: Request<Incoming>,
+	client: HttpClient,
+	captures: mpsc::UnboundedSender<Capture>,
+) -> anyhow::Result<Response<Full<Bytes>>, Infallible> {
+	if request.method() == Method::CONNECT {
+		return Ok(tunnel(request));
+	}
+	if request.uri().scheme().is_none() {
+		return Ok(error_response(
+			StatusCode::BAD_REQUEST,
+			"resto is a proxy: configure it as the HTTP proxy instead of calling it directly",
+		));
+	}
+
+	let request = match captured_request(request).await {
+		Ok(request) => request,
+		Err(error) => return Ok(error_response(StatusCode::BAD_REQUEST, &error.to_string())),
+	};
+	// Each side gets exactly what the other sent: no added headers, redirects and compression passed on
+	let options =
+		SendOptions { decompress: false, follow_redirects: false, default_headers: false, ..SendOptions::default() };
+	let mut response = match client.send_request(&request, options).await {
+		Ok(response) => response,
+		Err(error) => return Ok(error_response(StatusCode::BAD_GATEWAY, &format!("{}: {error}", request.url))),
+	};
+
+	let forwarded = forwarded_response(&response);
+	if let Err(error) = response.decode_body() {
+		tracing::warn!("Keeping the undecoded body: {error}");
+	}
+	// Edits to the body in the Request tab must take effect when it's sent again
+	let request = HttpRequest { raw_body: None, ..request };
+	let _ = captures.send(Capture { request, response });
+	Ok(forwarded)
+}
+
+/// The incoming request as one that can be edited and sent again from the Request tab, its body's bytes as received.
+async fn captured_request(request: Request<Incoming>) -> anyhow::Result<HttpRequest> {
+	let (parts, body) = request.into_parts();
+	let body = body.collect().await?.to_bytes();
+
+	let mut headers: HashMap<String, String> = HashMap::new();
+	for (key, value) in &parts.headers {
+		if HOP_BY_HOP_HEADERS.contains(&key.as_str()) {
+			continue;
+		}
+		let value = String::from_utf8_lossy(value.as_bytes());
+		let separator = if key == hyper::header::COOKIE { "; " } else { ", " };
+		headers
+			.entry(key.to_string())
+			.and_modify(|existing| {
+				existing.push_str(separator);
+				existing.push_str(&value);
+			})
+			.or_insert_with(|| value.into_owned());
+	}
+
+	let mut captured = HttpRequest::new();
+	captured.method = parts.method.as_str().parse().map_err(anyhow::Error::msg)?;
+	captured.url = parts.uri.to_string();
+	captured.headers = headers;
+	captured.body = String::from_utf8_lossy(&body).into_owned();
+	captured.raw_body = Some(body);
+	if !captured.body.is_empty() && !captured.has_body() {
+		captured.toggle_body();
+	}
+	Ok(captured)
+}
+
+fn forwarded_response(response: &HttpResponse) -> Response<Full<Bytes>> {
+	let mut forwarded = Response::new(Full::new(Bytes::from(response.bytes.clone())));
+	*forwarded.status_mut() = StatusCode::from_u16(response.status_code).unwrap_or(StatusCode::BAD_GATEWAY);
+	for (key, value) in &response.headers {
+		if HOP_BY_HOP_HEADERS.iter().any(|skipped| key.eq_ignore_ascii_case(skipped)) {
+			continue;
+		}
+		if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(value)) {
+			forwarded.headers_mut().append(name, value);
+		}
+	}
+	forwarded
+}
+
+/// Relays the bytes of a `CONNECT` both ways once the client upgrades; HTTPS stays encrypted, so isn't recorded.
+fn tunnel(request: Request<Incoming>) -> Response<Full<Bytes>> {
+	let Some(authority) = request.uri().authority().map(ToString::to_string) else {
+		return error_response(StatusCode::BAD_REQUEST, "CONNECT needs a host:port");
+	};
+
+	tokio::spawn(async move {
+		let result = async {
+			let mut server = TcpStream::connect(&authority).await?;
+			let mut client = TokioIo::new(hyper::upgrade::on(request).await?);
+			tokio::io::copy_bidirectional(&mut client, &mut server).await?;
+			anyhow::Ok(())
+		};
+		if let Err(error) = result.await {
+			tracing::debug!("Tunnel to {authority} closed: {error}");
+		}
+	});
+	Response::new(Full::new(Bytes::new()))
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
+	let mut response = Response::new(Full::new(Bytes::from(message.to_string())));
+	*response.status_mut() = status;
+	response.headers_mut().insert("content-type", HeaderValue::from_static("text/plain"));
+	response
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+	use super::*;
+	use crate::app::HttpMethod;
+	use crate::mock::{self, Example, MockServer};
+
+	/// Sends `message` through the proxy as a client configured with it would, returning the raw reply.
+	async fn send_raw(proxy: SocketAddr, message: impl AsRef<[u8]>) -> String {
+		let mut stream = TcpStream::connect(proxy).await.unwrap();
+		stream.write_all(message.as_ref()).await.unwrap();
+		let mut reply = String::new();
+		stream.read_to_string(&mut reply).await.unwrap();
+		reply
+	}
+
+	/// Reads one request of `body_size` body bytes, replies `200 OK` and returns the request as received.
+	async fn receive_raw(listener: TcpListener, body_size: usize) -> Vec<u8> {
+		let (mut stream, _) = listener.accept().await.unwrap();
+		let mut received = Vec::new();
+		let mut buffer = [0; 4096];
+		while received
+			.windows(4)
+			.position(|window| window == b"\r\n\r\n")
+			.is_none_or(|end| received.len() < end + 4 + body_size)
+		{
+			let read = stream.read(&mut buffer).await.unwrap();
+			assert!(read > 0, "connection closed early");
+			received.extend_from_slice(&buffer[..read]);
+		}
+		stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await.unwrap();
+		received
+	}
+
+	#[tokio::test]
+	async fn test_records_forwarded_exchanges() {
+		let mut upstream_request = HttpRequest::new();
+		upstream_request.method = HttpMethod::Post;
+		upstream_request.url = String::from("/users");
+		upstream_request.example = Some(Example {
+			status: 201,
+			body: String::from("{\"id\":1}"),
+			delay_ms: 0,
+			headers: BTreeMap::from([(String::from("Content-Type"), String::from("application/json"))]),
+		});
+		let upstream =
+			MockServer::start(SocketAddr::from(([127, 0, 0, 1], 0)), mock::routes(&[upstream_request], &HashMap::new()))
+				.unwrap();
+		let mut proxy = ProxyServer::start(SocketAddr::from(([127, 0, 0, 1], 0)), HttpClient::new()).unwrap();
+
+		let url = format!("http://{}/users?page=2", upstream.address);
+		let reply = send_raw(
+			proxy.address,
+			&format!(
+				"POST {url} HTTP/1.1\r\nHost: {}\r\nProxy-Connection: close\r\nConnection: close\r\n\
+				 Content-Type: application/json\r\nContent-Length: 12\r\n\r\n{{\"name\":\"a\"}}",
+				upstream.address
+			),
+		)
+		.await;
+		assert!(reply.starts_with("HTTP/1.1 201 Created\r\n"));
+		assert!(reply.ends_with("{\"id\":1}"));
+
+		let capture = proxy.next_capture().await.unwrap();
+		assert_eq!(capture.request.method, HttpMethod::Post);
+		assert_eq!(capture.request.url, url);
+		assert_eq!(capture.request.body, "{\"name\":\"a\"}");
+		assert_eq!(capture.request.headers["content-type"], "application/json");
+		assert!(!capture.request.headers.contains_key("proxy-connection"));
+		assert!(!capture.request.headers.contains_key("host"));
+		assert_eq!(capture.response.request_id, capture.request.id);
+		assert_eq!(capture.response.status_code, 201);
+		assert_eq!(capture.response.body, "{\"id\":1}");
+
+		let reply = send_raw(proxy.address, "GET /users HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await;
+		assert!(reply.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+		assert!(proxy.take_captures().is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_forwards_headers_and_body_as_sent() {
+		let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await.unwrap();
+		let upstream_address = listener.local_addr().unwrap();
+		let body = [0xff, 0x00, 0xfe, 0x80, b'\n'];
+		let upstream = tokio::spawn(receive_raw(listener, body.len()));
+		let mut proxy = ProxyServer::start(SocketAddr::from(([127, 0, 0, 1], 0)), HttpClient::new()).unwrap();
+
+		let mut message = format!(
+			"PUT http://{upstream_address}/upload HTTP/1.1\r\nHost: {upstream_address}\r\nConnection: close\r\n\
+			 User-Agent: curl/8.5.0\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\n\r\n",
+			body.len()
+		)
+		.into_bytes();
+		message.extend_from_slice(&body);
+		let reply = send_raw(proxy.address, message).await;
+		assert!(reply.starts_with("HTTP/1.1 200 OK\r\n"));
+
+		let received = upstream.await.unwrap();
+		assert!(received.ends_with(&body));
+		let head = String::from_utf8_lossy(&received).to_lowercase();
+		assert!(head.contains("user-agent: curl/8.5.0\r\n"));
+		assert!(head.contains("content-type: application/octet-stream\r\n"));
+		assert!(!head.contains("accept-encoding:"));
+		assert!(!head.contains("accept:"));
+
+		let capture = proxy.next_capture().await.unwrap();
+		assert_eq!(capture.request.body, String::from_utf8_lossy(&body));
+		assert!(capture.request.raw_body.is_none());
+	}
+}
@@ -1,42 +1,116 @@
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use url::form_urlencoded;
 use uuid::Uuid;
 
-use crate::{app::HttpMethod, utils::format_key_values::format_key_values};
+use crate::{
+	app::{HttpMethod, HttpVersion},
+	auth::Auth,
+	graphql::Graphql,
+	grpc,
+	mock::Example,
+	utils::{
+		format_key_values::{format_key_values, format_ordered_key_values},
+		variables::substitute_variables,
+	},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpRequest {
 	pub id: String,
+	#[serde(default)]
+	pub name: String,
 	pub method: HttpMethod,
 	pub url: String,
 	pub headers: HashMap<String, String>,
-	pub queries: HashMap<String, String>,
+	/// In order; a name may repeat, e.g. `tag=a&tag=b`
+	pub queries: Vec<(String, String)>,
 	pub body: String,
+	/// Overrides whether the body is sent; `None` uses the method's default
+	#[serde(default)]
+	pub send_body: Option<bool>,
+	#[serde(default)]
+	pub http_version: HttpVersion,
+	/// Set for GraphQL requests, whose body is then the query
+	#[serde(default)]
+	pub graphql: Option<Graphql>,
+	/// Rhai script run before sending, able to change the request and set variables
+	#[serde(default)]
+	pub pre_request_script: String,
+	/// Rhai script run on the response, for tests and extracting variables
+	#[serde(default)]
+	pub post_response_script: String,
+	/// Signs the request right before it's sent
+	#[serde(default)]
+	pub auth: Option<Auth>,
+	/// Served by the mock server for requests matching this one's method and path
+	#[serde(default)]
+	pub example: Option<Example>,
 	pub created_at: chrono::DateTime<chrono::Utc>,
+	/// The `.http` file it was loaded from, where exports write it back
+	#[serde(skip)]
+	pub source: Option<PathBuf>,
+	/// The request file it was loaded from or last saved to
+	#[serde(skip)]
+	pub file: Option<PathBuf>,
+	/// Sent instead of `body`, byte for byte, when it may not be text, e.g. by the capture proxy
+	#[serde(skip)]
+	pub raw_body: Option<Bytes>,
 }
 
 impl HttpRequest {
 	pub fn new() -> Self {
 		Self {
 			id: Uuid::new_v4().to_string(),
+			name: String::new(),
 			method: HttpMethod::Get,
 			url: String::new(),
 			headers: HashMap::new(),
-			queries: HashMap::new(),
+			queries: Vec::new(),
 			body: String::new(),
+			send_body: None,
+			http_version: HttpVersion::Negotiate,
+			graphql: None,
+			pre_request_script: String::new(),
+			post_response_script: String::new(),
+			auth: None,
+			example: None,
 			created_at: chrono::Utc::now(),
+			source: None,
+			file: None,
+			raw_body: None,
 		}
 	}
 
 	pub fn set_url(&mut self, url: &str) {
-		if url.starts_with("http") {
+		if url.starts_with("http")
+			|| url.starts_with("ws://")
+			|| url.starts_with("wss://")
+			|| grpc::is_grpc_url(url)
+			|| url.starts_with("{{")
+		{
 			self.url = String::from(url);
 		} else {
 			self.url = format!("https://{url}");
 		}
 	}
 
-	pub const fn set_method(&mut self, method: HttpMethod) {
+	/// Splits the query string off `url` into decoded `queries` and sets the remaining base url.
+	pub fn set_url_with_queries(&mut self, url: &str) {
+		let Some((base_url, query_str)) = url.split_once('?') else {
+			self.set_url(url);
+			return;
+		};
+
+		for (key, value) in form_urlencoded::parse(query_str.as_bytes()) {
+			self.add_query(key.into_owned(), value.into_owned());
+		}
+		self.set_url(base_url);
+	}
+
+	pub fn set_method(&mut self, method: HttpMethod) {
 		self.method = method;
 	}
 
@@ -45,7 +119,7 @@ impl HttpRequest {
 	}
 
 	pub fn add_query(&mut self, key: String, value: String) {
-		self.queries.insert(key, value);
+		self.queries.push((key, value));
 	}
 
 	pub fn set_body(&mut self, body: &str) -> anyhow::Result<()> {
@@ -54,16 +128,77 @@ impl HttpRequest {
 		Ok(())
 	}
 
-	pub fn is_valid(&self) -> bool {
-		!self.url.is_empty() && self.url.starts_with("http")
-	}
-
 	pub fn content_type(&self) -> Option<&String> {
 		self.headers.get("Content-Type").or_else(|| self.headers.get("content-type"))
 	}
 
-	pub const fn has_body(&self) -> bool {
-		matches!(self.method, HttpMethod::Post | HttpMethod::Put | HttpMethod::Patch)
+	/// `ws://` and `wss://` URLs open a WebSocket; the body is then the message to send.
+	pub fn is_websocket(&self) -> bool {
+		let url = self.url.to_lowercase();
+		url.starts_with("ws://") || url.starts_with("wss://")
+	}
+
+	/// `grpc://` / `grpcs://` URLs call a gRPC method with the body as its JSON request message.
+	pub fn is_grpc(&self) -> bool {
+		grpc::is_grpc_url(&self.url)
+	}
+
+	pub const fn is_graphql(&self) -> bool {
+		self.graphql.is_some()
+	}
+
+	/// Switches between a plain body and a GraphQL query, keeping the body text.
+	pub fn toggle_graphql(&mut self) {
+		self.graphql = if self.graphql.is_some() { None } else { Some(Graphql::default()) };
+	}
+
+	/// Returns the request as sent over HTTP: GraphQL requests become a JSON `POST` of the query envelope.
+	pub fn with_graphql_envelope(&self) -> anyhow::Result<Self> {
+		let Some(graphql) = &self.graphql else {
+			return Ok(self.clone());
+		};
+
+		let mut request = self.clone();
+		request.body = graphql.envelope(&self.body)?;
+		request.method = HttpMethod::Post;
+		request.send_body = None;
+		if request.content_type().is_none() {
+			request.add_header(String::from("Content-Type"), String::from("application/json"));
+		}
+		Ok(request)
+	}
+
+	/// Whether the body is sent: `send_body` when set, otherwise only for methods that usually carry one.
+	pub fn has_body(&self) -> bool {
+		self.send_body.unwrap_or_else(|| self.method.allows_body())
+	}
+
+	/// Flips [`Self::has_body`], dropping the override when it matches the method's default again.
+	pub fn toggle_body(&mut self) {
+		let send_body = !self.has_body();
+		self.send_body = (send_body != self.method.allows_body()).then_some(send_body);
+	}
+
+	/// Returns a copy with `{{name}}` references in url, headers, queries, body and auth replaced.
+	pub fn with_variables(&self, variables: &HashMap<String, String>) -> Self {
+		let mut request = self.clone();
+		request.url = substitute_variables(&self.url, variables);
+		request.headers = self
+			.headers
+			.iter()
+			.map(|(key, value)| (substitute_variables(key, variables), substitute_variables(value, variables)))
+			.collect();
+		request.queries = self
+			.queries
+			.iter()
+			.map(|(key, value)| (substitute_variables(key, variables), substitute_variables(value, variables)))
+			.collect();
+		request.body = substitute_variables(&self.body, variables);
+		if let Some(graphql) = &mut request.graphql {
+			graphql.variables = substitute_variables(&graphql.variables, variables);
+		}
+		request.auth = self.auth.as_ref().map(|auth| auth.map_values(|value| substitute_variables(value, variables)));
+		request
 	}
 
 	pub fn formatted_headers(&self) -> String {
@@ -71,7 +206,7 @@ impl HttpRequest {
 	}
 
 	pub fn formatted_queries(&self) -> String {
-		format_key_values(&self.queries)
+		format_ordered_key_values(&self.queries)
 	}
 }
 
@@ -80,3 +215,74 @@ impl Default for HttpRequest {
 		Self::new()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_toggle_body() {
+		let mut request = HttpRequest::new();
+		request.set_method(HttpMethod::Delete);
+		assert!(!request.has_body());
+
+		request.toggle_body();
+		assert!(request.has_body());
+		assert_eq!(request.send_body, Some(true));
+
+		request.toggle_body();
+		assert_eq!(request.send_body, None);
+
+		request.set_method("QUERY".parse().unwrap());
+		assert!(request.has_body());
+	}
+
+	#[test]
+	fn test_queries_are_decoded_and_keep_repeats() {
+		let mut request = HttpRequest::new();
+		request.set_url_with_queries("https://example.com/search?q=a%20b&tag=1&tag=2&sum=1+1&flag");
+
+		assert_eq!(request.url, "https://example.com/search");
+		assert_eq!(
+			request.queries,
+			vec![
+				(String::from("q"), String::from("a b")),
+				(String::from("tag"), String::from("1")),
+				(String::from("tag"), String::from("2")),
+				(String::from("sum"), String::from("1 1")),
+				(String::from("flag"), String::new()),
+			]
+		);
+		assert_eq!(crate::http_client::request_url(&request).unwrap().query(), Some("q=a%20b&tag=1&tag=2&sum=1%201&flag"));
+	}
+
+	#[test]
+	fn test_websocket_urls() {
+		let mut request = HttpRequest::new();
+		request.set_url("wss://example.com/socket");
+		assert_eq!(request.url, "wss://example.com/socket");
+		assert!(request.is_websocket());
+
+		request.set_url("example.com/socket");
+		assert!(!request.is_websocket());
+	}
+
+	#[test]
+	fn test_graphql_envelope() {
+		let mut request = HttpRequest::new();
+		request.body = String::from("{ user(id: $id) { name } }");
+		assert_eq!(request.with_graphql_envelope().unwrap().body, request.body);
+
+		request.toggle_graphql();
+		request.graphql.as_mut().unwrap().variables = String::from("{\"id\": \"{{user}}\"}");
+		let variables = HashMap::from([(String::from("user"), String::from("42"))]);
+		let sent = request.with_variables(&variables).with_graphql_envelope().unwrap();
+
+		assert_eq!(sent.method, HttpMethod::Post);
+		assert_eq!(sent.content_type().map(String::as_str), Some("application/json"));
+		assert_eq!(sent.body, r#"{"query":"{ user(id: $id) { name } }","variables":{"id":"42"}}"#);
+
+		request.toggle_graphql();
+		assert!(!request.is_graphql());
+	}
+}
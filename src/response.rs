@@ -1,21 +1,125 @@
-use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::time::Duration;
 
-use crate::utils::format_key_values::format_key_values;
+use crate::connection::ConnectionInfo;
+use crate::graphql::{GraphqlError, response_errors};
+use crate::openapi::Violation;
+use crate::script::ScriptReport;
+use crate::sse::{format_events, parse_events};
+use crate::timing::Timings;
+use crate::utils::{
+	compression::{StreamDecoder, decompress},
+	format_key_values::format_key_values,
+	hex_dump::hex_dump,
+};
+
+/// Larger bodies are kept in full in `bytes` but only this much is shown.
+pub const DISPLAY_LIMIT: usize = 1024 * 1024;
+/// Hex dumps take ~4.5 characters per byte, so binary bodies get a smaller display budget.
+const HEX_DUMP_LIMIT: usize = 64 * 1024;
+/// How much of an untyped body is inspected to guess whether it's binary.
+const SNIFF_LIMIT: usize = 8 * 1024;
+/// Decoding stops past this size, so a small compressed body can't blow up in memory.
+const DECODED_LIMIT: usize = 256 * 1024 * 1024;
+
+/// The request exactly as it went on the wire, including the headers the client adds.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SentRequest {
+	/// e.g. `POST /users?page=2 HTTP/1.1`
+	pub request_line: String,
+	/// In the order they were sent; HTTP/2 pseudo-headers such as `:authority` come first
+	pub headers: Vec<(String, String)>,
+	pub body: String,
+}
+
+impl SentRequest {
+	/// The request as an HTTP/1.1 style message.
+	pub fn formatted(&self) -> String {
+		let mut message = format!("{}\n", self.request_line);
+		for (key, value) in &self.headers {
+			let _ = writeln!(message, "{key}: {value}");
+		}
+		if !self.body.is_empty() {
+			let _ = write!(message, "\n{}", self.body);
+		}
+		message
+	}
+}
+
+/// A 3xx response that was followed on the way to the final response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedirectHop {
+	pub method: String,
+	pub url: String,
+	pub status_code: u16,
+	pub status_text: String,
+	pub location: String,
+	pub headers: HashMap<String, String>,
+	pub timings: Timings,
+	pub response_time: u64, // milliseconds
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct HttpResponse {
 	pub id: String,
 	pub request_id: String,
 	pub status_code: u16,
 	pub status_text: String,
 	pub headers: HashMap<String, String>,
+	/// Text shown in the response pane: the body as UTF-8 or a hex dump for binary content, cut at the display limit
 	pub body: String,
-	pub response_time: u64, // milliseconds
-	pub size: usize,        // bytes
+	/// The body exactly as received
+	#[serde(default)]
+	pub bytes: Vec<u8>,
+	#[serde(default)]
+	pub is_binary: bool,
+	pub response_time: u64, // milliseconds, including any redirects
+	/// Per-phase breakdown of the final request
+	#[serde(default)]
+	pub timings: Timings,
+	pub size: usize, // body bytes, after decoding
+	/// Body bytes as transferred, before any content decoding
+	#[serde(default)]
+	pub transfer_size: usize,
+	/// Status line and headers as received
+	#[serde(default)]
+	pub header_size: usize,
+	/// `Content-Encoding` of the payload, e.g. `gzip`
+	#[serde(default)]
+	pub content_encoding: Option<String>,
+	/// Whether `bytes` were decompressed from `content_encoding`
+	#[serde(default)]
+	pub decoded: bool,
+	/// `bytes` hold the gRPC response messages as JSON rather than the length-prefixed protobuf payload
+	#[serde(default)]
+	pub grpc_messages: bool,
 	pub created_at: chrono::DateTime<chrono::Utc>,
+	#[serde(default)]
+	pub validation: Option<Vec<Violation>>, // None when no OpenAPI spec is linked
+	/// What was actually sent; `None` for responses recorded before it was captured
+	#[serde(default)]
+	pub sent_request: Option<SentRequest>,
+	/// Protocol, peer address and TLS session of the final request
+	#[serde(default)]
+	pub connection: Option<ConnectionInfo>,
+	/// Redirects followed before this response, in order
+	#[serde(default)]
+	pub redirects: Vec<RedirectHop>,
+	/// The body was cut short by stopping the stream
+	#[serde(default)]
+	pub stopped: bool,
+	/// The body broke off with this error after streaming in partly
+	#[serde(default)]
+	pub error: Option<String>,
+	/// How many of `bytes` a text body shows so far, so a streaming one is extended rather than rebuilt
+	#[serde(skip)]
+	shown: usize,
+	/// Tests and log lines from the request's scripts; `None` when it has none
+	#[serde(default)]
+	pub script_report: Option<ScriptReport>,
 }
 
 impl HttpResponse {
@@ -24,35 +128,140 @@ impl HttpResponse {
 		status_code: u16,
 		status_text: String,
 		headers: HashMap<String, String>,
-		body: String,
+		bytes: Vec<u8>,
 		response_time: Duration,
 	) -> Self {
-		let size = body.len();
+		let size = bytes.len();
+		let content_encoding = headers
+			.iter()
+			.find(|(key, _)| key.eq_ignore_ascii_case("content-encoding"))
+			.map(|(_, value)| value.trim().to_lowercase())
+			.filter(|encoding| !encoding.is_empty() && encoding != "identity");
 
-		Self {
+		let mut response = Self {
 			id: uuid::Uuid::new_v4().to_string(),
 			request_id,
 			status_code,
 			status_text,
 			headers,
-			body,
+			body: String::new(),
+			bytes,
+			is_binary: false,
 			#[allow(clippy::cast_possible_truncation)]
 			response_time: response_time.as_millis() as u64,
+			timings: Timings::default(),
 			size,
+			transfer_size: size,
+			header_size: 0,
+			content_encoding,
+			decoded: false,
+			grpc_messages: false,
 			created_at: chrono::Utc::now(),
+			validation: None,
+			sent_request: None,
+			connection: None,
+			redirects: Vec::new(),
+			stopped: false,
+			error: None,
+			shown: 0,
+			script_report: None,
+		};
+		response.update_body();
+		response
+	}
+
+	/// Adds a chunk of a body that is still streaming in, `transferred` bytes of it on the wire.
+	pub fn append_chunk(&mut self, chunk: &[u8], transferred: usize) {
+		let previous_size = self.bytes.len();
+		self.bytes.extend_from_slice(chunk);
+		self.size = self.bytes.len();
+		self.transfer_size += transferred;
+
+		// Until the start of the body is in, it may still turn out binary; hex dumps are short enough to redo
+		if self.is_binary || previous_size < SNIFF_LIMIT {
+			self.update_body();
+		} else {
+			self.append_text(previous_size);
 		}
 	}
 
-	pub const fn is_success(&self) -> bool {
-		self.status_code >= 200 && self.status_code < 300
+	/// Decompresses the chunks of a streaming body as they arrive, so they can be shown before it completes; they're
+	/// then passed through it before [`Self::append_chunk`]. `None` when the encoding can only be decoded in full.
+	pub fn stream_decoder(&mut self) -> Option<StreamDecoder> {
+		let decoder = StreamDecoder::new(self.content_encoding.as_deref().filter(|_| !self.decoded)?, DECODED_LIMIT)?;
+		self.decoded = true;
+		self.update_body();
+		Some(decoder)
+	}
+
+	/// Marks `bytes` as gRPC messages transcoded to JSON, so they're shown as text.
+	pub fn with_grpc_messages(mut self) -> Self {
+		self.grpc_messages = true;
+		self.update_body();
+		self
+	}
+
+	/// Decompresses `bytes` according to `content_encoding`; on error the raw payload is kept.
+	pub fn decode_body(&mut self) -> std::io::Result<()> {
+		let Some(content_encoding) = self.content_encoding.as_deref().filter(|_| !self.decoded) else {
+			return Ok(());
+		};
+
+		self.bytes = decompress(content_encoding, &self.bytes, DECODED_LIMIT)?;
+		self.size = self.bytes.len();
+		self.decoded = true;
+		self.update_body();
+		Ok(())
+	}
+
+	fn update_body(&mut self) {
+		let content_type = if self.grpc_messages {
+			Some(String::from("application/json"))
+		} else {
+			self.header("content-type").map(mime_type)
+		};
+		let encoding = self.content_encoding.as_deref().filter(|_| !self.decoded);
+
+		self.is_binary = encoding.is_some() || is_binary_content(content_type.as_deref(), &self.bytes);
+
+		let description = match (content_type, encoding) {
+			(Some(content_type), Some(encoding)) => format!("{content_type} · {encoding} encoded"),
+			(None, Some(encoding)) => format!("{encoding} encoded"),
+			(Some(content_type), None) => content_type,
+			(None, None) => String::from("unknown type"),
+		};
+		if self.is_binary {
+			self.body = binary_body(&description, &self.bytes);
+		} else {
+			self.body.clear();
+			self.shown = 0;
+			self.append_text(0);
+		}
 	}
 
-	pub const fn is_client_error(&self) -> bool {
-		self.status_code >= 400 && self.status_code < 500
+	/// Shows the text that arrived after the first `previous_size` bytes, up to the display limit. A character split
+	/// between chunks waits for the rest of it.
+	fn append_text(&mut self, previous_size: usize) {
+		if previous_size > DISPLAY_LIMIT {
+			let notice = truncation_notice(self.shown, previous_size);
+			self.body.truncate(self.body.len() - notice.len());
+		}
+
+		let tail = &self.bytes[self.shown..self.bytes.len().min(DISPLAY_LIMIT)];
+		let complete = match std::str::from_utf8(tail) {
+			Err(error) if error.error_len().is_none() => error.valid_up_to(),
+			_ => tail.len(),
+		};
+		self.body.push_str(&String::from_utf8_lossy(&tail[..complete]));
+		self.shown += complete;
+
+		if self.bytes.len() > DISPLAY_LIMIT {
+			self.body.push_str(&truncation_notice(self.shown, self.bytes.len()));
+		}
 	}
 
-	pub const fn is_server_error(&self) -> bool {
-		self.status_code >= 500
+	fn header(&self, name: &str) -> Option<&str> {
+		self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
 	}
 
 	pub fn content_type(&self) -> Option<&String> {
@@ -63,18 +272,59 @@ impl HttpResponse {
 		self.content_type().is_some_and(|ct| ct.contains("application/json"))
 	}
 
-	pub fn is_xml(&self) -> bool {
-		self.content_type().is_some_and(|ct| ct.contains("application/xml") || ct.contains("text/xml"))
+	/// `text/event-stream`, i.e. Server-Sent Events.
+	pub fn is_event_stream(&self) -> bool {
+		self.header("content-type").is_some_and(|content_type| mime_type(content_type) == "text/event-stream")
 	}
 
-	pub fn is_html(&self) -> bool {
-		self.content_type().is_some_and(|ct| ct.contains("text/html"))
+	/// The body as shown: pretty printed JSON, Server-Sent Events split into their fields, or as is.
+	pub fn formatted_body(&self) -> String {
+		if self.is_event_stream() && !self.is_binary {
+			format_events(&parse_events(&self.body))
+		} else if self.is_json() {
+			self.pretty_json().unwrap_or_else(|_| self.body.clone())
+		} else {
+			self.body.clone()
+		}
+	}
+
+	/// The `errors` of a GraphQL result, also served as `application/graphql-response+json`; `None` for other bodies.
+	pub fn graphql_errors(&self) -> Option<Vec<GraphqlError>> {
+		self.content_type().filter(|content_type| content_type.contains("json"))?;
+		response_errors(&self.body)
 	}
 
 	pub fn formatted_headers(&self) -> String {
 		format_key_values(&self.headers)
 	}
 
+	/// Each redirect with its headers, ending with the final status.
+	pub fn formatted_redirects(&self) -> String {
+		if self.redirects.is_empty() {
+			return String::from("No redirects");
+		}
+
+		let mut chain = String::new();
+		for (index, hop) in self.redirects.iter().enumerate() {
+			let _ = writeln!(
+				chain,
+				"{}. {} {} → {} {} · {}ms",
+				index + 1,
+				hop.method,
+				hop.url,
+				hop.status_code,
+				hop.status_text,
+				hop.response_time
+			);
+			for line in format_key_values(&hop.headers).lines() {
+				let _ = writeln!(chain, "   {line}");
+			}
+			chain.push('\n');
+		}
+		let _ = write!(chain, "{}. {} {} (final response)", self.redirects.len() + 1, self.status_code, self.status_text);
+		chain
+	}
+
 	pub fn pretty_json(&self) -> anyhow::Result<String, serde_json::Error> {
 		if self.is_json() {
 			let json_value: serde_json::Value = serde_json::from_str(&self.body)?;
@@ -85,22 +335,241 @@ impl HttpResponse {
 	}
 
 	pub fn formatted_size(&self) -> String {
-		if self.size < 1024 {
-			format!("{} B", self.size)
-		} else if self.size < 1024 * 1024 {
-			format!("{:.1} KB", self.size as f64 / 1024.0)
-		} else {
-			format!("{:.1} MB", self.size as f64 / (1024.0 * 1024.0))
+		format_size(self.size)
+	}
+
+	/// Body size with the encoding and transferred size when compressed, plus header size, e.g.
+	/// `1.2 KB (gzip, 340 B transferred) + 210 B headers`.
+	pub fn formatted_sizes(&self) -> String {
+		let body = match (&self.content_encoding, self.decoded) {
+			(Some(encoding), true) => {
+				format!("{} ({encoding}, {} transferred)", self.formatted_size(), format_size(self.transfer_size))
+			},
+			(Some(encoding), false) => format!("{} ({encoding}, not decoded)", format_size(self.transfer_size)),
+			(None, _) => self.formatted_size(),
+		};
+
+		if self.header_size == 0 { body } else { format!("{body} + {} headers", format_size(self.header_size)) }
+	}
+
+	/// File name for saving the body: the `Content-Disposition` filename, or one derived from the content type.
+	pub fn suggested_file_name(&self) -> String {
+		let disposition_name = self
+			.header("content-disposition")
+			.and_then(|value| value.split(';').find_map(|part| part.trim().strip_prefix("filename=")))
+			.map(|name| name.trim_matches('"'))
+			.and_then(|name| std::path::Path::new(name).file_name())
+			.map(|name| name.to_string_lossy().into_owned());
+
+		disposition_name.unwrap_or_else(|| {
+			let extension = self.content_type().map_or("bin", |content_type| file_extension(content_type));
+			format!("response-{}.{extension}", self.id.split('-').next().unwrap_or_default())
+		})
+	}
+}
+
+pub fn format_size(size: usize) -> String {
+	if size < 1024 {
+		format!("{size} B")
+	} else if size < 1024 * 1024 {
+		format!("{:.1} KB", size as f64 / 1024.0)
+	} else {
+		format!("{:.1} MB", size as f64 / (1024.0 * 1024.0))
+	}
+}
+
+fn mime_type(content_type: &str) -> String {
+	content_type.split(';').next().unwrap_or_default().trim().to_lowercase()
+}
+
+fn is_binary_content(content_type: Option<&str>, bytes: &[u8]) -> bool {
+	if let Some(content_type) = content_type {
+		let mime = mime_type(content_type);
+		let is_text = mime.starts_with("text/")
+			|| mime.ends_with("json")
+			|| mime.ends_with("xml")
+			|| mime.contains("javascript")
+			|| mime == "application/x-www-form-urlencoded";
+		if is_text {
+			return false;
 		}
+
+		let is_binary = ["image/", "audio/", "video/", "font/"].iter().any(|prefix| mime.starts_with(prefix))
+			|| [
+				"application/octet-stream",
+				"application/pdf",
+				"application/zip",
+				"application/gzip",
+				"application/x-protobuf",
+				"application/protobuf",
+				"application/grpc",
+				"application/wasm",
+			]
+			.contains(&mime.as_str());
+		if is_binary {
+			return true;
+		}
+	}
+
+	let sample = &bytes[..bytes.len().min(SNIFF_LIMIT)];
+	// A multi-byte character cut off by the sample boundary (`error_len() == None`) is still text
+	sample.contains(&0) || std::str::from_utf8(sample).is_err_and(|error| error.error_len().is_some())
+}
+
+fn binary_body(description: &str, bytes: &[u8]) -> String {
+	let shown = &bytes[..bytes.len().min(HEX_DUMP_LIMIT)];
+	let header = format!("Binary content · {description} · {}", format_size(bytes.len()));
+	if shown.len() < bytes.len() {
+		format!(
+			"{header}\n\n{}\n… {} more not shown, save the body to see all of it",
+			hex_dump(shown),
+			format_size(bytes.len() - shown.len())
+		)
+	} else {
+		format!("{header}\n\n{}", hex_dump(shown))
+	}
+}
+
+/// Follows the text of a body cut at the display limit.
+fn truncation_notice(shown: usize, size: usize) -> String {
+	format!("\n\n… truncated, showing {} of {}; save the body to see all of it", format_size(shown), format_size(size))
+}
+
+fn file_extension(content_type: &str) -> &'static str {
+	match mime_type(content_type).as_str() {
+		"application/json" => "json",
+		"application/xml" | "text/xml" => "xml",
+		"text/html" => "html",
+		"text/csv" => "csv",
+		"text/plain" => "txt",
+		"application/pdf" => "pdf",
+		"application/zip" => "zip",
+		"application/gzip" => "gz",
+		"image/png" => "png",
+		"image/jpeg" => "jpg",
+		"image/gif" => "gif",
+		"image/webp" => "webp",
+		"image/svg+xml" => "svg",
+		_ => "bin",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn response(content_type: &str, bytes: &[u8]) -> HttpResponse {
+		let headers = HashMap::from([(String::from("Content-Type"), content_type.to_string())]);
+		HttpResponse::new(String::new(), 200, String::from("OK"), headers, bytes.to_vec(), Duration::from_millis(1))
+	}
+
+	#[test]
+	fn test_binary_detection() {
+		assert!(response("image/png", b"\x89PNG\r\n").is_binary);
+		assert!(response("", b"\x00\x01\x02").is_binary);
+		assert!(response("", &[0xff, 0xfe, 0x41]).is_binary);
+		assert!(!response("application/json; charset=utf-8", b"{\"ok\": true}").is_binary);
+		assert!(!response("", "héllo".as_bytes()).is_binary);
+	}
+
+	#[test]
+	fn test_display_body() {
+		let png = response("image/png", b"\x89PNG\r\n\x1a\n");
+		assert!(png.body.starts_with("Binary content · image/png · 8 B\n\n00000000  89 50 4e 47"));
+		assert_eq!(png.bytes, b"\x89PNG\r\n\x1a\n");
+
+		let large = response("text/plain", &vec![b'a'; DISPLAY_LIMIT + 10]);
+		assert_eq!(large.size, DISPLAY_LIMIT + 10);
+		assert!(large.body.ends_with("… truncated, showing 1.0 MB of 1.0 MB; save the body to see all of it"));
+	}
+
+	#[test]
+	fn test_decode_body_and_sizes() {
+		let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+		std::io::Write::write_all(&mut encoder, br#"{"users": []}"#).unwrap();
+		let gzipped = encoder.finish().unwrap();
+
+		let headers = HashMap::from([
+			(String::from("Content-Type"), String::from("application/json")),
+			(String::from("Content-Encoding"), String::from("gzip")),
+		]);
+		let mut compressed =
+			HttpResponse::new(String::new(), 200, String::from("OK"), headers, gzipped.clone(), Duration::ZERO);
+		compressed.header_size = 120;
+
+		assert!(compressed.is_binary);
+		assert!(compressed.body.starts_with("Binary content · application/json · gzip encoded"));
+		assert_eq!(compressed.formatted_sizes(), format!("{} B (gzip, not decoded) + 120 B headers", gzipped.len()));
+
+		compressed.decode_body().unwrap();
+
+		assert!(!compressed.is_binary);
+		assert_eq!(compressed.body, r#"{"users": []}"#);
+		assert_eq!(compressed.transfer_size, gzipped.len());
+		assert_eq!(compressed.formatted_sizes(), format!("13 B (gzip, {} B transferred) + 120 B headers", gzipped.len()));
+	}
+
+	#[test]
+	fn test_append_chunk() {
+		let mut streaming = response("text/event-stream", b"");
+		streaming.append_chunk(b"data: one\n\n", 11);
+		streaming.append_chunk(b"data: tw", 8);
+		assert_eq!(streaming.size, 19);
+		assert_eq!(streaming.transfer_size, 19);
+		assert_eq!(streaming.formatted_body(), "message\none");
+
+		streaming.append_chunk(b"o\n\n", 3);
+		assert_eq!(streaming.formatted_body(), "message\none\n\nmessage\ntwo");
+	}
+
+	#[test]
+	fn test_append_chunk_past_the_sniffed_start() {
+		let mut streaming = response("text/plain", b"");
+		streaming.append_chunk(&vec![b'a'; SNIFF_LIMIT], SNIFF_LIMIT);
+		let euro = "€".as_bytes();
+		streaming.append_chunk(&euro[..1], 1);
+		streaming.append_chunk(&euro[1..], 2);
+		assert!(!streaming.is_binary);
+		assert_eq!(streaming.body, format!("{}€", "a".repeat(SNIFF_LIMIT)));
+
+		streaming.append_chunk(&vec![b'b'; DISPLAY_LIMIT], DISPLAY_LIMIT);
+		streaming.append_chunk(b"c", 1);
+		let complete = response("text/plain", &streaming.bytes);
+		assert_eq!(streaming.body, complete.body);
+		assert!(streaming.body.ends_with("… truncated, showing 1.0 MB of 1.0 MB; save the body to see all of it"));
 	}
 
-	pub const fn status_color(&self) -> Color {
-		match self.status_code {
-			200..=299 => Color::Green,
-			300..=399 => Color::Yellow,
-			400..=499 => Color::Red,
-			500..=599 => Color::Magenta,
-			_ => Color::White,
+	#[test]
+	fn test_stream_decoder() {
+		let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+		std::io::Write::write_all(&mut encoder, b"data: one\n\n").unwrap();
+		let gzipped = encoder.finish().unwrap();
+		let headers = HashMap::from([
+			(String::from("Content-Type"), String::from("text/event-stream")),
+			(String::from("Content-Encoding"), String::from("gzip")),
+		]);
+		let mut streaming = HttpResponse::new(String::new(), 200, String::from("OK"), headers, Vec::new(), Duration::ZERO);
+		assert!(streaming.is_binary);
+
+		let mut decoder = streaming.stream_decoder().unwrap();
+		for chunk in gzipped.chunks(4) {
+			streaming.append_chunk(&decoder.decode(chunk).unwrap(), chunk.len());
 		}
+		assert!(!streaming.is_binary);
+		assert_eq!(streaming.formatted_body(), "message\none");
+		assert_eq!(streaming.transfer_size, gzipped.len());
+		assert!(streaming.stream_decoder().is_none());
+	}
+
+	#[test]
+	fn test_suggested_file_name() {
+		let mut download = response("application/pdf", b"%PDF");
+		download.id = String::from("0f8e2a4c-1b2d-4e5f-8a9b-0c1d2e3f4a5b");
+		assert_eq!(download.suggested_file_name(), "response-0f8e2a4c.pdf");
+
+		download
+			.headers
+			.insert(String::from("Content-Disposition"), String::from("attachment; filename=\"../report.pdf\""));
+		assert_eq!(download.suggested_file_name(), "report.pdf");
 	}
 }
@@ -0,0 +1,362 @@
+// see https://rhai.rs/book/ for the language
+
+use rhai::{Dynamic, Engine, Map, Scope};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::app::HttpMethod;
+use crate::request::HttpRequest;
+use crate::response::HttpResponse;
+use crate::utils::dynamic_variables;
+
+/// Stops runaway scripts such as `loop {}` instead of freezing the UI.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+	PreRequest,
+	PostResponse,
+}
+
+impl Stage {
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::PreRequest => "pre-request",
+			Self::PostResponse => "post-response",
+		}
+	}
+}
+
+/// What the scripts of one request reported, kept with its response.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptReport {
+	pub tests: Vec<TestResult>,
+	/// `print` and `debug` output, prefixed with the stage that wrote it
+	pub logs: Vec<String>,
+	/// The error that stopped a script, with its stage and position
+	pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestResult {
+	pub name: String,
+	pub passed: bool,
+}
+
+impl ScriptReport {
+	pub fn extend(&mut self, other: Self) {
+		self.tests.extend(other.tests);
+		self.logs.extend(other.logs);
+		self.error = self.error.take().or(other.error);
+	}
+
+	/// `passed/total`, or `None` when no tests ran.
+	pub fn summary(&self) -> Option<String> {
+		if self.tests.is_empty() {
+			return None;
+		}
+		let passed = self.tests.iter().filter(|test| test.passed).count();
+		Some(format!("{passed}/{} tests passed", self.tests.len()))
+	}
+
+	/// Test results then log lines, as shown in the Tests tab.
+	pub fn formatted(&self) -> String {
+		let mut lines: Vec<String> =
+			self.tests.iter().map(|test| format!("{} {}", if test.passed { "✓" } else { "✗" }, test.name)).collect();
+		if let Some(error) = &self.error {
+			lines.push(format!("Error: {error}"));
+		}
+		if !self.logs.is_empty() {
+			if !lines.is_empty() {
+				lines.push(String::new());
+			}
+			lines.extend(self.logs.iter().cloned());
+		}
+		lines.join("\n")
+	}
+}
+
+/// The result of running a script: its report and the variables it set with `set_var`.
+#[derive(Debug, Default)]
+pub struct ScriptOutcome {
+	pub report: ScriptReport,
+	pub variables: HashMap<String, String>,
+}
+
+/// The request as scripts see it, as a map they can change before it's sent.
+#[derive(Debug, Serialize, Deserialize)]
+struct ScriptRequest {
+	method: String,
+	url: String,
+	headers: HashMap<String, String>,
+	queries: HashMap<String, String>,
+	body: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ScriptResponse {
+	status: u16,
+	status_text: String,
+	headers: HashMap<String, String>,
+	body: String,
+	/// The body parsed as JSON, `()` when it isn't
+	json: Option<serde_json::Value>,
+	/// Milliseconds
+	time: u64,
+}
+
+#[derive(Default)]
+struct State {
+	variables: HashMap<String, String>,
+	changed: HashMap<String, String>,
+	report: ScriptReport,
+}
+
+/// Runs `script` with `request` in scope; changes it makes to `request` are applied when it succeeds.
+pub fn run_pre_request(script: &str, request: &mut HttpRequest, variables: &HashMap<String, String>) -> ScriptOutcome {
+	let mut scope = Scope::new();
+	let script_request = ScriptRequest {
+		method: request.method.as_str().to_string(),
+		url: request.url.clone(),
+		headers: request.headers.clone(),
+		queries: request.queries.iter().cloned().collect(),
+		body: request.body.clone(),
+	};
+	let (shared, result) = run(Stage::PreRequest, script, &mut scope, &script_request, None, variables);
+
+	let result = result.and_then(|()| {
+		let changed: ScriptRequest = scope
+			.get_value::<Dynamic>("request")
+			.map(|value| rhai::serde::from_dynamic(&value))
+			.transpose()
+			.map_err(|error| format!("invalid request: {error}"))?
+			.ok_or_else(|| String::from("the request variable was removed"))?;
+		let method = changed.method.parse::<HttpMethod>().map_err(|error| format!("invalid request method: {error}"))?;
+
+		request.set_method(method);
+		request.url = changed.url;
+		request.headers = changed.headers;
+		request.queries = merged_queries(&request.queries, changed.queries);
+		request.body = changed.body;
+		Ok(())
+	});
+	outcome(Stage::PreRequest, &shared, result)
+}
+
+/// Applies a script's changes to the map of queries it saw, where a repeated name showed only its last value.
+/// Names whose value is unchanged keep all their original entries; new names are added at the end.
+fn merged_queries(original: &[(String, String)], changed: HashMap<String, String>) -> Vec<(String, String)> {
+	let seen: HashMap<&str, &str> = original.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect();
+
+	let mut queries: Vec<(String, String)> = Vec::new();
+	for (key, value) in original {
+		match changed.get(key) {
+			Some(new) if seen.get(key.as_str()) == Some(&new.as_str()) => queries.push((key.clone(), value.clone())),
+			Some(new) if !queries.iter().any(|(existing, _)| existing == key) => queries.push((key.clone(), new.clone())),
+			_ => {},
+		}
+	}
+
+	let mut added: Vec<_> = changed.into_iter().filter(|(key, _)| !seen.contains_key(key.as_str())).collect();
+	added.sort();
+	queries.extend(added);
+	queries
+}
+
+/// Runs `script` with the sent `request` and its `response` in scope; changes to them are discarded.
+pub fn run_post_response(
+	script: &str,
+	request: &HttpRequest,
+	response: &HttpResponse,
+	variables: &HashMap<String, String>,
+) -> ScriptOutcome {
+	let script_request = ScriptRequest {
+		method: request.method.as_str().to_string(),
+		url: request.url.clone(),
+		headers: request.headers.clone(),
+		queries: request.queries.iter().cloned().collect(),
+		body: request.body.clone(),
+	};
+	let script_response = ScriptResponse {
+		status: response.status_code,
+		status_text: response.status_text.clone(),
+		headers: response.headers.clone(),
+		body: response.body.clone(),
+		json: serde_json::from_slice(&response.bytes).ok(),
+		time: response.response_time,
+	};
+	let (shared, result) =
+		run(Stage::PostResponse, script, &mut Scope::new(), &script_request, Some(&script_response), variables);
+	outcome(Stage::PostResponse, &shared, result)
+}
+
+fn run(
+	stage: Stage,
+	script: &str,
+	scope: &mut Scope<'static>,
+	request: &ScriptRequest,
+	response: Option<&ScriptResponse>,
+	variables: &HashMap<String, String>,
+) -> (Rc<RefCell<State>>, Result<(), String>) {
+	let shared = Rc::new(RefCell::new(State { variables: variables.clone(), ..State::default() }));
+	let engine = engine(stage, &shared);
+
+	let result = (|| {
+		let request = rhai::serde::to_dynamic(request).map_err(|error| error.to_string())?;
+		scope.push_dynamic("request", request);
+		if let Some(response) = response {
+			scope.push_dynamic("response", rhai::serde::to_dynamic(response).map_err(|error| error.to_string())?);
+		}
+		engine.run_with_scope(scope, script).map_err(|error| error.to_string())
+	})();
+
+	(shared, result)
+}
+
+fn outcome(stage: Stage, shared: &RefCell<State>, result: Result<(), String>) -> ScriptOutcome {
+	let State { changed, mut report, .. } = std::mem::take(&mut *shared.borrow_mut());
+	report.error = result.err().map(|error| format!("{} script: {error}", stage.as_str()));
+	ScriptOutcome { report, variables: changed }
+}
+
+/// An engine whose `print`, `debug`, `test`, `set_var` and `get_var` write to `shared`.
+fn engine(stage: Stage, shared: &Rc<RefCell<State>>) -> Engine {
+	let mut engine = Engine::new();
+	engine.set_max_operations(MAX_OPERATIONS);
+
+	let log = Rc::clone(shared);
+	engine.on_print(move |text| log.borrow_mut().report.logs.push(format!("[{}] {text}", stage.as_str())));
+	let log = Rc::clone(shared);
+	engine.on_debug(move |text, _, position| {
+		log.borrow_mut().report.logs.push(format!("[{}] {position:?} {text}", stage.as_str()));
+	});
+
+	let tests = Rc::clone(shared);
+	engine.register_fn("test", move |name: &str, passed: bool| {
+		tests.borrow_mut().report.tests.push(TestResult { name: name.to_string(), passed });
+	});
+
+	let variables = Rc::clone(shared);
+	engine.register_fn("set_var", move |name: &str, value: Dynamic| {
+		let value = value.to_string();
+		let mut variables = variables.borrow_mut();
+		variables.variables.insert(name.to_string(), value.clone());
+		variables.changed.insert(name.to_string(), value);
+	});
+	let variables = Rc::clone(shared);
+	engine.register_fn("get_var", move |name: &str| {
+		variables.borrow().variables.get(name).map_or(Dynamic::UNIT, |value| Dynamic::from(value.clone()))
+	});
+
+	engine.register_fn("uuid", || uuid::Uuid::new_v4().to_string());
+	engine.register_fn("timestamp", || chrono::Utc::now().timestamp());
+	engine.register_fn("base64", |text: &str| dynamic_variables::base64(text.as_bytes()));
+	engine.register_fn("sha256", |text: &str| dynamic_variables::sha256_hex(text.as_bytes()));
+	engine.register_fn("parse_json", |json: &str| -> Result<Dynamic, Box<rhai::EvalAltResult>> {
+		let value: serde_json::Value = serde_json::from_str(json).map_err(|error| error.to_string())?;
+		rhai::serde::to_dynamic(value)
+	});
+	engine.register_fn("to_json", |value: Dynamic| -> Result<String, Box<rhai::EvalAltResult>> {
+		let value: serde_json::Value = rhai::serde::from_dynamic(&value)?;
+		Ok(value.to_string())
+	});
+	engine
+		.register_fn("keys", |map: &mut Map| map.keys().map(|key| Dynamic::from(key.to_string())).collect::<rhai::Array>());
+
+	engine
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use super::*;
+
+	fn request() -> HttpRequest {
+		let mut request = HttpRequest::new();
+		request.set_url("https://api.example.com/users");
+		request.add_header(String::from("Accept"), String::from("application/json"));
+		request
+	}
+
+	#[test]
+	fn test_pre_request_script_changes_the_request() {
+		let mut request = request();
+		request.add_query(String::from("tag"), String::from("a"));
+		request.add_query(String::from("tag"), String::from("b"));
+		request.add_query(String::from("page"), String::from("1"));
+		let variables = HashMap::from([(String::from("secret"), String::from("s3cr3t"))]);
+		let script = r#"
+			request.method = "POST";
+			request.headers["X-Signature"] = get_var("secret") + ":" + request.url;
+			request.queries.page = "2";
+			request.body = to_json(#{ name: "Joe", at: 1 });
+			request.headers["X-Digest"] = sha256(request.body) + " " + base64("Joe");
+			set_var("started", 42);
+			print("signed");
+		"#;
+
+		let outcome = run_pre_request(script, &mut request, &variables);
+
+		assert_eq!(outcome.report.error, None);
+		assert_eq!(request.method, HttpMethod::Post);
+		assert_eq!(request.headers["X-Signature"], "s3cr3t:https://api.example.com/users");
+		assert_eq!(
+			request.queries,
+			vec![
+				(String::from("tag"), String::from("a")),
+				(String::from("tag"), String::from("b")),
+				(String::from("page"), String::from("2")),
+			]
+		);
+		assert!(request.headers["X-Digest"].ends_with(" Sm9l"));
+		assert_eq!(serde_json::from_str::<serde_json::Value>(&request.body).unwrap()["name"], "Joe");
+		assert_eq!(outcome.variables, HashMap::from([(String::from("started"), String::from("42"))]));
+		assert_eq!(outcome.report.logs, vec!["[pre-request] signed"]);
+	}
+
+	#[test]
+	fn test_failed_pre_request_script_leaves_the_request() {
+		let mut request = request();
+
+		let outcome = run_pre_request("request.url = \"https://other\"; throw \"nope\";", &mut request, &HashMap::new());
+		assert!(outcome.report.error.unwrap().starts_with("pre-request script: "));
+		assert_eq!(request.url, "https://api.example.com/users");
+
+		let outcome = run_pre_request("request.headers = 1;", &mut request, &HashMap::new());
+		assert!(outcome.report.error.unwrap().contains("invalid request"));
+
+		let outcome = run_pre_request("loop {}", &mut request, &HashMap::new());
+		assert!(outcome.report.error.is_some());
+	}
+
+	#[test]
+	fn test_post_response_script_tests_and_extracts() {
+		let mut headers = HashMap::new();
+		headers.insert(String::from("content-type"), String::from("application/json"));
+		let body = br#"{"token": "abc", "items": [1, 2, 3]}"#.to_vec();
+		let response = HttpResponse::new(String::new(), 200, String::from("OK"), headers, body, Duration::from_millis(12));
+		let script = r#"
+			test("status is 200", response.status == 200);
+			test("has three items", response.json.items.len() == 3);
+			test("fast", response.time < 10);
+			set_var("token", response.json.token);
+			print(`${request.method} ${request.url}`);
+		"#;
+
+		let outcome = run_post_response(script, &request(), &response, &HashMap::new());
+
+		assert_eq!(outcome.report.tests.iter().map(|test| test.passed).collect::<Vec<_>>(), vec![true, true, false]);
+		assert_eq!(outcome.report.summary().unwrap(), "2/3 tests passed");
+		assert_eq!(outcome.variables["token"], "abc");
+		assert_eq!(outcome.report.logs, vec!["[post-response] GET https://api.example.com/users"]);
+		assert_eq!(outcome.report.error, None);
+
+		let outcome =
+			run_post_response("test(\"first\", true); response.missing.field", &request(), &response, &HashMap::new());
+		assert_eq!(outcome.report.tests.len(), 1);
+		assert!(outcome.report.error.unwrap().starts_with("post-response script: "));
+	}
+}
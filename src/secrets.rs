@@ -0,0 +1,447 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::auth::Auth;
+use crate::cli::SecretCommand;
+use crate::request::HttpRequest;
+use crate::response::HttpResponse;
+
+pub const SECRETS_FILE: &str = "secrets.enc";
+/// Unlocks the secrets without asking, e.g. in CI
+pub const PASSPHRASE_ENV: &str = "RESTO_SECRETS_PASSPHRASE";
+pub const MASK: &str = "••••••";
+
+const MAGIC: &[u8] = b"resto-secrets\x01";
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+const KEYRING_SERVICE: &str = env!("CARGO_PKG_NAME");
+/// Shorter values would mask unrelated text wherever they happen to appear
+const MIN_REDACTED_LENGTH: usize = 4;
+/// Headers whose values are credentials even when typed in directly rather than through a secret variable
+const SENSITIVE_HEADERS: [&str; 6] =
+	["authorization", "proxy-authorization", "cookie", "x-api-key", "api-key", "x-auth-token"];
+
+#[derive(Debug)]
+pub enum SecretError {
+	WrongPassphrase(PathBuf),
+	InvalidFile(PathBuf),
+	PassphraseMismatch,
+	Encryption,
+}
+
+impl std::fmt::Display for SecretError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::WrongPassphrase(path) => write!(f, "Wrong passphrase for {}", path.display()),
+			Self::InvalidFile(path) => write!(f, "Not a secrets file: {}", path.display()),
+			Self::PassphraseMismatch => write!(f, "Passphrases don't match"),
+			Self::Encryption => write!(f, "Could not encrypt the secrets"),
+		}
+	}
+}
+
+impl std::error::Error for SecretError {}
+
+/// Secret variables, kept in a file encrypted with a key derived from a passphrase.
+///
+/// The file holds the magic bytes, the Argon2id salt, the `XChaCha20-Poly1305` nonce and the sealed JSON object.
+pub struct SecretStore {
+	path: PathBuf,
+	passphrase: String,
+	secrets: BTreeMap<String, String>,
+}
+
+impl SecretStore {
+	/// Decrypts the file at `path`, or starts an empty store when there is none yet.
+	pub fn open(path: &Path, passphrase: &str) -> anyhow::Result<Self> {
+		let secrets = if path.is_file() { decrypt(path, &std::fs::read(path)?, passphrase)? } else { BTreeMap::new() };
+		Ok(Self { path: path.to_path_buf(), passphrase: passphrase.to_string(), secrets })
+	}
+
+	/// Opens the store with the passphrase from `RESTO_SECRETS_PASSPHRASE`, the OS keyring or a prompt, remembering
+	/// a prompted one in the keyring where there is one.
+	pub fn unlock(path: &Path) -> anyhow::Result<Self> {
+		if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+			return Self::open(path, &passphrase);
+		}
+
+		let entry = keyring::Entry::new(KEYRING_SERVICE, &path.display().to_string()).ok();
+		if let Some(passphrase) = entry.as_ref().and_then(|entry| entry.get_password().ok())
+			&& let Ok(store) = Self::open(path, &passphrase)
+		{
+			return Ok(store);
+		}
+
+		let passphrase = if path.is_file() {
+			rpassword::prompt_password(format!("Passphrase for {}: ", path.display()))?
+		} else {
+			let passphrase = rpassword::prompt_password(format!("New passphrase for {}: ", path.display()))?;
+			if rpassword::prompt_password("Repeat the passphrase: ")? != passphrase {
+				return Err(SecretError::PassphraseMismatch.into());
+			}
+			passphrase
+		};
+
+		let store = Self::open(path, &passphrase)?;
+		if let Some(entry) = entry
+			&& let Err(error) = entry.set_password(&passphrase)
+		{
+			tracing::debug!("Passphrase not stored in the keyring: {error}");
+		}
+		Ok(store)
+	}
+
+	/// Encrypts the secrets to the file again, with a new salt and nonce.
+	pub fn save(&self) -> anyhow::Result<()> {
+		let content = encrypt(&self.secrets, &self.passphrase)?;
+		if let Some(parent) = self.path.parent() {
+			std::fs::create_dir_all(parent)?;
+		}
+
+		let mut options = std::fs::OpenOptions::new();
+		options.write(true).create(true).truncate(true);
+		#[cfg(unix)]
+		std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+		std::io::Write::write_all(&mut options.open(&self.path)?, &content)?;
+		Ok(())
+	}
+
+	pub fn set(&mut self, name: &str, value: &str) {
+		self.secrets.insert(name.to_string(), value.to_string());
+	}
+
+	/// Returns whether there was a secret called `name`.
+	pub fn remove(&mut self, name: &str) -> bool {
+		self.secrets.remove(name).is_some()
+	}
+
+	pub const fn secrets(&self) -> &BTreeMap<String, String> {
+		&self.secrets
+	}
+}
+
+/// Runs `resto secret ...` against the store at `path`.
+pub fn run_command(command: &SecretCommand, path: &Path) -> anyhow::Result<()> {
+	let mut store = SecretStore::unlock(path)?;
+
+	match command {
+		SecretCommand::Set { name } => {
+			let value = rpassword::prompt_password(format!("Value for {name}: "))?;
+			store.set(name, &value);
+			store.save()?;
+			println!("Saved {name} to {}", path.display());
+		},
+		SecretCommand::Remove { name } => {
+			if store.remove(name) {
+				store.save()?;
+				println!("Removed {name}");
+			} else {
+				println!("No secret called {name}");
+			}
+		},
+		SecretCommand::List => {
+			for name in store.secrets().keys() {
+				println!("{name}");
+			}
+		},
+	}
+
+	Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> anyhow::Result<XChaCha20Poly1305> {
+	let mut key = [0; 32];
+	Argon2::default().hash_password_into(passphrase.as_bytes(), salt, &mut key).map_err(|_| SecretError::Encryption)?;
+	Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+fn encrypt(secrets: &BTreeMap<String, String>, passphrase: &str) -> anyhow::Result<Vec<u8>> {
+	let mut salt = [0; SALT_LENGTH];
+	let mut nonce = [0; NONCE_LENGTH];
+	rand::rng().fill(&mut salt);
+	rand::rng().fill(&mut nonce);
+
+	let sealed = derive_key(passphrase, &salt)?
+		.encrypt(XNonce::from_slice(&nonce), serde_json::to_vec(secrets)?.as_slice())
+		.map_err(|_| SecretError::Encryption)?;
+	Ok([MAGIC, &salt, &nonce, &sealed].concat())
+}
+
+fn decrypt(path: &Path, content: &[u8], passphrase: &str) -> anyhow::Result<BTreeMap<String, String>> {
+	let invalid = || SecretError::InvalidFile(path.to_path_buf());
+	let content = content.strip_prefix(MAGIC).ok_or_else(invalid)?;
+	if content.len() < SALT_LENGTH + NONCE_LENGTH {
+		return Err(invalid().into());
+	}
+	let (salt, content) = content.split_at(SALT_LENGTH);
+	let (nonce, sealed) = content.split_at(NONCE_LENGTH);
+
+	let plain = derive_key(passphrase, salt)?
+		.decrypt(XNonce::from_slice(nonce), sealed)
+		.map_err(|_| SecretError::WrongPassphrase(path.to_path_buf()))?;
+	Ok(serde_json::from_slice(&plain)?)
+}
+
+/// Replaces secret values in text shown, logged or written out.
+#[derive(Debug, Default)]
+pub struct Redactor {
+	/// `(name, value)`, longest value first so a secret containing another is replaced whole
+	secrets: Vec<(String, String)>,
+}
+
+impl Redactor {
+	pub fn new(secrets: &BTreeMap<String, String>) -> Self {
+		let mut secrets: Vec<(String, String)> = secrets
+			.iter()
+			.filter(|(_, value)| value.chars().count() >= MIN_REDACTED_LENGTH)
+			.map(|(name, value)| (name.clone(), value.clone()))
+			.collect();
+		secrets.sort_by_key(|(_, value)| std::cmp::Reverse(value.len()));
+		Self { secrets }
+	}
+
+	pub fn mask(&self, text: &str) -> String {
+		self.secrets.iter().fold(text.to_string(), |text, (_, value)| text.replace(value, MASK))
+	}
+
+	/// Puts `{{name}}` references back in place of the values, for files that are saved or exported.
+	pub fn to_references(&self, text: &str) -> String {
+		self.secrets.iter().fold(text.to_string(), |text, (name, value)| text.replace(value, &format!("{{{{{name}}}}}")))
+	}
+
+	/// [`Self::to_references`] over each field of `request` rather than the file it's written to, so the syntax around
+	/// the values is left alone.
+	pub fn referenced_request(&self, request: &HttpRequest) -> HttpRequest {
+		let mut referenced = request.clone();
+		referenced.url = self.to_references(&request.url);
+		referenced.headers = request.headers.iter().map(|(key, value)| (key.clone(), self.to_references(value))).collect();
+		referenced.queries = request.queries.iter().map(|(key, value)| (key.clone(), self.to_references(value))).collect();
+		referenced.body = self.to_references(&request.body);
+		referenced.pre_request_script = self.to_references(&request.pre_request_script);
+		referenced.post_response_script = self.to_references(&request.post_response_script);
+		if let Some(graphql) = &mut referenced.graphql {
+			graphql.variables = self.to_references(&graphql.variables);
+		}
+		if let Some(auth) = &mut referenced.auth {
+			match auth {
+				Auth::AwsSigV4(aws) => {
+					aws.access_key = self.to_references(&aws.access_key);
+					aws.secret_key = self.to_references(&aws.secret_key);
+					aws.session_token = self.to_references(&aws.session_token);
+				},
+				Auth::Hmac(hmac) => {
+					hmac.secret = self.to_references(&hmac.secret);
+					hmac.message = self.to_references(&hmac.message);
+					hmac.value = self.to_references(&hmac.value);
+				},
+			}
+		}
+		referenced
+	}
+}
+
+static REDACTOR: RwLock<Redactor> = RwLock::new(Redactor { secrets: Vec::new() });
+
+/// Makes [`mask`] and [`to_references`] hide these secrets from now on.
+pub fn register(secrets: &BTreeMap<String, String>) {
+	if let Ok(mut redactor) = REDACTOR.write() {
+		*redactor = Redactor::new(secrets);
+	}
+}
+
+pub fn mask(text: &str) -> String {
+	REDACTOR.read().map_or_else(|_| text.to_string(), |redactor| redactor.mask(text))
+}
+
+pub fn to_references(text: &str) -> String {
+	REDACTOR.read().map_or_else(|_| text.to_string(), |redactor| redactor.to_references(text))
+}
+
+/// Masks a credential unless it only refers to variables, which are resolved when sending.
+fn mask_credential(value: &str) -> String {
+	if value.is_empty() || value.contains("{{") { mask(value) } else { String::from(MASK) }
+}
+
+/// Masks the value of a credential header such as `Authorization`, keeping a leading scheme like `Bearer`.
+pub fn mask_header(name: &str, value: &str) -> String {
+	if !SENSITIVE_HEADERS.iter().any(|sensitive| name.eq_ignore_ascii_case(sensitive)) {
+		return mask(value);
+	}
+	match value.split_once(' ') {
+		Some((scheme, credentials)) if !name.eq_ignore_ascii_case("cookie") => {
+			format!("{scheme} {}", mask_credential(credentials))
+		},
+		_ => mask_credential(value),
+	}
+}
+
+/// A copy of `request` safe to display, with secrets and credentials masked.
+pub fn masked_request(request: &HttpRequest) -> HttpRequest {
+	let mut masked = request.clone();
+	masked.url = mask(&request.url);
+	masked.headers = request.headers.iter().map(|(key, value)| (key.clone(), mask_header(key, value))).collect();
+	masked.queries = request.queries.iter().map(|(key, value)| (key.clone(), mask(value))).collect();
+	masked.body = mask(&request.body);
+	masked.auth = request.auth.as_ref().map(|auth| match auth {
+		Auth::AwsSigV4(aws) => {
+			let mut aws = aws.clone();
+			aws.secret_key = mask_credential(&aws.secret_key);
+			aws.session_token = mask_credential(&aws.session_token);
+			Auth::AwsSigV4(aws)
+		},
+		Auth::Hmac(hmac) => {
+			let mut hmac = hmac.clone();
+			hmac.secret = mask_credential(&hmac.secret);
+			Auth::Hmac(hmac)
+		},
+	});
+	masked
+}
+
+/// A copy of `request` to save or export, with secret values put back as `{{name}}` references.
+pub fn referenced_request(request: &HttpRequest) -> HttpRequest {
+	REDACTOR.read().map_or_else(|_| request.clone(), |redactor| redactor.referenced_request(request))
+}
+
+/// Where `request` has a credential typed in rather than a secret variable reference, e.g. `Authorization`, so
+/// saving it can warn that it's written in clear.
+pub fn plain_credentials(request: &HttpRequest) -> Vec<String> {
+	let typed_in = |value: &str| !value.is_empty() && !value.contains("{{");
+	let mut found: Vec<String> = request
+		.headers
+		.iter()
+		.filter(|(key, value)| {
+			let credentials = value.split_once(' ').map_or(value.as_str(), |(_, credentials)| credentials);
+			SENSITIVE_HEADERS.iter().any(|sensitive| key.eq_ignore_ascii_case(sensitive)) && typed_in(credentials)
+		})
+		.map(|(key, _)| key.clone())
+		.collect();
+	found.sort();
+
+	match &request.auth {
+		Some(Auth::AwsSigV4(aws)) if typed_in(&aws.secret_key) || typed_in(&aws.session_token) => {
+			found.push(String::from("AWS secret key"));
+		},
+		Some(Auth::Hmac(hmac)) if typed_in(&hmac.secret) => found.push(String::from("HMAC secret")),
+		_ => {},
+	}
+	found
+}
+
+/// Masks what a response recorded about the request and the scripts before it goes into the history.
+pub fn redact_response(response: &mut HttpResponse) {
+	if let Some(sent) = &mut response.sent_request {
+		sent.request_line = mask(&sent.request_line);
+		for (key, value) in &mut sent.headers {
+			*value = mask_header(key, value);
+		}
+		sent.body = mask(&sent.body);
+	}
+	for hop in &mut response.redirects {
+		hop.url = mask(&hop.url);
+		hop.location = mask(&hop.location);
+	}
+	if let Some(report) = &mut response.script_report {
+		for log in &mut report.logs {
+			*log = mask(log);
+		}
+		report.error = report.error.as_deref().map(mask);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn temp_path() -> PathBuf {
+		std::env::temp_dir().join(format!("resto-secrets-{}", uuid::Uuid::new_v4())).join(SECRETS_FILE)
+	}
+
+	#[test]
+	fn test_store_round_trip() {
+		let path = temp_path();
+		let mut store = SecretStore::open(&path, "correct horse").unwrap();
+		assert!(store.secrets().is_empty());
+		store.set("token", "s3cr3t-token");
+		store.set("password", "hunter22");
+		assert!(store.remove("password"));
+		assert!(!store.remove("password"));
+		store.save().unwrap();
+
+		let content = std::fs::read(&path).unwrap();
+		assert!(content.starts_with(MAGIC));
+		assert!(!String::from_utf8_lossy(&content).contains("s3cr3t-token"));
+		#[cfg(unix)]
+		assert_eq!(
+			std::os::unix::fs::PermissionsExt::mode(&std::fs::metadata(&path).unwrap().permissions()) & 0o777,
+			0o600
+		);
+
+		let reopened = SecretStore::open(&path, "correct horse").unwrap();
+		assert_eq!(reopened.secrets(), &BTreeMap::from([(String::from("token"), String::from("s3cr3t-token"))]));
+
+		let error = SecretStore::open(&path, "wrong horse").err().unwrap();
+		assert!(matches!(error.downcast_ref::<SecretError>(), Some(SecretError::WrongPassphrase(_))));
+
+		std::fs::write(&path, b"token = \"plain\"").unwrap();
+		let error = SecretStore::open(&path, "correct horse").err().unwrap();
+		assert!(matches!(error.downcast_ref::<SecretError>(), Some(SecretError::InvalidFile(_))));
+
+		std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+	}
+
+	#[test]
+	fn test_redactor() {
+		let redactor = Redactor::new(&BTreeMap::from([
+			(String::from("token"), String::from("abc123")),
+			(String::from("long_token"), String::from("abc123xyz")),
+			(String::from("pin"), String::from("42")),
+		]));
+
+		assert_eq!(redactor.mask("Bearer abc123xyz or abc123, pin 42"), format!("Bearer {MASK} or {MASK}, pin 42"));
+		assert_eq!(redactor.to_references("Authorization: Bearer abc123xyz"), "Authorization: Bearer {{long_token}}");
+	}
+
+	#[test]
+	fn test_mask_credentials() {
+		assert_eq!(mask_header("Authorization", "Bearer eyJhbGciOi"), format!("Bearer {MASK}"));
+		assert_eq!(mask_header("authorization", "Bearer {{token}}"), "Bearer {{token}}");
+		assert_eq!(mask_header("X-Api-Key", "key-123"), MASK);
+		assert_eq!(mask_header("Cookie", "session=abc; theme=dark"), MASK);
+		assert_eq!(mask_header("Content-Type", "application/json"), "application/json");
+
+		let mut request = HttpRequest::new();
+		request.headers.insert(String::from("Authorization"), String::from("Basic dXNlcjpwYXNz"));
+		request.auth = Auth::from_form("type: hmac\nsecret: plain-secret").unwrap();
+		let masked = masked_request(&request);
+		assert_eq!(masked.headers["Authorization"], format!("Basic {MASK}"));
+		assert!(!masked.auth.unwrap().to_form().contains("plain-secret"));
+	}
+
+	#[test]
+	fn test_referenced_request_and_plain_credentials() {
+		let redactor = Redactor::new(&BTreeMap::from([(String::from("token"), String::from("abc123xyz"))]));
+
+		let mut request = HttpRequest::new();
+		request.url = String::from("https://api.example.com/users?key=abc123xyz");
+		request.headers.insert(String::from("X-Api-Key"), String::from("abc123xyz"));
+		request.headers.insert(String::from("Authorization"), String::from("Bearer typed-in"));
+		request.body = String::from("{\"token\": \"abc123xyz\"}");
+		request.auth = Auth::from_form("type: hmac\nsecret: abc123xyz").unwrap();
+
+		let referenced = redactor.referenced_request(&request);
+		assert_eq!(referenced.url, "https://api.example.com/users?key={{token}}");
+		assert_eq!(referenced.headers["X-Api-Key"], "{{token}}");
+		assert_eq!(referenced.body, "{\"token\": \"{{token}}\"}");
+		assert_eq!(plain_credentials(&referenced), vec![String::from("Authorization")]);
+		assert_eq!(
+			plain_credentials(&request),
+			vec![String::from("Authorization"), String::from("X-Api-Key"), String::from("HMAC secret")]
+		);
+	}
+}
@@ -0,0 +1,103 @@
+use std::fmt::Write;
+
+/// One event of a `text/event-stream` body.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerSentEvent {
+	pub id: Option<String>,
+	/// `None` means the default `message` type
+	pub event: Option<String>,
+	/// `data` lines joined with newlines
+	pub data: String,
+	/// Reconnection time in milliseconds
+	pub retry: Option<u64>,
+}
+
+/// The complete events in `body`; a trailing event without its blank line is still arriving and left out.
+pub fn parse_events(body: &str) -> Vec<ServerSentEvent> {
+	let mut events = Vec::new();
+	let mut event = ServerSentEvent::default();
+	let mut data_lines: Vec<&str> = Vec::new();
+	let mut has_fields = false;
+
+	let body = body.strip_prefix('\u{feff}').unwrap_or(body);
+	let mut lines = body.split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line)).peekable();
+	while let Some(line) = lines.next() {
+		// The last piece has no line ending yet
+		if lines.peek().is_none() {
+			break;
+		}
+
+		if line.is_empty() {
+			if has_fields {
+				event.data = data_lines.join("\n");
+				events.push(std::mem::take(&mut event));
+				data_lines.clear();
+				has_fields = false;
+			}
+			continue;
+		}
+		if line.starts_with(':') {
+			continue; // Comment, often used as a keep-alive
+		}
+
+		let (field, value) = line.split_once(':').unwrap_or((line, ""));
+		let value = value.strip_prefix(' ').unwrap_or(value);
+		match field {
+			"data" => data_lines.push(value),
+			"event" => event.event = Some(value.to_string()),
+			"id" if !value.contains('\0') => event.id = Some(value.to_string()),
+			"retry" => event.retry = value.parse().ok(),
+			_ => continue,
+		}
+		has_fields = true;
+	}
+
+	events
+}
+
+/// Each event as its type and id followed by its data, e.g. `message #1\n{"delta":"Hel"}`.
+pub fn format_events(events: &[ServerSentEvent]) -> String {
+	if events.is_empty() {
+		return String::from("No events yet");
+	}
+
+	let mut formatted = String::new();
+	for event in events {
+		let _ = write!(formatted, "{}", event.event.as_deref().unwrap_or("message"));
+		if let Some(id) = &event.id {
+			let _ = write!(formatted, " #{id}");
+		}
+		if let Some(retry) = event.retry {
+			let _ = write!(formatted, " (retry {retry}ms)");
+		}
+		let _ = write!(formatted, "\n{}\n\n", event.data);
+	}
+	formatted.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_events() {
+		let body = ": keep-alive\n\nid: 1\ndata: {\"delta\":\"Hel\"}\n\nevent: update\r\nid: 2\r\ndata: first\r\ndata:second\r\nretry: 3000\r\n\r\ndata: partial";
+		let events = parse_events(body);
+
+		assert_eq!(events.len(), 2);
+		assert_eq!(
+			events[0],
+			ServerSentEvent { id: Some(String::from("1")), data: String::from("{\"delta\":\"Hel\"}"), ..Default::default() }
+		);
+		assert_eq!(events[1].event.as_deref(), Some("update"));
+		assert_eq!(events[1].data, "first\nsecond");
+		assert_eq!(events[1].retry, Some(3000));
+	}
+
+	#[test]
+	fn test_format_events() {
+		let events = parse_events("id: 7\ndata: hello\n\nevent: done\ndata: [DONE]\n\n");
+		assert_eq!(format_events(&events), "message #7\nhello\n\ndone\n[DONE]");
+		assert_eq!(format_events(&[]), "No events yet");
+	}
+}
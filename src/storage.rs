@@ -0,0 +1,434 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::{
+	app::{HttpMethod, HttpVersion},
+	auth::Auth,
+	graphql::Graphql,
+	mock::Example,
+	request::HttpRequest,
+	secrets,
+};
+
+const REQUEST_FILE_EXTENSION: &str = "toml";
+
+#[derive(Debug)]
+pub enum StorageError {
+	InvalidMethod(String),
+	InvalidFile(PathBuf, String),
+}
+
+impl std::fmt::Display for StorageError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::InvalidMethod(method) => write!(f, "Invalid HTTP method: {method}"),
+			Self::InvalidFile(path, message) => write!(f, "Invalid request file {}: {message}", path.display()),
+		}
+	}
+}
+
+impl std::error::Error for StorageError {}
+
+/// On-disk form of an [`HttpRequest`]: no ids or timestamps, and sorted maps so diffs stay small.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredRequest {
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	name: String,
+	method: String,
+	url: String,
+	/// In order and possibly repeated, e.g. `[["tag", "a"], ["tag", "b"]]`
+	#[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "deserialize_queries")]
+	queries: Vec<(String, String)>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	send_body: Option<bool>,
+	#[serde(default, skip_serializing_if = "HttpVersion::is_negotiate")]
+	http_version: HttpVersion,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	body: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	graphql: Option<Graphql>,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pre_request_script: String,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	post_response_script: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	auth: Option<Auth>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	example: Option<Example>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	headers: BTreeMap<String, String>,
+}
+
+/// Queries as a list of pairs, or as a `[queries]` table, which reads better by hand when no name repeats.
+fn deserialize_queries<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<(String, String)>, D::Error> {
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Queries {
+		Pairs(Vec<(String, String)>),
+		Table(BTreeMap<String, String>),
+	}
+
+	Ok(match Queries::deserialize(deserializer)? {
+		Queries::Pairs(pairs) => pairs,
+		Queries::Table(table) => table.into_iter().collect(),
+	})
+}
+
+impl From<&HttpRequest> for StoredRequest {
+	fn from(request: &HttpRequest) -> Self {
+		Self {
+			name: request.name.clone(),
+			method: request.method.as_str().to_string(),
+			url: request.url.clone(),
+			send_body: request.send_body,
+			http_version: request.http_version,
+			body: request.body.clone(),
+			graphql: request.graphql.clone(),
+			pre_request_script: request.pre_request_script.clone(),
+			post_response_script: request.post_response_script.clone(),
+			auth: request.auth.clone(),
+			example: request.example.clone(),
+			queries: request.queries.clone(),
+			headers: request.headers.clone().into_iter().collect(),
+		}
+	}
+}
+
+impl TryFrom<StoredRequest> for HttpRequest {
+	type Error = StorageError;
+
+	fn try_from(stored: StoredRequest) -> anyhow::Result<Self, Self::Error> {
+		let mut request = Self::new();
+		request.name = stored.name;
+		request.method = stored.method.parse::<HttpMethod>().map_err(|_| StorageError::InvalidMethod(stored.method))?;
+		request.url = stored.url;
+		request.queries = stored.queries;
+		request.headers = stored.headers.into_iter().collect();
+		request.body = stored.body;
+		request.send_body = stored.send_body;
+		request.http_version = stored.http_version;
+		request.graphql = stored.graphql;
+		request.pre_request_script = stored.pre_request_script;
+		request.post_response_script = stored.post_response_script;
+		request.auth = stored.auth;
+		request.example = stored.example;
+		Ok(request)
+	}
+}
+
+/// Fields that often span lines, written as multi-line literals.
+const MULTI_LINE_FIELDS: [&[&str]; 5] =
+	[&["body"], &["pre_request_script"], &["post_response_script"], &["graphql", "variables"], &["example", "body"]];
+
+pub fn request_to_toml(request: &HttpRequest) -> anyhow::Result<String> {
+	let mut document: toml_edit::DocumentMut = toml::to_string(&StoredRequest::from(request))?.parse()?;
+
+	for path in MULTI_LINE_FIELDS {
+		let item = path
+			.iter()
+			.try_fold(document.as_item_mut(), |item, key| item.as_table_like_mut().and_then(|table| table.get_mut(key)));
+		if let Some(value) = item.and_then(toml_edit::Item::as_value_mut)
+			&& let Some(literal) = value.as_str().and_then(multi_line_literal)
+		{
+			*value = literal;
+		}
+	}
+
+	Ok(document.to_string())
+}
+
+/// A multi-line literal keeps bodies readable and diffable, leaving their quotes and backslashes unescaped. Literals
+/// can't hold other control characters or their own closing quotes, so those values stay as `toml` writes them.
+fn multi_line_literal(value: &str) -> Option<toml_edit::Value> {
+	let has_control = value.chars().any(|ch| ch.is_control() && ch != '\n' && ch != '\t');
+	if !value.contains('\n') || value.contains("'''") || has_control {
+		return None;
+	}
+
+	// The newline after the opening quotes isn't part of the value
+	format!("'''\n{value}'''").parse().ok()
+}
+
+pub fn request_from_toml(content: &str) -> anyhow::Result<HttpRequest> {
+	let stored: StoredRequest = toml::from_str(content)?;
+	Ok(HttpRequest::try_from(stored)?)
+}
+
+/// File name for a request, derived from its name or method and url.
+pub fn request_file_name(request: &HttpRequest) -> String {
+	let label = if request.name.is_empty() {
+		format!(
+			"{} {}",
+			request.method.as_str(),
+			request.url.split_once("://").map_or(request.url.as_str(), |(_, rest)| rest)
+		)
+	} else {
+		request.name.clone()
+	};
+
+	let mut slug = String::new();
+	for ch in label.chars() {
+		if ch.is_ascii_alphanumeric() {
+			slug.push(ch.to_ascii_lowercase());
+		} else if !slug.is_empty() && !slug.ends_with('-') {
+			slug.push('-');
+		}
+	}
+
+	let slug = slug.trim_end_matches('-');
+	format!("{}.{REQUEST_FILE_EXTENSION}", if slug.is_empty() { "request" } else { slug })
+}
+
+/// The request's own file while its name still fits, otherwise the first free one, numbered when requests share a
+/// name, e.g. `create-user-2.toml`.
+fn unique_file_path(directory: &Path, request: &HttpRequest) -> PathBuf {
+	let file_name = request_file_name(request);
+	let stem = file_name.trim_end_matches(&format!(".{REQUEST_FILE_EXTENSION}")).to_string();
+	std::iter::once(file_name)
+		.chain((2..usize::MAX).map(|number| format!("{stem}-{number}.{REQUEST_FILE_EXTENSION}")))
+		.map(|name| directory.join(name))
+		.find(|path| request.file.as_ref() == Some(path) || !path.exists())
+		.unwrap_or_default()
+}
+
+/// Writes the request to its file in `directory`, removing the one it had under a previous name.
+pub fn save_request(directory: &Path, request: &HttpRequest) -> anyhow::Result<PathBuf> {
+	std::fs::create_dir_all(directory)?;
+	let path = unique_file_path(directory, request);
+	// Typed-in secret values are saved as references to the secret variables
+	std::fs::write(&path, request_to_toml(&secrets::referenced_request(request))?)?;
+
+	if let Some(previous) = &request.file
+		&& *previous != path
+		&& previous.parent() == Some(directory)
+		&& previous.exists()
+	{
+		std::fs::remove_file(previous)?;
+	}
+	Ok(path)
+}
+
+/// Loads every request file in `directory`, ordered by file name.
+pub fn load_requests(directory: &Path) -> anyhow::Result<Vec<HttpRequest>> {
+	if !directory.is_dir() {
+		return Ok(Vec::new());
+	}
+
+	let mut paths: Vec<PathBuf> = std::fs::read_dir(directory)?
+		.filter_map(Result::ok)
+		.map(|entry| entry.path())
+		.filter(|path| path.extension().is_some_and(|extension| extension == REQUEST_FILE_EXTENSION))
+		.collect();
+	paths.sort();
+
+	paths
+		.into_iter()
+		.map(|path| {
+			let content = std::fs::read_to_string(&path)?;
+			let mut request =
+				request_from_toml(&content).map_err(|error| StorageError::InvalidFile(path.clone(), error.to_string()))?;
+			request.file = Some(path);
+			Ok(request)
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use super::*;
+
+	fn request() -> HttpRequest {
+		let mut request = HttpRequest::new();
+		request.name = String::from("Create user");
+		request.set_method(HttpMethod::Post);
+		request.set_url("https://api.example.com/users");
+		request.add_header(String::from("X-Trace"), String::from("1"));
+		request.add_header(String::from("Content-Type"), String::from("application/json"));
+		request.add_query(String::from("notify"), String::from("true"));
+		request.body = String::from("{\n  \"name\": \"Joe\"\n}");
+		request
+	}
+
+	#[test]
+	fn test_toml_is_stable_and_human_editable() {
+		let content = request_to_toml(&request()).unwrap();
+
+		assert_eq!(
+			content,
+			r#"name = "Create user"
+method = "POST"
+url = "https://api.example.com/users"
+queries = [["notify", "true"]]
+body = '''
+{
+  "name": "Joe"
+}'''
+
+[headers]
+Content-Type = "application/json"
+X-Trace = "1"
+"#
+		);
+		assert!(!content.contains("created_at"));
+		assert_eq!(content, request_to_toml(&request()).unwrap());
+	}
+
+	#[test]
+	fn test_toml_round_trip() {
+		let original = request();
+		let parsed = request_from_toml(&request_to_toml(&original).unwrap()).unwrap();
+
+		assert_eq!(parsed.name, original.name);
+		assert_eq!(parsed.method, original.method);
+		assert_eq!(parsed.url, original.url);
+		assert_eq!(parsed.headers, original.headers);
+		assert_eq!(parsed.queries, original.queries);
+		assert_eq!(parsed.body, original.body);
+	}
+
+	#[test]
+	fn test_repeated_and_table_queries() {
+		let mut original = request();
+		original.add_query(String::from("tag"), String::from("a b"));
+		original.add_query(String::from("tag"), String::from("c"));
+		let content = request_to_toml(&original).unwrap();
+		assert!(content.contains("queries = [[\"notify\", \"true\"], [\"tag\", \"a b\"], [\"tag\", \"c\"]]\n"));
+		assert_eq!(request_from_toml(&content).unwrap().queries, original.queries);
+
+		let table = "method = \"GET\"\nurl = \"https://example.com\"\n\n[queries]\nb = \"2\"\na = \"1\"\n";
+		assert_eq!(
+			request_from_toml(table).unwrap().queries,
+			vec![(String::from("a"), String::from("1")), (String::from("b"), String::from("2"))]
+		);
+	}
+
+	#[test]
+	fn test_only_multi_line_fields_become_literals() {
+		let mut original = request();
+		original.body = String::from("same\nvalue");
+		original.add_header(String::from("body"), String::from("same\nvalue"));
+
+		let content = request_to_toml(&original).unwrap();
+		assert!(content.contains("body = '''\nsame\nvalue'''\n"));
+		assert!(content.contains("body = \"\"\"\nsame\nvalue\"\"\"\n"));
+		assert_eq!(request_from_toml(&content).unwrap().headers, original.headers);
+	}
+
+	#[test]
+	fn test_trailing_newlines_and_control_characters_round_trip() {
+		let mut original = request();
+		original.body = String::from("line\n");
+		let parsed = request_from_toml(&request_to_toml(&original).unwrap()).unwrap();
+		assert_eq!(parsed.body, original.body);
+
+		let parsed = request_from_toml("method = \"GET\"\nurl = \"https://example.com\"\nbody = \"ends\\n\"\n").unwrap();
+		assert_eq!(parsed.body, "ends\n");
+
+		original.body = String::from("bell\u{7}\r\nnext\nline");
+		let content = request_to_toml(&original).unwrap();
+		assert!(content.contains("body = \"\"\"\nbell\\u0007\\r\nnext\nline\"\"\"\n"));
+		assert_eq!(request_from_toml(&content).unwrap().body, original.body);
+	}
+
+	#[test]
+	fn test_graphql_round_trip() {
+		let mut original = HttpRequest::new();
+		original.set_url("https://api.example.com/graphql");
+		original.body = String::from("query GetUser($id: ID!) {\n  user(id: $id) { name }\n}");
+		original.graphql =
+			Some(Graphql { variables: String::from("{\n  \"id\": 1\n}"), operation_name: Some(String::from("GetUser")) });
+
+		let content = request_to_toml(&original).unwrap();
+		assert!(content.contains("[graphql]\nvariables = '''\n{\n  \"id\": 1\n}'''\noperation_name = \"GetUser\"\n"));
+		assert_eq!(request_from_toml(&content).unwrap().graphql, original.graphql);
+
+		original.graphql = Some(Graphql::default());
+		let parsed = request_from_toml(&request_to_toml(&original).unwrap()).unwrap();
+		assert_eq!(parsed.graphql, Some(Graphql::default()));
+	}
+
+	#[test]
+	fn test_scripts_round_trip() {
+		let mut original = request();
+		original.pre_request_script = String::from("let now = timestamp();\nrequest.headers[\"X-Time\"] = `${now}`;");
+		original.post_response_script = String::from("test(\"ok\", response.status == 200);");
+
+		let content = request_to_toml(&original).unwrap();
+		assert!(content.contains("pre_request_script = '''\nlet now = timestamp();\n"));
+		assert!(content.contains("post_response_script = 'test(\"ok\", response.status == 200);'\n"));
+
+		let parsed = request_from_toml(&content).unwrap();
+		assert_eq!(parsed.pre_request_script, original.pre_request_script);
+		assert_eq!(parsed.post_response_script, original.post_response_script);
+	}
+
+	#[test]
+	fn test_auth_round_trip() {
+		let mut original = request();
+		original.auth = Auth::from_form(
+			"type: aws-sigv4\naccess_key: {{aws_key}}\nsecret_key: {{aws_secret}}\nregion: eu-west-1\nservice: execute-api",
+		)
+		.unwrap();
+
+		let content = request_to_toml(&original).unwrap();
+		assert!(content.contains("[auth]\ntype = \"aws-sigv4\"\naccess_key = \"{{aws_key}}\"\n"));
+		assert!(!content.contains("session_token"));
+		assert_eq!(request_from_toml(&content).unwrap().auth, original.auth);
+	}
+
+	#[test]
+	fn test_example_round_trip() {
+		let mut original = request();
+		original.example =
+			Example::from_text("201\nX-Mock-Delay: 300\nContent-Type: application/json\n\n{\n  \"id\": 1\n}").unwrap();
+
+		let content = request_to_toml(&original).unwrap();
+		assert!(content.contains("[example]\nstatus = 201\nbody = '''\n{\n  \"id\": 1\n}'''\ndelay_ms = 300\n"));
+		assert!(content.contains("body = '''\n{\n  \"name\": \"Joe\"\n}'''\n"));
+		assert_eq!(request_from_toml(&content).unwrap().example, original.example);
+	}
+
+	#[test]
+	fn test_minimal_file_and_invalid_method() {
+		let parsed = request_from_toml("method = \"GET\"\nurl = \"https://example.com\"\n").unwrap();
+		assert_eq!(parsed.method, HttpMethod::Get);
+		assert_eq!(parsed.headers, HashMap::new());
+
+		let parsed = request_from_toml("method = \"PROPFIND\"\nurl = \"https://dav.example.com\"\n").unwrap();
+		assert_eq!(parsed.method, HttpMethod::Custom(String::from("PROPFIND")));
+
+		assert!(request_from_toml("method = \"FET CH\"\nurl = \"https://example.com\"\n").is_err());
+	}
+
+	#[test]
+	fn test_request_file_name() {
+		assert_eq!(request_file_name(&request()), "create-user.toml");
+
+		let mut unnamed = HttpRequest::new();
+		unnamed.set_url("https://api.example.com/users/{id}");
+		assert_eq!(request_file_name(&unnamed), "get-api-example-com-users-id.toml");
+	}
+
+	#[test]
+	fn test_saved_file_names_are_unique_and_follow_renames() {
+		let directory = std::env::temp_dir().join(format!("resto-requests-{}", uuid::Uuid::new_v4()));
+		let mut first = request();
+		first.file = Some(save_request(&directory, &first).unwrap());
+		let mut second = request();
+		second.name = String::from("create-user");
+		second.file = Some(save_request(&directory, &second).unwrap());
+		assert_eq!(first.file, Some(directory.join("create-user.toml")));
+		assert_eq!(second.file, Some(directory.join("create-user-2.toml")));
+		assert_eq!(save_request(&directory, &second).unwrap(), directory.join("create-user-2.toml"));
+
+		first.name = String::from("Delete user");
+		assert_eq!(save_request(&directory, &first).unwrap(), directory.join("delete-user.toml"));
+		let names: Vec<String> = load_requests(&directory).unwrap().into_iter().map(|request| request.name).collect();
+		assert_eq!(names, ["create-user", "Delete user"]);
+
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+}
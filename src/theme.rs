@@ -0,0 +1,367 @@
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+
+use crate::app::HttpMethod;
+
+pub const DEFAULT_THEME: &str = "dark";
+pub const BUILT_IN_THEMES: &[&str] = &["dark", "light", "high-contrast"];
+
+#[derive(Debug)]
+pub enum ThemeError {
+	Unknown(String),
+	Invalid(String, String),
+}
+
+impl std::fmt::Display for ThemeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Unknown(name) => write!(f, "Unknown theme: {name} (built-in themes: {})", BUILT_IN_THEMES.join(", ")),
+			Self::Invalid(name, message) => write!(f, "Invalid theme {name}: {message}"),
+		}
+	}
+}
+
+impl std::error::Error for ThemeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MethodColors {
+	pub get: Color,
+	pub post: Color,
+	pub put: Color,
+	pub patch: Color,
+	pub delete: Color,
+	pub head: Color,
+	pub options: Color,
+	/// Custom and extension methods
+	pub other: Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatusColors {
+	pub success: Color,
+	pub redirection: Color,
+	pub client_error: Color,
+	pub server_error: Color,
+	pub other: Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModeColors {
+	pub border: Color,
+	pub cursor: Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VimColors {
+	pub normal: ModeColors,
+	pub insert: ModeColors,
+	pub visual: ModeColors,
+	pub operator: ModeColors,
+}
+
+/// Every color the UI draws with. User themes in the config file override any subset on top of a built-in `base`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+	/// Regular text and borders
+	pub text: Color,
+	/// Inactive tabs, disabled sections and empty placeholders
+	pub muted: Color,
+	/// Active tabs, key hints and the help screen border
+	pub accent: Color,
+	/// Workspace and version info in the footer
+	pub info: Color,
+	/// Vim mode indicator in the footer
+	pub mode: Color,
+	pub success: Color,
+	pub error: Color,
+	pub line_number_background: Color,
+	pub methods: MethodColors,
+	pub status: StatusColors,
+	pub vim: VimColors,
+}
+
+impl Theme {
+	pub const fn dark() -> Self {
+		Self {
+			text: Color::White,
+			muted: Color::Gray,
+			accent: Color::Yellow,
+			info: Color::Magenta,
+			mode: Color::Cyan,
+			success: Color::Green,
+			error: Color::Red,
+			line_number_background: Color::DarkGray,
+			methods: MethodColors {
+				get: Color::Green,
+				post: Color::Blue,
+				put: Color::Magenta,
+				patch: Color::LightMagenta,
+				delete: Color::Red,
+				head: Color::Cyan,
+				options: Color::Yellow,
+				other: Color::LightCyan,
+			},
+			status: StatusColors {
+				success: Color::Green,
+				redirection: Color::Yellow,
+				client_error: Color::Red,
+				server_error: Color::Magenta,
+				other: Color::White,
+			},
+			vim: VimColors {
+				normal: ModeColors { border: Color::Blue, cursor: Color::Reset },
+				insert: ModeColors { border: Color::Yellow, cursor: Color::LightBlue },
+				visual: ModeColors { border: Color::Cyan, cursor: Color::LightYellow },
+				operator: ModeColors { border: Color::Green, cursor: Color::LightGreen },
+			},
+		}
+	}
+
+	pub const fn light() -> Self {
+		Self {
+			text: Color::Black,
+			muted: Color::DarkGray,
+			accent: Color::Blue,
+			info: Color::Magenta,
+			mode: Color::Blue,
+			success: Color::Green,
+			error: Color::Red,
+			line_number_background: Color::Gray,
+			methods: MethodColors {
+				get: Color::Green,
+				post: Color::Blue,
+				put: Color::Magenta,
+				patch: Color::Magenta,
+				delete: Color::Red,
+				head: Color::Cyan,
+				options: Color::Rgb(0x9a, 0x6a, 0x00),
+				other: Color::DarkGray,
+			},
+			status: StatusColors {
+				success: Color::Green,
+				redirection: Color::Rgb(0x9a, 0x6a, 0x00),
+				client_error: Color::Red,
+				server_error: Color::Magenta,
+				other: Color::Black,
+			},
+			vim: VimColors {
+				normal: ModeColors { border: Color::Blue, cursor: Color::Reset },
+				insert: ModeColors { border: Color::Rgb(0x9a, 0x6a, 0x00), cursor: Color::Blue },
+				visual: ModeColors { border: Color::Cyan, cursor: Color::Rgb(0x9a, 0x6a, 0x00) },
+				operator: ModeColors { border: Color::Green, cursor: Color::Green },
+			},
+		}
+	}
+
+	pub const fn high_contrast() -> Self {
+		Self {
+			text: Color::White,
+			muted: Color::White,
+			accent: Color::LightYellow,
+			info: Color::LightCyan,
+			mode: Color::LightCyan,
+			success: Color::LightGreen,
+			error: Color::LightRed,
+			line_number_background: Color::Black,
+			methods: MethodColors {
+				get: Color::LightGreen,
+				post: Color::LightBlue,
+				put: Color::LightMagenta,
+				patch: Color::LightMagenta,
+				delete: Color::LightRed,
+				head: Color::LightCyan,
+				options: Color::LightYellow,
+				other: Color::White,
+			},
+			status: StatusColors {
+				success: Color::LightGreen,
+				redirection: Color::LightYellow,
+				client_error: Color::LightRed,
+				server_error: Color::LightMagenta,
+				other: Color::White,
+			},
+			vim: VimColors {
+				normal: ModeColors { border: Color::White, cursor: Color::Reset },
+				insert: ModeColors { border: Color::LightYellow, cursor: Color::LightYellow },
+				visual: ModeColors { border: Color::LightCyan, cursor: Color::LightCyan },
+				operator: ModeColors { border: Color::LightGreen, cursor: Color::LightGreen },
+			},
+		}
+	}
+
+	/// Terminal default colors everywhere, for `NO_COLOR` (<https://no-color.org>).
+	pub const fn monochrome() -> Self {
+		let mode = ModeColors { border: Color::Reset, cursor: Color::Reset };
+		Self {
+			text: Color::Reset,
+			muted: Color::Reset,
+			accent: Color::Reset,
+			info: Color::Reset,
+			mode: Color::Reset,
+			success: Color::Reset,
+			error: Color::Reset,
+			line_number_background: Color::Reset,
+			methods: MethodColors {
+				get: Color::Reset,
+				post: Color::Reset,
+				put: Color::Reset,
+				patch: Color::Reset,
+				delete: Color::Reset,
+				head: Color::Reset,
+				options: Color::Reset,
+				other: Color::Reset,
+			},
+			status: StatusColors {
+				success: Color::Reset,
+				redirection: Color::Reset,
+				client_error: Color::Reset,
+				server_error: Color::Reset,
+				other: Color::Reset,
+			},
+			vim: VimColors { normal: mode, insert: mode, visual: mode, operator: mode },
+		}
+	}
+
+	pub fn built_in(name: &str) -> Option<Self> {
+		match name {
+			"dark" => Some(Self::dark()),
+			"light" => Some(Self::light()),
+			"high-contrast" => Some(Self::high_contrast()),
+			_ => None,
+		}
+	}
+
+	/// Resolves `name` against the built-in themes and the `[themes.<name>]` tables of the config file.
+	/// A user theme starts from its `base` built-in theme (dark by default) and overrides the colors it lists.
+	pub fn resolve(name: &str, user_themes: &toml::Table) -> anyhow::Result<Self, ThemeError> {
+		let Some(user_theme) = user_themes.get(name) else {
+			return Self::built_in(name).ok_or_else(|| ThemeError::Unknown(name.to_string()));
+		};
+
+		let invalid = |message: String| ThemeError::Invalid(name.to_string(), message);
+
+		let mut overrides = user_theme.as_table().cloned().ok_or_else(|| invalid(String::from("expected a table")))?;
+		let base_name = match overrides.remove("base") {
+			Some(toml::Value::String(base)) => base,
+			Some(_) => return Err(invalid(String::from("base must be a theme name"))),
+			None => String::from(DEFAULT_THEME),
+		};
+		let base = Self::built_in(&base_name).ok_or(ThemeError::Unknown(base_name))?;
+
+		let mut merged = toml::Table::try_from(base).map_err(|error| invalid(error.to_string()))?;
+		merge_tables(&mut merged, overrides);
+		merged.try_into().map_err(|error: toml::de::Error| invalid(error.message().to_string()))
+	}
+
+	pub const fn method_color(&self, method: &HttpMethod) -> Color {
+		match method {
+			HttpMethod::Get => self.methods.get,
+			HttpMethod::Post => self.methods.post,
+			HttpMethod::Put => self.methods.put,
+			HttpMethod::Patch => self.methods.patch,
+			HttpMethod::Delete => self.methods.delete,
+			HttpMethod::Head => self.methods.head,
+			HttpMethod::Options => self.methods.options,
+			HttpMethod::Custom(_) => self.methods.other,
+		}
+	}
+
+	pub const fn status_color(&self, status: u16) -> Color {
+		match status {
+			200..=299 => self.status.success,
+			300..=399 => self.status.redirection,
+			400..=499 => self.status.client_error,
+			500..=599 => self.status.server_error,
+			_ => self.status.other,
+		}
+	}
+
+	pub fn text(&self) -> Style {
+		Style::default().fg(self.text)
+	}
+
+	pub fn muted(&self) -> Style {
+		Style::default().fg(self.muted)
+	}
+
+	/// Active tabs and other highlights; reversed when the accent is the terminal default so it stays visible.
+	pub fn accent(&self) -> Style {
+		if self.accent == Color::Reset {
+			Style::default().add_modifier(Modifier::REVERSED)
+		} else {
+			Style::default().fg(self.accent)
+		}
+	}
+}
+
+impl Default for Theme {
+	fn default() -> Self {
+		Self::dark()
+	}
+}
+
+fn merge_tables(base: &mut toml::Table, overrides: toml::Table) {
+	for (key, value) in overrides {
+		match (base.get_mut(&key), value) {
+			(Some(toml::Value::Table(base_table)), toml::Value::Table(override_table)) => {
+				merge_tables(base_table, override_table);
+			},
+			(_, value) => {
+				base.insert(key, value);
+			},
+		}
+	}
+}
+
+/// Whether `NO_COLOR` is set to a non-empty value.
+pub fn no_color() -> bool {
+	std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_built_in_themes() {
+		for name in BUILT_IN_THEMES {
+			assert!(Theme::resolve(name, &toml::Table::new()).is_ok());
+		}
+		assert!(matches!(Theme::resolve("solarized", &toml::Table::new()), Err(ThemeError::Unknown(_))));
+	}
+
+	#[test]
+	fn test_user_theme_overrides_base() {
+		let themes: toml::Table = toml::from_str(
+			r##"
+[solarized]
+base = "light"
+accent = "#b58900"
+
+[solarized.methods]
+get = "bright green"
+"##,
+		)
+		.unwrap();
+
+		let theme = Theme::resolve("solarized", &themes).unwrap();
+
+		assert_eq!(theme.accent, Color::Rgb(0xb5, 0x89, 0x00));
+		assert_eq!(theme.method_color(&HttpMethod::Get), Color::LightGreen);
+		assert_eq!(theme.method_color(&HttpMethod::Post), Theme::light().methods.post);
+		assert_eq!(theme.text, Theme::light().text);
+	}
+
+	#[test]
+	fn test_invalid_user_theme() {
+		let themes: toml::Table = toml::from_str("[broken]\nacent = \"red\"\n\n[ugly]\ntext = \"not a color\"\n").unwrap();
+
+		assert!(matches!(Theme::resolve("broken", &themes), Err(ThemeError::Invalid(..))));
+		assert!(matches!(Theme::resolve("ugly", &themes), Err(ThemeError::Invalid(..))));
+	}
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Phases of a request, each starting where the previous one ended.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timings {
+	pub dns: Duration,
+	/// Includes setting up the tunnel when there's a proxy
+	pub connect: Duration,
+	/// `None` for plain HTTP and reused connections
+	pub tls: Option<Duration>,
+	/// From sending the request until the response headers arrive
+	pub ttfb: Duration,
+	pub download: Duration,
+}
+
+/// One bar of the waterfall, in terminal columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WaterfallBar {
+	pub label: &'static str,
+	pub duration: Duration,
+	pub offset: u16,
+	pub length: u16,
+}
+
+impl Timings {
+	pub fn total(&self) -> Duration {
+		self.phases().iter().map(|(_, duration)| *duration).sum()
+	}
+
+	pub fn phases(&self) -> Vec<(&'static str, Duration)> {
+		let mut phases = vec![("DNS lookup", self.dns), ("TCP connect", self.connect)];
+		if let Some(tls) = self.tls {
+			phases.push(("TLS handshake", tls));
+		}
+		phases.push(("Waiting (TTFB)", self.ttfb));
+		phases.push(("Content download", self.download));
+		phases
+	}
+
+	/// Lays the phases out over `width` columns, each bar starting where the previous one ended.
+	/// Non-zero phases get at least one column so short ones stay visible, unless there are no columns at all.
+	pub fn waterfall(&self, width: u16) -> Vec<WaterfallBar> {
+		let total = self.total().as_secs_f64();
+		let column = |elapsed: Duration| {
+			if total == 0.0 {
+				0
+			} else {
+				#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+				let column = (elapsed.as_secs_f64() / total * f64::from(width)).round() as u16;
+				column.min(width)
+			}
+		};
+
+		let mut elapsed = Duration::ZERO;
+		self
+			.phases()
+			.into_iter()
+			.map(|(label, duration)| {
+				let offset = column(elapsed);
+				elapsed += duration;
+				let end = column(elapsed);
+
+				let length = if duration.is_zero() { 0 } else { (end - offset).max(1).min(width) };
+				WaterfallBar { label, duration, offset: offset.min(width.saturating_sub(length)), length }
+			})
+			.collect()
+	}
+}
+
+/// e.g. `0.42 ms`, `12.3 ms` or `1.25 s`.
+pub fn format_duration(duration: Duration) -> String {
+	let millis = duration.as_secs_f64() * 1000.0;
+	if millis < 1.0 {
+		format!("{millis:.2} ms")
+	} else if millis < 1000.0 {
+		format!("{millis:.1} ms")
+	} else {
+		format!("{:.2} s", duration.as_secs_f64())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn timings() -> Timings {
+		Timings {
+			dns: Duration::from_millis(10),
+			connect: Duration::from_millis(20),
+			tls: Some(Duration::from_millis(30)),
+			ttfb: Duration::from_millis(40),
+			download: Duration::ZERO,
+		}
+	}
+
+	#[test]
+	fn test_total_and_phases() {
+		assert_eq!(timings().total(), Duration::from_millis(100));
+		assert_eq!(Timings { tls: None, ..timings() }.phases().len(), 4);
+	}
+
+	#[test]
+	fn test_waterfall() {
+		let bars = timings().waterfall(50);
+
+		let layout: Vec<(u16, u16)> = bars.iter().map(|bar| (bar.offset, bar.length)).collect();
+		assert_eq!(layout, vec![(0, 5), (5, 10), (15, 15), (30, 20), (50, 0)]);
+		assert_eq!(bars[3].label, "Waiting (TTFB)");
+
+		assert!(Timings::default().waterfall(50).iter().all(|bar| bar.length == 0));
+
+		// A pane too narrow for any bar
+		assert!(timings().waterfall(0).iter().all(|bar| bar.offset == 0 && bar.length == 0));
+		assert!(timings().waterfall(1).iter().all(|bar| bar.offset + bar.length <= 1));
+	}
+
+	#[test]
+	fn test_format_duration() {
+		assert_eq!(format_duration(Duration::from_micros(420)), "0.42 ms");
+		assert_eq!(format_duration(Duration::from_micros(12_345)), "12.3 ms");
+		assert_eq!(format_duration(Duration::from_millis(1250)), "1.25 s");
+	}
+}
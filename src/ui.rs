@@ -1,16 +1,27 @@
 use ratatui::{
 	Frame,
 	layout::{Alignment, Constraint, Direction, Layout, Rect},
-	style::{Color, Modifier, Style},
+	style::{Modifier, Style},
 	symbols,
 	text::{Line, Span, ToSpan},
-	widgets::{Block, Borders, List, ListItem, Padding, Paragraph, Tabs},
+	widgets::{Block, Borders, Clear, List, ListItem, Padding, Paragraph, Tabs, Wrap},
 };
 
 use crate::{
 	app::{App, AppState, FullscreenSection, InputMode},
-	response::HttpResponse,
+	auth::Auth,
+	connection::ConnectionInfo,
+	graphql::GraphqlError,
+	keymap::Action,
+	mock::Example,
+	openapi::Violation,
+	response::{HttpResponse, SentRequest},
+	script::ScriptReport,
+	secrets,
+	theme::Theme,
+	timing::format_duration,
 	vim,
+	websocket::{self, EntryKind},
 };
 
 pub fn draw(frame: &mut Frame, app: &App) {
@@ -23,7 +34,11 @@ pub fn draw(frame: &mut Frame, app: &App) {
 		.split(frame.area());
 
 	match app.state {
-		AppState::Help => draw_help(frame, chunks[0]),
+		AppState::Help => draw_help(frame, chunks[0], app),
+		AppState::PreviewingRequest => {
+			draw_main_content(frame, chunks[0], app);
+			draw_request_preview(frame, centered_rect(80, 70, chunks[0]), app);
+		},
 		_ => draw_main_content(frame, chunks[0], app),
 	}
 
@@ -33,43 +48,54 @@ pub fn draw(frame: &mut Frame, app: &App) {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MainContentTab {
 	Request,
+	Collection,
 	History,
+	/// The mock server and the requests it answered
+	Mock,
 }
 
 impl MainContentTab {
-	pub const TABS: &'static [Self] = &[Self::Request, Self::History];
+	pub const TABS: &'static [Self] = &[Self::Request, Self::Collection, Self::History, Self::Mock];
 
 	const fn as_str(&self) -> &'static str {
 		match self {
 			Self::Request => "Request",
+			Self::Collection => "Collection",
 			Self::History => "History",
+			Self::Mock => "Mock",
 		}
 	}
 
 	pub const fn as_index(&self) -> usize {
 		match self {
 			Self::Request => 0,
-			Self::History => 1,
+			Self::Collection => 1,
+			Self::History => 2,
+			Self::Mock => 3,
 		}
 	}
 
 	pub const fn from_index(index: usize) -> Option<Self> {
 		match index {
 			0 => Some(Self::Request),
-			1 => Some(Self::History),
+			1 => Some(Self::Collection),
+			2 => Some(Self::History),
+			3 => Some(Self::Mock),
 			_ => None,
 		}
 	}
 }
 
 fn draw_main_content(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
 	let tab_titles: Vec<Line> = MainContentTab::TABS
 		.iter()
 		.map(|tab| {
 			if tab == &app.active_tab {
-				Line::from(Span::styled(tab.as_str(), Style::default().fg(Color::Yellow)))
+				Line::from(Span::styled(tab.as_str(), theme.accent()))
 			} else {
-				Line::from(Span::styled(tab.as_str(), Style::default().fg(Color::Gray)))
+				Line::from(Span::styled(tab.as_str(), theme.muted()))
 			}
 		})
 		.collect();
@@ -77,7 +103,7 @@ fn draw_main_content(frame: &mut Frame, area: Rect, app: &App) {
 	let tabs_widget = Tabs::new(tab_titles)
 		.block(Block::default().padding(Padding::top(1)))
 		.divider(symbols::DOT)
-		.highlight_style(Style::default().fg(Color::Yellow))
+		.highlight_style(theme.accent())
 		.select(app.active_tab.as_index());
 
 	let chunks = Layout::default()
@@ -89,7 +115,9 @@ fn draw_main_content(frame: &mut Frame, area: Rect, app: &App) {
 
 	match app.active_tab {
 		MainContentTab::Request => draw_request_tab(frame, chunks[1], app),
+		MainContentTab::Collection => draw_collection_tab(frame, chunks[1], app),
 		MainContentTab::History => draw_history_tab(frame, chunks[1], app),
+		MainContentTab::Mock => draw_mock_tab(frame, chunks[1], app),
 	}
 }
 
@@ -97,35 +125,58 @@ fn draw_main_content(frame: &mut Frame, area: Rect, app: &App) {
 pub enum RequestSectionTab {
 	Headers,
 	Body,
+	/// GraphQL variables, only shown for GraphQL requests
+	Variables,
 	Query,
+	PreRequestScript,
+	PostResponseScript,
+	/// AWS Signature Version 4 or HMAC signing
+	Auth,
+	/// The response the mock server replies with
+	Example,
 }
 
 impl RequestSectionTab {
-	pub const TABS: &'static [Self] = &[Self::Headers, Self::Body, Self::Query];
+	const TABS: &'static [Self] = &[
+		Self::Headers,
+		Self::Body,
+		Self::Query,
+		Self::Auth,
+		Self::PreRequestScript,
+		Self::PostResponseScript,
+		Self::Example,
+	];
+	const GRAPHQL_TABS: &'static [Self] = &[
+		Self::Headers,
+		Self::Body,
+		Self::Variables,
+		Self::Query,
+		Self::Auth,
+		Self::PreRequestScript,
+		Self::PostResponseScript,
+		Self::Example,
+	];
+
+	pub const fn tabs(graphql: bool) -> &'static [Self] {
+		if graphql { Self::GRAPHQL_TABS } else { Self::TABS }
+	}
 
 	const fn as_str(&self) -> &'static str {
 		match self {
 			Self::Headers => "Headers",
 			Self::Body => "Body",
+			Self::Variables => "Variables",
 			Self::Query => "Query",
+			Self::PreRequestScript => "Pre-request",
+			Self::PostResponseScript => "Post-response",
+			Self::Auth => "Auth",
+			Self::Example => "Example",
 		}
 	}
 
-	pub const fn as_index(&self) -> usize {
-		match self {
-			Self::Headers => 0,
-			Self::Body => 1,
-			Self::Query => 2,
-		}
-	}
-
-	pub const fn from_index(index: usize) -> Option<Self> {
-		match index {
-			0 => Some(Self::Headers),
-			1 => Some(Self::Body),
-			2 => Some(Self::Query),
-			_ => None,
-		}
+	/// Position among `tabs`, or the first tab's when it isn't one of them.
+	pub fn index_in(&self, tabs: &[Self]) -> usize {
+		tabs.iter().position(|tab| tab == self).unwrap_or(0)
 	}
 }
 
@@ -134,16 +185,35 @@ pub enum ResponseSectionTab {
 	Body,
 	Headers,
 	Cookies,
+	Redirects,
+	Timing,
+	Connection,
+	SentRequest,
+	Tests,
 }
 
 impl ResponseSectionTab {
-	pub const TABS: &'static [Self] = &[Self::Body, Self::Headers, Self::Cookies];
+	pub const TABS: &'static [Self] = &[
+		Self::Body,
+		Self::Headers,
+		Self::Cookies,
+		Self::Redirects,
+		Self::Timing,
+		Self::Connection,
+		Self::SentRequest,
+		Self::Tests,
+	];
 
 	const fn as_str(&self) -> &'static str {
 		match self {
 			Self::Body => "Body",
 			Self::Headers => "Headers",
 			Self::Cookies => "Cookies",
+			Self::Redirects => "Redirects",
+			Self::Timing => "Timing",
+			Self::Connection => "Connection",
+			Self::SentRequest => "Sent Request",
+			Self::Tests => "Tests",
 		}
 	}
 
@@ -152,6 +222,11 @@ impl ResponseSectionTab {
 			Self::Body => 0,
 			Self::Headers => 1,
 			Self::Cookies => 2,
+			Self::Redirects => 3,
+			Self::Timing => 4,
+			Self::Connection => 5,
+			Self::SentRequest => 6,
+			Self::Tests => 7,
 		}
 	}
 
@@ -160,42 +235,50 @@ impl ResponseSectionTab {
 			0 => Some(Self::Body),
 			1 => Some(Self::Headers),
 			2 => Some(Self::Cookies),
+			3 => Some(Self::Redirects),
+			4 => Some(Self::Timing),
+			5 => Some(Self::Connection),
+			6 => Some(Self::SentRequest),
+			7 => Some(Self::Tests),
 			_ => None,
 		}
 	}
 }
 
 fn draw_request_tab(frame: &mut Frame, area: Rect, app: &App) {
-	let request_section_tab_titles: Vec<Line> = RequestSectionTab::TABS
+	let theme = &app.config.theme;
+
+	let request_section_tabs = RequestSectionTab::tabs(app.current_request.is_graphql());
+	let request_section_tab_titles: Vec<Line> = request_section_tabs
 		.iter()
 		.map(|tab| {
 			if tab == &app.request_section_active_tab {
-				Line::from(Span::styled(tab.as_str(), Style::default().fg(Color::Yellow)))
+				Line::from(Span::styled(tab.as_str(), theme.accent()))
 			} else {
-				Line::from(Span::styled(tab.as_str(), Style::default().fg(Color::Gray)))
+				Line::from(Span::styled(tab.as_str(), theme.muted()))
 			}
 		})
 		.collect();
 
 	let request_section_tabs_widget = Tabs::new(request_section_tab_titles)
 		.block(Block::default().borders(Borders::ALL).title("Request"))
-		.highlight_style(Style::default().fg(Color::Yellow))
-		.select(app.request_section_active_tab.as_index());
+		.highlight_style(theme.accent())
+		.select(app.request_section_active_tab.index_in(request_section_tabs));
 
 	let response_section_tab_titles: Vec<Line> = ResponseSectionTab::TABS
 		.iter()
 		.map(|tab| {
 			if tab == &app.response_section_active_tab {
-				Line::from(Span::styled(tab.as_str(), Style::default().fg(Color::Yellow)))
+				Line::from(Span::styled(tab.as_str(), theme.accent()))
 			} else {
-				Line::from(Span::styled(tab.as_str(), Style::default().fg(Color::Gray)))
+				Line::from(Span::styled(tab.as_str(), theme.muted()))
 			}
 		})
 		.collect();
 
 	let response_section_tabs_widget = Tabs::new(response_section_tab_titles)
 		.block(Block::default().borders(Borders::ALL).title("Response"))
-		.highlight_style(Style::default().fg(Color::Yellow))
+		.highlight_style(theme.accent())
 		.select(app.response_section_active_tab.as_index());
 
 	let (request_section_height, response_section_height) = match app.fullscreen_section {
@@ -229,7 +312,18 @@ fn draw_request_tab(frame: &mut Frame, area: Rect, app: &App) {
 	match app.request_section_active_tab {
 		RequestSectionTab::Headers => draw_request_headers_tab(frame, request_section_chunks[1], app),
 		RequestSectionTab::Body => draw_request_body_tab(frame, request_section_chunks[1], app),
+		RequestSectionTab::Variables => draw_request_variables_tab(frame, request_section_chunks[1], app),
 		RequestSectionTab::Query => draw_request_queries_tab(frame, request_section_chunks[1], app),
+		RequestSectionTab::Auth => draw_request_auth_tab(frame, request_section_chunks[1], app),
+		RequestSectionTab::Example => draw_request_example_tab(frame, request_section_chunks[1], app),
+		RequestSectionTab::PreRequestScript | RequestSectionTab::PostResponseScript => {
+			draw_request_script_tab(frame, request_section_chunks[1], app);
+		},
+	}
+
+	if app.current_request.is_websocket() {
+		draw_websocket_log(frame, chunks[2], app);
+		return;
 	}
 
 	frame.render_widget(response_section_tabs_widget, response_section_chunks[0]);
@@ -237,120 +331,271 @@ fn draw_request_tab(frame: &mut Frame, area: Rect, app: &App) {
 		ResponseSectionTab::Body => draw_response_body_tab(frame, response_section_chunks[1], app),
 		ResponseSectionTab::Headers => draw_response_headers_tab(frame, response_section_chunks[1], app),
 		ResponseSectionTab::Cookies => {},
+		ResponseSectionTab::Redirects => {
+			render_response_content(frame, response_section_chunks[1], app, HttpResponse::formatted_redirects);
+		},
+		ResponseSectionTab::Timing => draw_response_timing_tab(frame, response_section_chunks[1], app),
+		ResponseSectionTab::Connection => draw_connection_tab(frame, response_section_chunks[1], app),
+		ResponseSectionTab::SentRequest => draw_sent_request_tab(frame, response_section_chunks[1], app),
+		ResponseSectionTab::Tests => draw_tests_tab(frame, response_section_chunks[1], app),
 	}
 }
 
 fn draw_method_url_section(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
 	let method_padding = 6;
+	let method = if app.current_request.is_websocket() {
+		"WS"
+	} else if app.current_request.is_grpc() {
+		"gRPC"
+	} else if app.current_request.is_graphql() {
+		"GQL"
+	} else {
+		app.current_request.method.as_str()
+	};
 
 	let chunks = Layout::default()
 		.direction(Direction::Horizontal)
-		.constraints([
-			Constraint::Length(app.current_request.method.as_str().len() as u16 + method_padding),
-			Constraint::Min(0),
-		])
+		.constraints([Constraint::Length(method.len() as u16 + method_padding), Constraint::Min(0)])
 		.split(area);
 
-	let method_widget = Paragraph::new(app.current_request.method.as_str())
-		.style(Style::default().fg(app.current_request.method.color()).add_modifier(Modifier::BOLD))
+	let method_widget = Paragraph::new(method)
+		.style(Style::default().fg(theme.method_color(&app.current_request.method)).add_modifier(Modifier::BOLD))
 		.alignment(Alignment::Center)
 		.block(
-			Block::default().borders(Borders::ALL).border_style(Style::default().fg(app.current_request.method.color())),
+			Block::default()
+				.borders(Borders::ALL)
+				.border_style(Style::default().fg(theme.method_color(&app.current_request.method))),
 		);
 	frame.render_widget(method_widget, chunks[0]);
 
 	if matches!(app.state, AppState::EditingUrl) {
 		frame.render_widget(app.get_url_textarea(), chunks[1]);
+	} else if matches!(app.state, AppState::EditingName) {
+		frame.render_widget(app.get_name_textarea(), chunks[1]);
+	} else if matches!(app.state, AppState::EditingSavePath) {
+		frame.render_widget(app.get_save_path_textarea(), chunks[1]);
 	} else {
-		let url_style = Style::default().fg(Color::White);
-		let url_text = if app.current_request.url.is_empty() { "" } else { &app.current_request.url };
-
-		let url_widget = Paragraph::new(url_text).style(url_style).block(
-			Block::default()
-				.borders(Borders::ALL)
-				.title("URL ( press 'u' to edit )")
-				.border_style(Style::default().fg(Color::White)),
-		);
+		let url_style = theme.text();
+		let url_text = secrets::mask(&app.current_request.url);
+
+		let url_title = if app.current_request.name.is_empty() {
+			String::from("URL ( press 'u' to edit )")
+		} else {
+			format!("{} ( press 'u' to edit URL, 'n' to rename )", app.current_request.name)
+		};
+		let url_title = if app.current_request.http_version.is_negotiate() {
+			url_title
+		} else {
+			format!("{url_title} · {}", app.current_request.http_version.as_str())
+		};
+
+		let url_widget = Paragraph::new(url_text)
+			.style(url_style)
+			.block(Block::default().borders(Borders::ALL).title(url_title).border_style(theme.text()));
 		frame.render_widget(url_widget, chunks[1]);
 	}
 }
 
 fn draw_request_headers_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
 	if matches!(app.state, AppState::EditingHeaders) {
 		frame.render_widget(app.get_headers_textarea(), area);
 	} else {
-		let headers_text =
-			if app.current_request.headers.is_empty() { "" } else { &app.current_request.formatted_headers() };
+		let headers_text = secrets::masked_request(&app.current_request).formatted_headers();
 
-		let headers_style = Style::default().fg(Color::White);
+		let headers_style = theme.text();
 
 		let headers_widget = Paragraph::new(headers_text).style(headers_style).block(
 			Block::default()
 				.borders(Borders::ALL)
 				.title("( press 'e' to edit )")
 				.padding(Padding::symmetric(2, 1))
-				.border_style(Style::default().fg(Color::White)),
+				.border_style(theme.text()),
 		);
 		frame.render_widget(headers_widget, area);
 	}
 }
 
 fn draw_request_body_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
 	if matches!(app.state, AppState::EditingBody) {
 		frame.render_widget(app.get_body_textarea(), area);
 	} else {
-		let body_text = if app.current_request.body.is_empty() { "" } else { &app.current_request.body };
-
-		let body_style =
-			if app.current_request.has_body() { Style::default().fg(Color::White) } else { Style::default().fg(Color::Gray) };
+		let body_text = secrets::mask(&app.current_request.body);
+
+		let (body_style, body_title) = if app.current_request.is_websocket() {
+			(
+				theme.text(),
+				format!(
+					"( press 'e' to edit the message · {}, '{}' to change · '{}' to {} )",
+					app.message_kind.as_str(),
+					app.config.keymap.keys_for(Action::NextMessageKind),
+					app.config.keymap.keys_for(Action::SendRequest),
+					if app.websocket.as_ref().is_some_and(|connection| connection.connected) { "send" } else { "connect" }
+				),
+			)
+		} else if app.current_request.is_grpc() {
+			(
+				theme.text(),
+				format!(
+					"( press 'e' to edit the JSON message · '{}' to pick the method · '{}' to load services by reflection )",
+					app.config.keymap.keys_for(Action::NextOperation),
+					app.config.keymap.keys_for(Action::IntrospectSchema),
+				),
+			)
+		} else if let Some(graphql) = &app.current_request.graphql {
+			(
+				theme.text(),
+				format!(
+					"( press 'e' to edit the query · operation {}, '{}' to change · '{}' to {} the schema )",
+					graphql.operation_name.as_deref().unwrap_or("default"),
+					app.config.keymap.keys_for(Action::NextOperation),
+					app.config.keymap.keys_for(Action::IntrospectSchema),
+					if app.graphql_schema.is_some() { "reload" } else { "load" }
+				),
+			)
+		} else if app.current_request.has_body() {
+			(theme.text(), String::from("( press 'e' to edit )"))
+		} else {
+			(
+				theme.muted(),
+				format!(
+					"( press 'e' to edit · not sent with {}, press '{}' to send )",
+					app.current_request.method.as_str(),
+					app.config.keymap.keys_for(Action::ToggleBody)
+				),
+			)
+		};
 
 		let body_widget = Paragraph::new(body_text).style(body_style).block(
 			Block::default()
 				.borders(Borders::ALL)
-				.title("( press 'e' to edit )")
+				.title(body_title)
 				.padding(Padding::symmetric(2, 1))
-				.border_style(Style::default().fg(Color::White)),
+				.border_style(theme.text()),
 		);
 		frame.render_widget(body_widget, area);
 	}
 }
 
+fn draw_request_variables_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
+	if matches!(app.state, AppState::EditingGraphqlVariables) {
+		frame.render_widget(app.get_graphql_variables_textarea(), area);
+	} else {
+		let variables_text = app.current_request.graphql.as_ref().map_or("", |graphql| graphql.variables.as_str());
+
+		let variables_widget = Paragraph::new(variables_text).style(theme.text()).block(
+			Block::default()
+				.borders(Borders::ALL)
+				.title("( press 'e' to edit the JSON variables )")
+				.padding(Padding::symmetric(2, 1))
+				.border_style(theme.text()),
+		);
+		frame.render_widget(variables_widget, area);
+	}
+}
+
 fn draw_request_queries_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
 	if matches!(app.state, AppState::EditingQueries) {
 		frame.render_widget(app.get_queries_textarea(), area);
 	} else {
-		let queries_text =
-			if app.current_request.queries.is_empty() { "" } else { &app.current_request.formatted_queries() };
+		let queries_text = secrets::masked_request(&app.current_request).formatted_queries();
 
-		let queries_style = Style::default().fg(Color::White);
+		let queries_style = theme.text();
 
 		let queries_widget = Paragraph::new(queries_text).style(queries_style).block(
 			Block::default()
 				.borders(Borders::ALL)
 				.title("( press 'e' to edit )")
 				.padding(Padding::symmetric(2, 1))
-				.border_style(Style::default().fg(Color::White)),
+				.border_style(theme.text()),
 		);
 		frame.render_widget(queries_widget, area);
 	}
 }
 
-fn create_response_block() -> Block<'static> {
-	Block::default()
-		.padding(Padding::symmetric(2, 1))
-		.borders(Borders::ALL)
-		.border_style(Style::default().fg(Color::White))
+fn draw_request_script_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
+	if matches!(app.state, AppState::EditingScript) {
+		frame.render_widget(app.get_script_textarea(), area);
+	} else {
+		let (script, title) = if app.request_section_active_tab == RequestSectionTab::PreRequestScript {
+			(&app.current_request.pre_request_script, "( press 'e' to edit · Rhai, run before sending with `request` )")
+		} else {
+			(
+				&app.current_request.post_response_script,
+				"( press 'e' to edit · Rhai, run on the response with `request` and `response` )",
+			)
+		};
+
+		let script_widget = Paragraph::new(script.as_str()).style(theme.text()).block(
+			Block::default().borders(Borders::ALL).title(title).padding(Padding::symmetric(2, 1)).border_style(theme.text()),
+		);
+		frame.render_widget(script_widget, area);
+	}
+}
+
+fn draw_request_auth_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
+	if matches!(app.state, AppState::EditingAuth) {
+		frame.render_widget(app.get_auth_textarea(), area);
+	} else {
+		let auth_form = secrets::masked_request(&app.current_request).auth.as_ref().map_or_else(String::new, Auth::to_form);
+
+		let auth_widget = Paragraph::new(auth_form).style(theme.text()).block(
+			Block::default()
+				.borders(Borders::ALL)
+				.title("( press 'e' to edit · type: aws-sigv4 or hmac, signed right before sending )")
+				.padding(Padding::symmetric(2, 1))
+				.border_style(theme.text()),
+		);
+		frame.render_widget(auth_widget, area);
+	}
+}
+
+fn draw_request_example_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
+	if matches!(app.state, AppState::EditingExample) {
+		frame.render_widget(app.get_example_textarea(), area);
+	} else {
+		let example_text = app.current_request.example.as_ref().map_or_else(String::new, Example::to_text);
+		let title = format!(
+			"( press 'e' to edit, '{}' to use the response · served by the mock server, '{}' )",
+			app.config.keymap.keys_for(Action::SaveExample),
+			app.config.keymap.keys_for(Action::ToggleMockServer)
+		);
+
+		let example_widget = Paragraph::new(example_text).style(theme.text()).block(
+			Block::default().borders(Borders::ALL).title(title).padding(Padding::symmetric(2, 1)).border_style(theme.text()),
+		);
+		frame.render_widget(example_widget, area);
+	}
+}
+
+fn create_response_block(theme: &Theme) -> Block<'static> {
+	Block::default().padding(Padding::symmetric(2, 1)).borders(Borders::ALL).border_style(theme.text())
 }
 
 fn render_response_content<F>(frame: &mut Frame, area: Rect, app: &App, content_fn: F)
 where
 	F: FnOnce(&HttpResponse) -> String,
 {
-	if app.loading {
-		let widget = Paragraph::new("loading...")
-			.style(Style::default().fg(Color::White))
-			.alignment(Alignment::Center)
-			.block(create_response_block());
+	let theme = &app.config.theme;
+
+	if app.loading && app.streaming.is_none() {
+		let widget =
+			Paragraph::new("loading...").style(theme.text()).alignment(Alignment::Center).block(create_response_block(theme));
 		frame.render_widget(widget, area);
 		return;
 	}
@@ -358,24 +603,52 @@ where
 	if let Some(response) = app.get_current_response() {
 		let content = content_fn(response);
 		let status_text = app.get_current_response().map_or(String::new(), |response| {
+			let redirects = match response.redirects.len() {
+				0 => String::new(),
+				1 => String::from(" | 1 redirect"),
+				count => format!(" | {count} redirects"),
+			};
+			let time = if app.is_streaming(response) {
+				String::from("streaming")
+			} else if response.stopped {
+				format!("{}ms, stopped", response.response_time)
+			} else if response.error.is_some() {
+				format!("{}ms, incomplete", response.response_time)
+			} else {
+				format!("{}ms", response.response_time)
+			};
+			let tests = response
+				.script_report
+				.as_ref()
+				.and_then(ScriptReport::summary)
+				.map_or_else(String::new, |summary| format!(" | {summary}"));
 			format!(
-				"( {} {} | {} | {}ms )",
+				"( {} {}{redirects} | {} | {time}{tests} )",
 				response.status_code,
 				response.status_text,
-				response.formatted_size(),
-				response.response_time
+				response.formatted_sizes(),
 			)
 		});
 
-		let widget = Paragraph::new(content).style(Style::default().fg(Color::White)).block(
-			create_response_block().title("( press 'r' to inspect )").title(status_text.to_span().into_centered_line()),
-		);
+		let (hint, scroll) = if app.is_streaming(response) {
+			// Follow the newest data while it streams in
+			let visible_lines = area.height.saturating_sub(4);
+			let scroll = (content.lines().count() as u16).saturating_sub(visible_lines);
+			(format!("( press '{}' to stop )", app.config.keymap.keys_for(Action::StopStream)), scroll)
+		} else {
+			(String::from("( press 'r' to inspect )"), 0)
+		};
+
+		let widget = Paragraph::new(content)
+			.style(theme.text())
+			.block(create_response_block(theme).title(hint).title(status_text.to_span().into_centered_line()))
+			.scroll((scroll, 0));
 		frame.render_widget(widget, area);
 	} else {
 		let widget = Paragraph::new("No response yet\nSend a request to see the response here")
-			.style(Style::default().fg(Color::Gray))
+			.style(theme.muted())
 			.alignment(Alignment::Center)
-			.block(create_response_block());
+			.block(create_response_block(theme));
 		frame.render_widget(widget, area);
 	}
 }
@@ -384,16 +657,101 @@ fn draw_response_body_tab(frame: &mut Frame, area: Rect, app: &App) {
 	if matches!(app.state, AppState::InspectingResponseBody) {
 		frame.render_widget(app.get_response_body_textarea(), area);
 	} else {
-		render_response_content(frame, area, app, |response| {
-			if response.is_json() {
-				response.pretty_json().unwrap_or_else(|_| response.body.clone())
-			} else {
-				response.body.clone()
+		let response = if app.loading { None } else { app.get_current_response() };
+		let validation = response.and_then(|response| response.validation.as_ref());
+		let graphql_errors = response
+			.filter(|_| app.current_request.is_graphql())
+			.and_then(HttpResponse::graphql_errors)
+			.filter(|errors| !errors.is_empty());
+
+		let body_area = if validation.is_some() || graphql_errors.is_some() {
+			let chunks = Layout::default()
+				.direction(Direction::Horizontal)
+				.constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+				.split(area);
+			let panels = Layout::default()
+				.direction(Direction::Vertical)
+				.constraints(if validation.is_some() && graphql_errors.is_some() {
+					[Constraint::Percentage(50), Constraint::Percentage(50)]
+				} else {
+					[Constraint::Percentage(100), Constraint::Percentage(0)]
+				})
+				.split(chunks[1]);
+
+			if let Some(errors) = &graphql_errors {
+				draw_graphql_errors_panel(frame, panels[0], &app.config.theme, errors);
 			}
-		});
+			if let Some(violations) = validation {
+				draw_validation_panel(frame, panels[usize::from(graphql_errors.is_some())], &app.config.theme, violations);
+			}
+			chunks[0]
+		} else {
+			area
+		};
+
+		render_response_content(frame, body_area, app, HttpResponse::formatted_body);
 	}
 }
 
+fn draw_validation_panel(frame: &mut Frame, area: Rect, theme: &Theme, violations: &[Violation]) {
+	let (title, border_color) = if violations.is_empty() {
+		(String::from("Validation ( ok )"), theme.success)
+	} else {
+		(format!("Validation ( {} issues )", violations.len()), theme.error)
+	};
+
+	let items: Vec<ListItem> = if violations.is_empty() {
+		vec![ListItem::new("Response matches the OpenAPI spec").style(Style::default().fg(theme.success))]
+	} else {
+		violations
+			.iter()
+			.map(|violation| {
+				ListItem::new(vec![
+					Line::from(Span::styled(violation.path.clone(), Style::default().fg(theme.accent))),
+					Line::from(Span::styled(format!("  {}", violation.message), theme.text())),
+				])
+			})
+			.collect()
+	};
+
+	let validation_list = List::new(items).block(
+		Block::default()
+			.borders(Borders::ALL)
+			.title(title)
+			.padding(Padding::horizontal(1))
+			.border_style(Style::default().fg(border_color)),
+	);
+	frame.render_widget(validation_list, area);
+}
+
+/// The `errors` of a GraphQL result next to its `data`, which may be partial.
+fn draw_graphql_errors_panel(frame: &mut Frame, area: Rect, theme: &Theme, errors: &[GraphqlError]) {
+	let items: Vec<ListItem> = errors
+		.iter()
+		.map(|error| {
+			let mut location = error.path.clone().unwrap_or_default();
+			if !error.locations.is_empty() {
+				location = format!("{location} (line {})", error.locations.join(", ")).trim_start().to_string();
+			}
+
+			let mut lines = vec![Line::from(Span::styled(format!("  {}", error.message), theme.text()))];
+			if !location.is_empty() {
+				lines.insert(0, Line::from(Span::styled(location, Style::default().fg(theme.accent))));
+			}
+			ListItem::new(lines)
+		})
+		.collect();
+
+	let errors_list = List::new(items).block(
+		Block::default()
+			.borders(Borders::ALL)
+			.title(format!("GraphQL errors ( {} )", errors.len()))
+			.padding(Padding::horizontal(1))
+			.border_style(Style::default().fg(theme.error)),
+	);
+	frame.render_widget(errors_list, area);
+}
+
 fn draw_response_headers_tab(frame: &mut Frame, area: Rect, app: &App) {
 	if matches!(app.state, AppState::InspectingResponseHeaders) {
 		frame.render_widget(app.get_response_headers_textarea(), area);
@@ -402,12 +760,179 @@ fn draw_response_headers_tab(frame: &mut Frame, area: Rect, app: &App) {
 	}
 }
 
+fn draw_connection_tab(frame: &mut Frame, area: Rect, app: &App) {
+	render_response_content(frame, area, app, |response| {
+		response
+			.connection
+			.as_ref()
+			.map_or_else(|| String::from("Connection details were not recorded for this response"), ConnectionInfo::formatted)
+	});
+}
+
+fn draw_sent_request_tab(frame: &mut Frame, area: Rect, app: &App) {
+	render_response_content(frame, area, app, |response| {
+		response
+			.sent_request
+			.as_ref()
+			.map_or_else(|| String::from("The sent request was not recorded for this response"), SentRequest::formatted)
+	});
+}
+
+fn draw_tests_tab(frame: &mut Frame, area: Rect, app: &App) {
+	render_response_content(frame, area, app, |response| {
+		response.script_report.as_ref().map_or_else(
+			|| String::from("No scripts ran for this request\nAdd them in the Pre-request and Post-response tabs"),
+			ScriptReport::formatted,
+		)
+	});
+}
+
+/// One waterfall row per phase: label, bar offset by the phases before it, and the duration.
+fn draw_response_timing_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
+	let Some(timings) = app
+		.get_current_response()
+		.map(|response| response.timings)
+		.filter(|timings| !app.loading && !timings.total().is_zero())
+	else {
+		render_response_content(frame, area, app, |_| String::from("No timing breakdown recorded for this response"));
+		return;
+	};
+
+	let label_width = timings.phases().iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+	let duration_width = 10;
+	// Borders, padding and the gaps around the bar
+	let bar_width = area.width.saturating_sub(6 + label_width as u16 + duration_width + 2);
+
+	let mut lines: Vec<Line> = timings
+		.waterfall(bar_width)
+		.into_iter()
+		.map(|bar| {
+			Line::from(vec![
+				Span::styled(format!("{:<label_width$} ", bar.label), theme.muted()),
+				Span::raw(" ".repeat(bar.offset.into())),
+				Span::styled("█".repeat(bar.length.into()), theme.accent()),
+				Span::raw(" ".repeat(bar_width.saturating_sub(bar.offset + bar.length).into())),
+				Span::styled(
+					format!(" {:>width$}", format_duration(bar.duration), width = duration_width.into()),
+					theme.text(),
+				),
+			])
+		})
+		.collect();
+	lines.push(Line::default());
+	lines.push(Line::from(vec![
+		Span::styled(format!("{:<label_width$} ", "Total"), theme.muted()),
+		Span::styled(format_duration(timings.total()), theme.text()),
+	]));
+
+	frame.render_widget(Paragraph::new(lines).block(create_response_block(theme)), area);
+}
+
+/// The message log, newest at the bottom, in place of the response tabs for `ws://` / `wss://` URLs.
+fn draw_websocket_log(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
+	let title = match &app.websocket {
+		Some(connection) if connection.connected => format!(
+			"Messages ( connected to {} · '{}' to close )",
+			connection.url,
+			app.config.keymap.keys_for(Action::CloseWebSocket)
+		),
+		Some(connection) => format!("Messages ( connecting to {}... )", connection.url),
+		None => format!("Messages ( press '{}' to connect )", app.config.keymap.keys_for(Action::SendRequest)),
+	};
+	let block =
+		Block::default().borders(Borders::ALL).title(title).padding(Padding::horizontal(1)).border_style(theme.text());
+
+	if app.websocket_log.is_empty() {
+		let widget = Paragraph::new("No messages yet\nConnect to see frames sent and received here")
+			.style(theme.muted())
+			.alignment(Alignment::Center)
+			.block(block);
+		frame.render_widget(widget, area);
+		return;
+	}
+
+	let lines: Vec<Line> = app
+		.websocket_log
+		.iter()
+		.flat_map(|entry| {
+			let style = match (entry.kind, entry.direction) {
+				(EntryKind::Error, _) => Style::default().fg(theme.error),
+				(EntryKind::Connected, _) => Style::default().fg(theme.success),
+				(kind, _) if !kind.is_data() => theme.muted(),
+				(_, websocket::Direction::Sent) => theme.accent(),
+				(_, websocket::Direction::Received) => theme.text(),
+			};
+			entry.formatted().lines().map(|line| Line::styled(line.to_string(), style)).collect::<Vec<_>>()
+		})
+		.collect();
+
+	// Keep the newest frames in view
+	let scroll = lines.len().saturating_sub(area.height.saturating_sub(2).into());
+	frame.render_widget(Paragraph::new(lines).block(block).scroll((scroll as u16, 0)), area);
+}
+
+fn draw_collection_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
+	let title = format!("Collection ( {} )", app.http_file_path.display());
+
+	if app.collection.is_empty() {
+		let no_requests = Paragraph::new("No saved requests\nOpen a .http file or press 'a' to add the current request")
+			.style(theme.muted())
+			.alignment(Alignment::Center)
+			.block(Block::default().borders(Borders::ALL).title(title).border_style(theme.text()));
+		frame.render_widget(no_requests, area);
+		return;
+	}
+
+	let method_width = app.collection.iter().map(|request| request.method.as_str().len()).max().unwrap_or(0);
+
+	let items: Vec<ListItem> = app
+		.collection
+		.iter()
+		.map(|request| {
+			let label = if request.name.is_empty() { request.url.as_str() } else { request.name.as_str() };
+
+			ListItem::new(Line::from(vec![
+				Span::styled(
+					format!("{:<method_width$} ", request.method.as_str()),
+					Style::default().fg(theme.method_color(&request.method)).add_modifier(Modifier::BOLD),
+				),
+				Span::styled(label.to_string(), theme.text()),
+			]))
+		})
+		.collect();
+
+	let collection_list = List::new(items)
+		.block(Block::default().borders(Borders::ALL).title(title).border_style(theme.text()))
+		.highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+	let mut list_state = ratatui::widgets::ListState::default().with_selected(app.selected_collection_request);
+	frame.render_stateful_widget(collection_list, area, &mut list_state);
+}
+
 fn draw_history_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+	let proxy_keys = app.config.keymap.keys_for(Action::ToggleProxy);
+	let title = app.proxy.as_ref().map_or_else(
+		|| format!("History ( press '{proxy_keys}' to capture traffic through a proxy )"),
+		|proxy| {
+			format!(
+				"History · capturing via http://{} ( press '{proxy_keys}' to stop, Enter opens a request )",
+				proxy.address
+			)
+		},
+	);
+
 	if app.responses.is_empty() {
 		let no_history = Paragraph::new("No request history\nSend some requests to see them here")
-			.style(Style::default().fg(Color::Gray))
+			.style(theme.muted())
 			.alignment(Alignment::Center)
-			.block(Block::default().borders(Borders::ALL).title("History").border_style(Style::default().fg(Color::White)));
+			.block(Block::default().borders(Borders::ALL).title(title).border_style(theme.text()));
 		frame.render_widget(no_history, area);
 	} else {
 		let items: Vec<ListItem> = app
@@ -415,17 +940,25 @@ fn draw_history_tab(frame: &mut Frame, area: Rect, app: &App) {
 			.iter()
 			.enumerate()
 			.map(|(i, response)| {
+				let ttfb = if response.timings.total().is_zero() {
+					String::new()
+				} else {
+					format!(" (TTFB {})", format_duration(response.timings.ttfb))
+				};
+				let request = app
+					.history_request(response)
+					.map_or_else(String::new, |request| format!(" {} {}", request.method.as_str(), request.url));
 				let content = format!(
-					"{} {} - {}ms",
+					"{} {}{request} - {}ms{ttfb}",
 					response.status_code,
 					response.created_at.format("%H:%M:%S"),
 					response.response_time,
 				);
 
 				let style = if Some(i) == app.selected_response {
-					Style::default().fg(response.status_color()).add_modifier(Modifier::BOLD)
+					Style::default().fg(theme.status_color(response.status_code)).add_modifier(Modifier::BOLD)
 				} else {
-					Style::default().fg(response.status_color())
+					Style::default().fg(theme.status_color(response.status_code))
 				};
 
 				ListItem::new(content).style(style)
@@ -433,38 +966,90 @@ fn draw_history_tab(frame: &mut Frame, area: Rect, app: &App) {
 			.collect();
 
 		let history_list = List::new(items)
-			.block(Block::default().borders(Borders::ALL).title("History").border_style(Style::default().fg(Color::White)))
+			.block(Block::default().borders(Borders::ALL).title(title).border_style(theme.text()))
 			.highlight_style(Style::default().add_modifier(Modifier::REVERSED));
 
 		frame.render_stateful_widget(history_list, area, &mut ratatui::widgets::ListState::default());
 	}
 }
 
+fn draw_mock_tab(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+	let toggle_keys = app.config.keymap.keys_for(Action::ToggleMockServer);
+
+	let title = app.mock_server.as_ref().map_or_else(
+		|| format!("Mock server stopped ( press '{toggle_keys}' to serve the collection's examples )"),
+		|server| {
+			format!("Serving {} mocks on http://{} ( press '{toggle_keys}' to stop )", server.route_count(), server.address)
+		},
+	);
+	let block = Block::default().borders(Borders::ALL).title(title).border_style(theme.text());
+
+	if app.mock_log.is_empty() {
+		let empty = Paragraph::new(
+			"No requests yet
+Save examples with the Example tab, then point your app here",
+		)
+		.style(theme.muted())
+		.alignment(Alignment::Center)
+		.block(block);
+		frame.render_widget(empty, area);
+		return;
+	}
+
+	// Latest first
+	let items: Vec<ListItem> = app
+		.mock_log
+		.iter()
+		.rev()
+		.map(|hit| {
+			let style = if hit.route.is_some() { Style::default().fg(theme.status_color(hit.status)) } else { theme.muted() };
+			ListItem::new(hit.formatted()).style(style)
+		})
+		.collect();
+	frame.render_widget(List::new(items).block(block), area);
+}
+
 fn draw_footer(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
 	let should_hide_vim_mode = matches!(app.state, AppState::Normal | AppState::Help);
 
 	let vim_mode_text = format!("-- {} --", app.vim.mode);
 	let vim_mode_width = if should_hide_vim_mode { 0 } else { vim_mode_text.chars().count() as u16 + 2 };
 
-	let info_text = format!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+	let environment_name = app.get_active_environment().map_or("no env", |environment| environment.name.as_str());
+	let info_text = format!(
+		"{} · {}{}{} | {} v{}",
+		app.workspace.name,
+		environment_name,
+		if app.send_options.decompress { "" } else { " · raw bodies" },
+		if app.send_options.follow_redirects { "" } else { " · no redirects" },
+		env!("CARGO_PKG_NAME"),
+		env!("CARGO_PKG_VERSION")
+	);
 	let info_text_width = info_text.chars().count() as u16;
 
 	let vim_mode_widget =
-		Paragraph::new(vim_mode_text).style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+		Paragraph::new(vim_mode_text).style(Style::default().fg(theme.mode).add_modifier(Modifier::BOLD));
 
 	let keybindings_text = match (&app.vim.mode, &app.input_mode) {
-		(vim::Mode::Normal, InputMode::Editing) => "Save: Enter | Cancel: Escape",
-		(_, InputMode::Normal) => "Help: ? | Switch tabs: Tab | Change method: m/M | Send request: Enter",
-		_ => "",
+		(vim::Mode::Normal, InputMode::Editing) => String::from("Save: Enter | Cancel: Escape"),
+		(_, InputMode::Normal) => app.config.keymap.footer_hints(),
+		_ => String::new(),
 	};
 
-	let mut keybindings_widget = Paragraph::new(keybindings_text).style(Style::default().fg(Color::Yellow));
-
-	if let Some(error) = &app.error_message {
-		keybindings_widget = Paragraph::new(format!("Error: {error}")).style(Style::default().fg(Color::Red));
-	}
+	let keybindings_widget = app.error_message.as_ref().map_or_else(
+		|| {
+			app.status_message.as_ref().map_or_else(
+				|| Paragraph::new(keybindings_text).style(Style::default().fg(theme.accent)),
+				|status| Paragraph::new(status.as_str()).style(Style::default().fg(theme.success)),
+			)
+		},
+		|error| Paragraph::new(format!("Error: {error}")).style(Style::default().fg(theme.error)),
+	);
 
-	let info_widget = Paragraph::new(info_text).style(Style::default().fg(Color::Magenta));
+	let info_widget = Paragraph::new(info_text).style(Style::default().fg(theme.info));
 
 	let layout = Layout::default()
 		.direction(Direction::Horizontal)
@@ -476,31 +1061,34 @@ fn draw_footer(frame: &mut Frame, area: Rect, app: &App) {
 	frame.render_widget(info_widget, layout[2]);
 }
 
-fn draw_help(frame: &mut Frame, area: Rect) {
-	let help_text = vec![
-		"Navigation:",
-		"  Tab - Iterate through tabs",
-		"  ]   - Iterate through request tabs",
-		"  }   - Iterate through response tabs",
-		"  q   - Quit application",
-		"",
-		"Request Building:",
-		"  u             - Edit URL",
-		"  e             - Edit focused request headers/body ..etc",
-		"  r             - Inspect focused response headers/body ..etc",
-		"  m/M           - Change HTTP method (forward/backward)",
-		"  Enter         - Send request",
-		"",
-		"Press Esc to close this help screen.",
-	];
+fn draw_help(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
+	let mut help_text = app.config.keymap.help_lines();
+	help_text.push(String::new());
+	help_text.push(String::from("Press Esc to close this help screen."));
 
 	let help_paragraph = Paragraph::new(help_text.join("\n"))
-		.style(Style::default().fg(Color::White))
-		.block(Block::default().borders(Borders::ALL).title("Help").border_style(Style::default().fg(Color::Yellow)));
+		.style(theme.text())
+		.block(Block::default().borders(Borders::ALL).title("Help").border_style(Style::default().fg(theme.accent)));
 	frame.render_widget(help_paragraph, area);
 }
 
-#[allow(dead_code)]
+/// The request as it would be sent, as a popup; generated values change again when it's sent.
+fn draw_request_preview(frame: &mut Frame, area: Rect, app: &App) {
+	let theme = &app.config.theme;
+
+	let preview = Paragraph::new(app.request_preview.as_str()).style(theme.text()).wrap(Wrap { trim: false }).block(
+		Block::default()
+			.borders(Borders::ALL)
+			.title("Preview ( {{$functions}} are generated again when sent · Esc to close )")
+			.padding(Padding::symmetric(2, 1))
+			.border_style(Style::default().fg(theme.accent)),
+	);
+	frame.render_widget(Clear, area);
+	frame.render_widget(preview, area);
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
 	let popup_layout = Layout::default()
 		.direction(Direction::Vertical)
@@ -0,0 +1,153 @@
+use std::io::{self, Read, Write};
+
+/// Encodings we can decode, in the order they're offered in `Accept-Encoding`.
+pub const SUPPORTED_ENCODINGS: &[&str] = &["gzip", "deflate", "br", "zstd"];
+
+/// Decodes a body sent with `Content-Encoding: <content_encoding>`, undoing multiple encodings in reverse order.
+/// Fails once any stage would produce more than `limit` bytes, so a small payload can't expand without bound.
+pub fn decompress(content_encoding: &str, bytes: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+	let mut decoded = bytes.to_vec();
+
+	for encoding in content_encoding.split(',').map(str::trim).rev() {
+		decoded = match encoding.to_lowercase().as_str() {
+			"" | "identity" => decoded,
+			"gzip" | "x-gzip" => read_all(flate2::read::MultiGzDecoder::new(decoded.as_slice()), limit)?,
+			// Servers disagree on whether `deflate` means zlib-wrapped or raw deflate
+			"deflate" => read_all(flate2::read::ZlibDecoder::new(decoded.as_slice()), limit).or_else(|error| {
+				if error.kind() == io::ErrorKind::FileTooLarge {
+					return Err(error);
+				}
+				read_all(flate2::read::DeflateDecoder::new(decoded.as_slice()), limit)
+			})?,
+			"br" => read_all(brotli_decompressor::Decompressor::new(decoded.as_slice(), 4096), limit)?,
+			"zstd" => {
+				read_all(ruzstd::decoding::StreamingDecoder::new(decoded.as_slice()).map_err(io::Error::other)?, limit)?
+			},
+			other => {
+				return Err(io::Error::new(io::ErrorKind::Unsupported, format!("unsupported content encoding: {other}")));
+			},
+		};
+	}
+
+	Ok(decoded)
+}
+
+/// Decompresses a body chunk by chunk as it streams in, for the encodings whose decoders can be fed that way.
+pub struct StreamDecoder {
+	writer: DecodingWriter,
+	/// How many more decoded bytes are allowed
+	remaining: usize,
+}
+
+enum DecodingWriter {
+	Gzip(flate2::write::MultiGzDecoder<Vec<u8>>),
+	Deflate(flate2::write::ZlibDecoder<Vec<u8>>),
+	Brotli(Box<brotli_decompressor::DecompressorWriter<Vec<u8>>>),
+}
+
+impl StreamDecoder {
+	/// `None` for `zstd` and stacked encodings, which are only decoded once the body is complete.
+	pub fn new(content_encoding: &str, limit: usize) -> Option<Self> {
+		let writer = match content_encoding.trim().to_lowercase().as_str() {
+			"gzip" | "x-gzip" => DecodingWriter::Gzip(flate2::write::MultiGzDecoder::new(Vec::new())),
+			"deflate" => DecodingWriter::Deflate(flate2::write::ZlibDecoder::new(Vec::new())),
+			"br" => DecodingWriter::Brotli(Box::new(brotli_decompressor::DecompressorWriter::new(Vec::new(), 4096))),
+			_ => return None,
+		};
+		Some(Self { writer, remaining: limit })
+	}
+
+	/// What `chunk` decodes to; the end of a chunk that doesn't complete a block shows up with the next one.
+	pub fn decode(&mut self, chunk: &[u8]) -> io::Result<Vec<u8>> {
+		let output = match &mut self.writer {
+			DecodingWriter::Gzip(decoder) => write_chunk(decoder, chunk)?.get_mut(),
+			DecodingWriter::Deflate(decoder) => write_chunk(decoder, chunk)?.get_mut(),
+			DecodingWriter::Brotli(decoder) => write_chunk(decoder.as_mut(), chunk)?.get_mut(),
+		};
+		let decoded = std::mem::take(output);
+		self.remaining = self.remaining.checked_sub(decoded.len()).ok_or_else(|| {
+			io::Error::new(io::ErrorKind::FileTooLarge, String::from("decoded body is larger than the limit"))
+		})?;
+		Ok(decoded)
+	}
+}
+
+fn write_chunk<'a, W: Write>(writer: &'a mut W, chunk: &[u8]) -> io::Result<&'a mut W> {
+	writer.write_all(chunk)?;
+	writer.flush()?;
+	Ok(writer)
+}
+
+fn read_all(reader: impl Read, limit: usize) -> io::Result<Vec<u8>> {
+	let mut buffer = Vec::new();
+	// One byte over the limit tells a body of exactly `limit` bytes apart from a larger one
+	reader.take(limit as u64 + 1).read_to_end(&mut buffer)?;
+	if buffer.len() > limit {
+		return Err(io::Error::new(io::ErrorKind::FileTooLarge, format!("decoded body is larger than {limit} bytes")));
+	}
+	Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const LIMIT: usize = 1024;
+
+	fn gzip(bytes: &[u8]) -> Vec<u8> {
+		let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+		encoder.write_all(bytes).unwrap();
+		encoder.finish().unwrap()
+	}
+
+	#[test]
+	fn test_decompress() {
+		let body = br#"{"users": [1, 2, 3]}"#;
+
+		assert_eq!(decompress("gzip", &gzip(body), LIMIT).unwrap(), body);
+		assert_eq!(decompress("identity", body, LIMIT).unwrap(), body);
+
+		let mut deflate = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+		deflate.write_all(body).unwrap();
+		assert_eq!(decompress("deflate", &deflate.finish().unwrap(), LIMIT).unwrap(), body);
+
+		assert_eq!(decompress("gzip, gzip", &gzip(&gzip(body)), LIMIT).unwrap(), body);
+	}
+
+	#[test]
+	fn test_decompress_limit() {
+		let bomb = gzip(&vec![0; 1024 * 1024]);
+		assert!(bomb.len() < LIMIT * 2);
+		assert_eq!(decompress("gzip", &bomb, LIMIT).unwrap_err().kind(), io::ErrorKind::FileTooLarge);
+		assert_eq!(decompress("gzip", &gzip(&[b'a'; LIMIT]), LIMIT).unwrap().len(), LIMIT);
+	}
+
+	#[test]
+	fn test_stream_decoder() {
+		let body = b"data: one\n\ndata: two\n\n".repeat(20);
+		let compressed = gzip(&body);
+		let mut decoder = StreamDecoder::new("gzip", LIMIT).unwrap();
+		let output: Vec<u8> = compressed.chunks(7).flat_map(|chunk| decoder.decode(chunk).unwrap()).collect();
+		assert_eq!(output, body);
+
+		let mut deflate = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+		deflate.write_all(&body).unwrap();
+		let mut decoder = StreamDecoder::new("Deflate", LIMIT).unwrap();
+		let output: Vec<u8> =
+			deflate.finish().unwrap().chunks(5).flat_map(|chunk| decoder.decode(chunk).unwrap()).collect();
+		assert_eq!(output, body);
+
+		assert!(StreamDecoder::new("br", LIMIT).is_some());
+		assert!(StreamDecoder::new("zstd", LIMIT).is_none());
+		assert!(StreamDecoder::new("gzip, br", LIMIT).is_none());
+
+		let mut decoder = StreamDecoder::new("gzip", LIMIT).unwrap();
+		assert_eq!(decoder.decode(&gzip(&vec![0; LIMIT * 4])).unwrap_err().kind(), io::ErrorKind::FileTooLarge);
+	}
+
+	#[test]
+	fn test_decompress_errors() {
+		assert!(decompress("gzip", b"not gzip", LIMIT).is_err());
+		assert_eq!(decompress("compress", b"", LIMIT).unwrap_err().kind(), io::ErrorKind::Unsupported);
+	}
+}
@@ -0,0 +1,114 @@
+use base64::Engine;
+use chrono::{DateTime, Duration, Months, SecondsFormat, Utc};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+
+/// Evaluates a `{{$name arguments}}` reference, `expression` being what follows the `$`; `None` when the function is
+/// unknown or its arguments are invalid, so the reference is left in place.
+pub fn evaluate(expression: &str) -> Option<String> {
+	let (name, arguments) = expression.split_once(char::is_whitespace).unwrap_or((expression, ""));
+	let arguments = arguments.trim();
+
+	match name {
+		"uuid" | "guid" if arguments.is_empty() => Some(uuid::Uuid::new_v4().to_string()),
+		"timestamp" => Some(offset_now(arguments)?.timestamp().to_string()),
+		"isoTimestamp" => Some(offset_now(arguments)?.to_rfc3339_opts(SecondsFormat::Millis, true)),
+		"randomInt" => {
+			let (min, max) = arguments.split_once(char::is_whitespace)?;
+			let (min, max): (i64, i64) = (min.trim().parse().ok()?, max.trim().parse().ok()?);
+			(min < max).then(|| rand::rng().random_range(min..max).to_string())
+		},
+		"base64" => Some(base64(arguments.as_bytes())),
+		"sha256" => Some(sha256_hex(arguments.as_bytes())),
+		_ => None,
+	}
+}
+
+/// The current time moved by an optional `<amount> <unit>` offset, e.g. `-3 d`; units are `y`, `M`, `w`, `d`, `h`,
+/// `m`, `s` and `ms`.
+fn offset_now(offset: &str) -> Option<DateTime<Utc>> {
+	let now = Utc::now();
+	if offset.is_empty() {
+		return Some(now);
+	}
+
+	let (amount, unit) = offset.split_once(char::is_whitespace)?;
+	let amount: i64 = amount.parse().ok()?;
+	let months = |months: i64| {
+		let months = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+		if amount < 0 { now.checked_sub_months(months) } else { now.checked_add_months(months) }
+	};
+
+	match unit.trim() {
+		"y" => months(amount.checked_mul(12)?),
+		"M" => months(amount),
+		"w" => now.checked_add_signed(Duration::try_weeks(amount)?),
+		"d" => now.checked_add_signed(Duration::try_days(amount)?),
+		"h" => now.checked_add_signed(Duration::try_hours(amount)?),
+		"m" => now.checked_add_signed(Duration::try_minutes(amount)?),
+		"s" => now.checked_add_signed(Duration::try_seconds(amount)?),
+		"ms" => now.checked_add_signed(Duration::try_milliseconds(amount)?),
+		_ => None,
+	}
+}
+
+pub fn base64(bytes: &[u8]) -> String {
+	base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+	hex(&Sha256::digest(bytes))
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+	bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+		let _ = write!(hex, "{byte:02x}");
+		hex
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_generated_values() {
+		let uuid = evaluate("uuid").unwrap();
+		assert!(uuid::Uuid::parse_str(&uuid).is_ok());
+		assert_ne!(evaluate("guid").unwrap(), uuid);
+
+		let timestamp: i64 = evaluate("timestamp").unwrap().parse().unwrap();
+		assert!((timestamp - Utc::now().timestamp()).abs() <= 1);
+		let yesterday: i64 = evaluate("timestamp -1 d").unwrap().parse().unwrap();
+		assert!((timestamp - yesterday - 86_400).abs() <= 1);
+
+		let iso = evaluate("isoTimestamp").unwrap();
+		assert!(DateTime::parse_from_rfc3339(&iso).is_ok());
+		assert!(iso.ends_with('Z'));
+		let next_year = evaluate("isoTimestamp 1 y").unwrap();
+		assert!(next_year > iso);
+
+		for _ in 0..100 {
+			let value: i64 = evaluate("randomInt 1 4").unwrap().parse().unwrap();
+			assert!((1..4).contains(&value));
+		}
+	}
+
+	#[test]
+	fn test_encodings() {
+		assert_eq!(evaluate("base64 user:pass word").unwrap(), "dXNlcjpwYXNzIHdvcmQ=");
+		assert_eq!(evaluate("sha256 abc").unwrap(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+		assert_eq!(evaluate("sha256").unwrap(), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+	}
+
+	#[test]
+	fn test_invalid_references() {
+		assert_eq!(evaluate("unknown"), None);
+		assert_eq!(evaluate("uuid 4"), None);
+		assert_eq!(evaluate("randomInt 5"), None);
+		assert_eq!(evaluate("randomInt 5 5"), None);
+		assert_eq!(evaluate("randomInt a b"), None);
+		assert_eq!(evaluate("timestamp 3 fortnights"), None);
+	}
+}
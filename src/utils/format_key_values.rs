@@ -1,16 +1,16 @@
 use std::collections::HashMap;
 
 pub fn format_key_values(map: &HashMap<String, String>) -> String {
-	if map.is_empty() {
-		return String::new();
-	}
-
-	let max_key_len = map.keys().map(String::len).max().unwrap_or(0);
+	let mut sorted_pairs: Vec<_> = map.iter().map(|(key, value)| (key.clone(), value.clone())).collect();
+	sorted_pairs.sort();
+	format_ordered_key_values(&sorted_pairs)
+}
 
-	let mut sorted_pairs: Vec<_> = map.iter().collect();
-	sorted_pairs.sort_by(|a, b| a.0.cmp(b.0));
+/// Like [`format_key_values`], keeping the order and any repeated keys.
+pub fn format_ordered_key_values(pairs: &[(String, String)]) -> String {
+	let max_key_len = pairs.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
 
-	sorted_pairs.iter().map(|(key, value)| format!("{key:<max_key_len$} : {value}")).collect::<Vec<_>>().join("\n")
+	pairs.iter().map(|(key, value)| format!("{key:<max_key_len$} : {value}")).collect::<Vec<_>>().join("\n")
 }
 
 #[cfg(test)]
@@ -80,6 +80,16 @@ mod tests {
 		assert!(result.contains("Normal-Header : value"));
 	}
 
+	#[test]
+	fn test_ordered_pairs_keep_repeated_keys() {
+		let pairs = vec![
+			(String::from("tag"), String::from("b")),
+			(String::from("page"), String::from("2")),
+			(String::from("tag"), String::from("a")),
+		];
+		assert_eq!(format_ordered_key_values(&pairs), "tag  : b\npage : 2\ntag  : a");
+	}
+
 	#[test]
 	fn test_keys_with_special_characters() {
 		let mut map = HashMap::new();
@@ -0,0 +1,46 @@
+use std::fmt::Write;
+
+const BYTES_PER_LINE: usize = 16;
+
+/// Classic `hexdump -C` layout: offset, two groups of eight hex bytes and the printable ASCII.
+pub fn hex_dump(bytes: &[u8]) -> String {
+	let mut dump = String::new();
+
+	for (index, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+		let _ = write!(dump, "{:08x} ", index * BYTES_PER_LINE);
+
+		for position in 0..BYTES_PER_LINE {
+			if position % 8 == 0 {
+				dump.push(' ');
+			}
+			match line.get(position) {
+				Some(byte) => {
+					let _ = write!(dump, "{byte:02x} ");
+				},
+				None => dump.push_str("   "),
+			}
+		}
+
+		let ascii: String =
+			line.iter().map(|byte| if byte.is_ascii_graphic() || *byte == b' ' { char::from(*byte) } else { '.' }).collect();
+		let _ = writeln!(dump, " |{ascii}|");
+	}
+
+	dump.pop();
+	dump
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_hex_dump() {
+		assert_eq!(hex_dump(&[]), "");
+		assert_eq!(
+			hex_dump(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\x01\x02"),
+			"00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|\n\
+			 00000010  01 02                                             |..|"
+		);
+	}
+}
@@ -1 +1,5 @@
+pub mod compression;
+pub mod dynamic_variables;
 pub mod format_key_values;
+pub mod hex_dump;
+pub mod variables;
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use crate::utils::dynamic_variables;
+
+/// Replaces `{{name}}` references with values from `variables` and `{{$function arguments}}` ones with generated
+/// values, leaving unknown references untouched. References nest, so `{{$base64 {{user}}:{{password}}}}` works.
+pub fn substitute_variables(text: &str, variables: &HashMap<String, String>) -> String {
+	substitute(text, variables, true)
+}
+
+/// Like [`substitute_variables`], but leaves `{{$function}}` references to be generated when the request is sent.
+pub fn substitute_static_variables(text: &str, variables: &HashMap<String, String>) -> String {
+	substitute(text, variables, false)
+}
+
+/// Resolves values that refer to other variables, e.g. `@base = https://{{host}}/v1`; `{{$function}}` references
+/// are left to be generated on every send. Cycles stop after a few rounds with the references in place.
+pub fn resolve_references(variables: &HashMap<String, String>) -> HashMap<String, String> {
+	const MAX_DEPTH: usize = 8;
+
+	let mut resolved = variables.clone();
+	for _ in 0..MAX_DEPTH {
+		let next: HashMap<String, String> =
+			resolved.iter().map(|(name, value)| (name.clone(), substitute_static_variables(value, &resolved))).collect();
+		if next == resolved {
+			break;
+		}
+		resolved = next;
+	}
+	resolved
+}
+
+fn substitute(text: &str, variables: &HashMap<String, String>, dynamic: bool) -> String {
+	let mut result = String::with_capacity(text.len());
+	// Where the `{{` of each reference still open starts in `result`
+	let mut openings = Vec::new();
+	let mut rest = text;
+
+	loop {
+		match (rest.find("{{"), rest.find("}}")) {
+			(Some(open), Some(close)) if open < close => {
+				result.push_str(&rest[..open]);
+				openings.push(result.len());
+				result.push_str("{{");
+				rest = &rest[open + 2..];
+			},
+			(_, Some(close)) => {
+				result.push_str(&rest[..close]);
+				rest = &rest[close + 2..];
+
+				let value = openings.pop().and_then(|start| {
+					let name = result[start + 2..].trim();
+					let value = match name.strip_prefix('$') {
+						Some(expression) if dynamic => dynamic_variables::evaluate(expression),
+						Some(_) => None,
+						None => variables.get(name).cloned(),
+					};
+					value.map(|value| (start, value))
+				});
+				match value {
+					Some((start, value)) => {
+						result.truncate(start);
+						result.push_str(&value);
+					},
+					None => result.push_str("}}"),
+				}
+			},
+			_ => {
+				result.push_str(rest);
+				return result;
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_known_variables_are_replaced() {
+		let variables = HashMap::from([
+			(String::from("host"), String::from("api.example.com")),
+			(String::from("token"), String::from("secret")),
+		]);
+
+		let result = substitute_variables("https://{{host}}/users?token={{ token }}", &variables);
+
+		assert_eq!(result, "https://api.example.com/users?token=secret");
+	}
+
+	#[test]
+	fn test_unknown_and_unclosed_references_are_kept() {
+		let variables = HashMap::from([(String::from("host"), String::from("localhost"))]);
+
+		assert_eq!(substitute_variables("{{missing}}/{{host}}", &variables), "{{missing}}/localhost");
+		assert_eq!(substitute_variables("{{host}} {{oops", &variables), "localhost {{oops");
+		assert_eq!(substitute_variables(r#"{"a": {"b": {{host}}}}"#, &variables), r#"{"a": {"b": localhost}}"#);
+	}
+
+	#[test]
+	fn test_variables_referring_to_variables() {
+		let variables = HashMap::from([
+			(String::from("base"), String::from("https://{{host}}/{{version}}")),
+			(String::from("host"), String::from("{{domain}}")),
+			(String::from("domain"), String::from("api.example.com")),
+			(String::from("version"), String::from("v1")),
+			(String::from("id"), String::from("{{$uuid}}")),
+			(String::from("loop"), String::from("{{loop}}!")),
+		]);
+
+		let resolved = resolve_references(&variables);
+		assert_eq!(resolved["base"], "https://api.example.com/v1");
+		assert_eq!(resolved["id"], "{{$uuid}}");
+		assert!(resolved["loop"].starts_with("{{loop}}!"));
+	}
+
+	#[test]
+	fn test_dynamic_references_nest() {
+		let variables =
+			HashMap::from([(String::from("user"), String::from("joe")), (String::from("password"), String::from("secret"))]);
+
+		assert_eq!(substitute_variables("Basic {{$base64 {{user}}:{{password}}}}", &variables), "Basic am9lOnNlY3JldA==");
+		assert_eq!(substitute_variables("{{ $unknown {{user}} }}", &variables), "{{ $unknown joe }}");
+		assert_eq!(substitute_variables("{{$uuid}}", &variables).len(), 36);
+
+		assert_eq!(substitute_static_variables("{{$base64 {{user}}:{{password}}}}", &variables), "{{$base64 joe:secret}}");
+	}
+}
@@ -9,6 +9,8 @@ use tui_textarea::{CursorMove, Input, Key, Scrolling, TextArea};
 
 use arboard::Clipboard;
 
+use crate::theme::{ModeColors, Theme};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
 	Normal,
@@ -18,27 +20,25 @@ pub enum Mode {
 }
 
 impl Mode {
-	pub fn block(self) -> Block<'static> {
-		Block::default().borders(Borders::ALL).border_style(Style::default().fg(self.border_color()))
+	pub fn block(self, theme: &Theme) -> Block<'static> {
+		Block::default().borders(Borders::ALL).border_style(Style::default().fg(self.border_color(theme)))
 	}
 
-	pub const fn border_color(self) -> Color {
+	const fn colors(self, theme: &Theme) -> &ModeColors {
 		match self {
-			Self::Normal => Color::Blue,
-			Self::Insert => Color::Yellow,
-			Self::Visual => Color::Cyan,
-			Self::Operator(_) => Color::Green,
+			Self::Normal => &theme.vim.normal,
+			Self::Insert => &theme.vim.insert,
+			Self::Visual => &theme.vim.visual,
+			Self::Operator(_) => &theme.vim.operator,
 		}
 	}
 
-	pub fn cursor_style(self) -> Style {
-		let color = match self {
-			Self::Normal => Color::Reset,
-			Self::Insert => Color::LightBlue,
-			Self::Visual => Color::LightYellow,
-			Self::Operator(_) => Color::LightGreen,
-		};
-		Style::default().fg(color).add_modifier(Modifier::REVERSED)
+	pub const fn border_color(self, theme: &Theme) -> Color {
+		self.colors(theme).border
+	}
+
+	pub fn cursor_style(self, theme: &Theme) -> Style {
+		Style::default().fg(self.colors(theme).cursor).add_modifier(Modifier::REVERSED)
 	}
 }
 
@@ -58,7 +58,6 @@ pub enum Transition {
 	Nop,
 	Mode(Mode),
 	Pending(Input),
-	Quit,
 }
 
 // State of Vim emulation
@@ -66,12 +65,12 @@ pub enum Transition {
 pub struct Vim {
 	pub mode: Mode,
 	pub pending: Input, // Pending input to handle a sequence with two keys like gg
-	pub clipboard: Rc<RefCell<Clipboard>>,
+	pub clipboard: Rc<RefCell<Option<Clipboard>>>, // None when no system clipboard is available
 }
 
 impl Vim {
 	pub fn new(mode: Mode) -> Self {
-		let clipboard = Rc::new(RefCell::new(Clipboard::new().expect("failed to init clipboard")));
+		let clipboard = Rc::new(RefCell::new(Clipboard::new().ok()));
 
 		Self { mode, pending: Input::default(), clipboard }
 	}
@@ -80,6 +79,12 @@ impl Vim {
 		Self { pending, mode: self.mode, clipboard: self.clipboard }
 	}
 
+	fn copy_to_clipboard(&self, text: String) {
+		if let Some(clipboard) = self.clipboard.borrow_mut().as_mut() {
+			let _ = clipboard.set_text(text);
+		}
+	}
+
 	pub fn transition(&self, input: Input, textarea: &mut TextArea<'_>) -> Transition {
 		if input.key == Key::Null {
 			return Transition::Nop;
@@ -200,7 +205,7 @@ impl Vim {
 					Input { key: Key::Char('y'), ctrl: false, .. } if self.mode == Mode::Visual => {
 						textarea.move_cursor(CursorMove::Forward);
 						textarea.copy();
-						self.clipboard.borrow_mut().set_text(textarea.yank_text()).unwrap();
+						self.copy_to_clipboard(textarea.yank_text());
 						return Transition::Mode(Mode::Normal);
 					},
 					Input { key: Key::Char('d'), ctrl: false, .. } if self.mode == Mode::Visual => {
@@ -220,7 +225,7 @@ impl Vim {
 				match self.mode {
 					Mode::Operator('y') => {
 						textarea.copy();
-						self.clipboard.borrow_mut().set_text(textarea.yank_text()).unwrap();
+						self.copy_to_clipboard(textarea.yank_text());
 						Transition::Mode(Mode::Normal)
 					},
 					Mode::Operator('d') => {
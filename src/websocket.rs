@@ -0,0 +1,393 @@
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use hyper::header::{HeaderName, HeaderValue};
+use std::fmt::Write;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+use crate::app::HttpVersion;
+use crate::http_client::{HttpClient, HttpClientError, TIMEOUT, request_url};
+use crate::request::HttpRequest;
+use crate::timing::Timings;
+
+/// Longest payload shown in the log; the rest is summarized by its size.
+const MAX_SHOWN_BYTES: usize = 4096;
+
+/// How the message editor's text is sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageKind {
+	#[default]
+	Text,
+	/// A text frame, checked to be valid JSON first
+	Json,
+	/// Hex bytes, e.g. `de ad be ef`
+	Binary,
+}
+
+impl MessageKind {
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Text => "text",
+			Self::Json => "JSON",
+			Self::Binary => "binary",
+		}
+	}
+
+	pub const fn next(self) -> Self {
+		match self {
+			Self::Text => Self::Json,
+			Self::Json => Self::Binary,
+			Self::Binary => Self::Text,
+		}
+	}
+
+	/// The frame to send for `text` typed in the message editor.
+	pub fn encode(self, text: &str) -> anyhow::Result<Message> {
+		Ok(match self {
+			Self::Text => Message::text(text),
+			Self::Json => {
+				serde_json::from_str::<serde_json::Value>(text)?;
+				Message::text(text)
+			},
+			Self::Binary => Message::binary(parse_hex(text)?),
+		})
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+	Sent,
+	Received,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+	Connected,
+	Text,
+	Binary,
+	Ping,
+	Pong,
+	Close,
+	Disconnected,
+	Error,
+}
+
+impl EntryKind {
+	const fn as_str(self) -> &'static str {
+		match self {
+			Self::Connected => "open",
+			Self::Text => "text",
+			Self::Binary => "binary",
+			Self::Ping => "ping",
+			Self::Pong => "pong",
+			Self::Close => "close",
+			Self::Disconnected => "closed",
+			Self::Error => "error",
+		}
+	}
+
+	/// Frames carrying data, as opposed to control frames and connection events.
+	pub const fn is_data(self) -> bool {
+		matches!(self, Self::Text | Self::Binary)
+	}
+}
+
+/// One line of the message log: a frame that went either way, or a change to the connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+	pub at: DateTime<Utc>,
+	pub direction: Direction,
+	pub kind: EntryKind,
+	pub payload: Vec<u8>,
+}
+
+impl LogEntry {
+	fn new(direction: Direction, kind: EntryKind, payload: impl Into<Vec<u8>>) -> Self {
+		Self { at: Utc::now(), direction, kind, payload: payload.into() }
+	}
+
+	fn from_message(direction: Direction, message: &Message) -> Option<Self> {
+		let (kind, payload) = match message {
+			Message::Text(text) => (EntryKind::Text, text.as_bytes().to_vec()),
+			Message::Binary(bytes) => (EntryKind::Binary, bytes.to_vec()),
+			Message::Ping(bytes) => (EntryKind::Ping, bytes.to_vec()),
+			Message::Pong(bytes) => (EntryKind::Pong, bytes.to_vec()),
+			Message::Close(frame) => (
+				EntryKind::Close,
+				frame.as_ref().map_or_else(Vec::new, |frame| {
+					format!("{} {}", u16::from(frame.code), frame.reason).trim_end().as_bytes().to_vec()
+				}),
+			),
+			Message::Frame(_) => return None,
+		};
+		Some(Self::new(direction, kind, payload))
+	}
+
+	pub const fn is_connected(&self) -> bool {
+		matches!(self.kind, EntryKind::Connected)
+	}
+
+	pub const fn ends_connection(&self) -> bool {
+		matches!(self.kind, EntryKind::Disconnected | EntryKind::Error)
+	}
+
+	/// e.g. `12:00:01.123 → text    {"type":"subscribe"}`; binary payloads are shown as hex.
+	pub fn formatted(&self) -> String {
+		let arrow = match (self.kind, self.direction) {
+			(EntryKind::Connected | EntryKind::Disconnected | EntryKind::Error, _) => "•",
+			(_, Direction::Sent) => "→",
+			(_, Direction::Received) => "←",
+		};
+		format!("{} {arrow} {:<7} {}", self.at.format("%H:%M:%S%.3f"), self.kind.as_str(), self.formatted_payload())
+	}
+
+	fn formatted_payload(&self) -> String {
+		let shown = &self.payload[..self.payload.len().min(MAX_SHOWN_BYTES)];
+		let mut payload = match self.kind {
+			EntryKind::Binary | EntryKind::Ping | EntryKind::Pong => {
+				shown.iter().map(|byte| format!("{byte:02x}")).collect::<Vec<_>>().join(" ")
+			},
+			_ => String::from_utf8_lossy(shown).into_owned(),
+		};
+		if shown.len() < self.payload.len() {
+			let _ = write!(payload, " … ({} bytes)", self.payload.len());
+		}
+		payload
+	}
+}
+
+/// An open (or opening) connection; frames are queued to the task that owns the socket.
+/// Dropping it closes the socket in the background.
+#[derive(Debug)]
+pub struct WebSocketConnection {
+	pub url: String,
+	/// Set once the upgrade succeeded
+	pub connected: bool,
+	outgoing: mpsc::UnboundedSender<Message>,
+	log: mpsc::UnboundedReceiver<LogEntry>,
+}
+
+impl WebSocketConnection {
+	/// Connects in the background; the upgrade, every frame and the close are reported through [`Self::try_recv`].
+	pub fn open(http_client: HttpClient, request: HttpRequest) -> Self {
+		let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+		let (log_tx, log) = mpsc::unbounded_channel();
+		let url = request.url.clone();
+
+		tokio::spawn(async move {
+			let entry = match run(&http_client, &request, outgoing_rx, &log_tx).await {
+				Ok(()) => LogEntry::new(Direction::Received, EntryKind::Disconnected, Vec::new()),
+				Err(error) => LogEntry::new(Direction::Received, EntryKind::Error, error.to_string()),
+			};
+			let _ = log_tx.send(entry);
+		});
+
+		Self { url, connected: false, outgoing, log }
+	}
+
+	/// The next log entry, if one arrived.
+	pub fn try_recv(&mut self) -> Option<LogEntry> {
+		let entry = self.log.try_recv().ok()?;
+		if entry.is_connected() {
+			self.connected = true;
+		}
+		Some(entry)
+	}
+
+	pub fn send(&self, message: Message) -> anyhow::Result<()> {
+		self.outgoing.send(message).map_err(|_| anyhow::anyhow!("The connection is closed"))
+	}
+
+	/// Starts the closing handshake with a normal (1000) close code.
+	pub fn close(&self) -> anyhow::Result<()> {
+		self.send(Message::Close(Some(CloseFrame { code: CloseCode::Normal, reason: "".into() })))
+	}
+}
+
+/// Upgrades with the request's headers, then pumps frames both ways until either side closes.
+async fn run(
+	http_client: &HttpClient,
+	request: &HttpRequest,
+	mut outgoing: mpsc::UnboundedReceiver<Message>,
+	log: &mpsc::UnboundedSender<LogEntry>,
+) -> anyhow::Result<()> {
+	let url = request_url(request)?;
+	let secure = match url.scheme() {
+		"wss" => true,
+		"ws" => false,
+		other => return Err(HttpClientError::UnsupportedScheme(other.to_string()).into()),
+	};
+
+	let mut handshake = url.as_str().into_client_request()?;
+	for (key, value) in &request.headers {
+		let name = HeaderName::from_bytes(key.as_bytes()).map_err(|_| HttpClientError::InvalidHeader(key.clone()))?;
+		let value = HeaderValue::from_str(value).map_err(|_| HttpClientError::InvalidHeader(key.clone()))?;
+		handshake.headers_mut().insert(name, value);
+	}
+
+	let started = Instant::now();
+	let upgrade = async {
+		let (connection, remote_address, _) =
+			http_client.open(&url, secure, HttpVersion::Http1, &mut Timings::default()).await?;
+		let (socket, response) = tokio_tungstenite::client_async(handshake, connection).await?;
+		anyhow::Ok((socket, remote_address, response.status()))
+	};
+	let (socket, remote_address, status) =
+		tokio::time::timeout(TIMEOUT, upgrade).await.map_err(|_| HttpClientError::Timeout)??;
+
+	let _ = log.send(LogEntry::new(
+		Direction::Received,
+		EntryKind::Connected,
+		format!("{url} ({remote_address}, {status}, {}ms)", started.elapsed().as_millis()),
+	));
+
+	let (mut sink, mut stream) = socket.split();
+	loop {
+		tokio::select! {
+			message = outgoing.recv() => {
+				// The app dropped the connection, e.g. to open another one
+				let message = message.unwrap_or(Message::Close(None));
+				let entry = LogEntry::from_message(Direction::Sent, &message);
+				sink.send(message).await?;
+				if let Some(entry) = entry {
+					let _ = log.send(entry);
+				}
+			},
+			message = stream.next() => {
+				let Some(message) = message.transpose()? else {
+					return Ok(());
+				};
+				if let Some(entry) = LogEntry::from_message(Direction::Received, &message) {
+					let _ = log.send(entry);
+				}
+			},
+		}
+	}
+}
+
+/// Bytes from hex pairs, ignoring whitespace and an optional `0x` prefix on each group.
+fn parse_hex(text: &str) -> anyhow::Result<Vec<u8>> {
+	let digits: String =
+		text.split_whitespace().map(|group| group.strip_prefix("0x").unwrap_or(group)).collect::<Vec<_>>().concat();
+	if !digits.len().is_multiple_of(2) {
+		anyhow::bail!("Binary messages need an even number of hex digits");
+	}
+
+	(0..digits.len())
+		.step_by(2)
+		.map(|index| {
+			u8::from_str_radix(&digits[index..index + 2], 16)
+				.map_err(|_| anyhow::anyhow!("Invalid hex byte: {}", &digits[index..index + 2]))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use tokio::net::TcpListener;
+
+	use super::*;
+
+	/// Echoes data frames back after greeting with a ping, and hands back the upgrade request's headers.
+	async fn serve_echo() -> (String, tokio::task::JoinHandle<Vec<(String, String)>>) {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let address = listener.local_addr().unwrap();
+
+		let server = tokio::spawn(async move {
+			let (stream, _) = listener.accept().await.unwrap();
+			let mut headers = Vec::new();
+			#[allow(clippy::result_large_err)]
+			let callback = |request: &tokio_tungstenite::tungstenite::handshake::server::Request, response| {
+				headers = request
+					.headers()
+					.iter()
+					.map(|(key, value)| (key.to_string(), value.to_str().unwrap_or_default().to_string()))
+					.collect();
+				Ok(response)
+			};
+			let mut socket = tokio_tungstenite::accept_hdr_async(stream, callback).await.unwrap();
+
+			socket.send(Message::Ping("hi".into())).await.unwrap();
+			while let Some(Ok(message)) = socket.next().await {
+				if message.is_text() || message.is_binary() {
+					socket.send(message).await.unwrap();
+				}
+			}
+			headers
+		});
+
+		(format!("ws://{address}"), server)
+	}
+
+	async fn next_entry(connection: &mut WebSocketConnection, kind: EntryKind) -> LogEntry {
+		loop {
+			let entry = tokio::time::timeout(TIMEOUT, connection.log.recv()).await.unwrap().unwrap();
+			if entry.kind == kind {
+				return entry;
+			}
+		}
+	}
+
+	#[tokio::test]
+	async fn test_echo_session() {
+		let (url, server) = serve_echo().await;
+
+		let mut request = HttpRequest::new();
+		request.url = format!("{url}/chat");
+		request.headers.insert(String::from("Authorization"), String::from("Bearer secret"));
+		let mut connection = WebSocketConnection::open(HttpClient::new(), request);
+
+		assert!(next_entry(&mut connection, EntryKind::Connected).await.formatted().contains(&format!("{url}/chat")));
+		assert_eq!(next_entry(&mut connection, EntryKind::Ping).await.payload, b"hi");
+
+		connection.send(MessageKind::Json.encode(r#"{"type":"subscribe"}"#).unwrap()).unwrap();
+		let sent = next_entry(&mut connection, EntryKind::Text).await;
+		assert_eq!(sent.direction, Direction::Sent);
+		let echoed = next_entry(&mut connection, EntryKind::Text).await;
+		assert_eq!(echoed.direction, Direction::Received);
+		assert_eq!(echoed.payload, br#"{"type":"subscribe"}"#);
+
+		connection.send(MessageKind::Binary.encode("de ad 0xbeef").unwrap()).unwrap();
+		next_entry(&mut connection, EntryKind::Binary).await;
+		let echoed = next_entry(&mut connection, EntryKind::Binary).await;
+		assert!(echoed.formatted().ends_with("← binary  de ad be ef"));
+
+		connection.close().unwrap();
+		assert_eq!(next_entry(&mut connection, EntryKind::Close).await.payload, b"1000");
+		next_entry(&mut connection, EntryKind::Disconnected).await;
+
+		let headers = server.await.unwrap();
+		assert!(headers.contains(&(String::from("authorization"), String::from("Bearer secret"))));
+		assert!(headers.iter().any(|(key, _)| key == "sec-websocket-key"));
+	}
+
+	#[tokio::test]
+	async fn test_failed_upgrade_is_logged() {
+		let mut request = HttpRequest::new();
+		request.url = String::from("http://127.0.0.1:1");
+		let mut connection = WebSocketConnection::open(HttpClient::new(), request);
+
+		let entry = next_entry(&mut connection, EntryKind::Error).await;
+		assert!(entry.ends_connection());
+		assert!(entry.formatted().contains("Unsupported URL scheme: http"));
+	}
+
+	#[test]
+	fn test_encode_messages() {
+		assert_eq!(MessageKind::Text.encode("not json").unwrap(), Message::text("not json"));
+		assert!(MessageKind::Json.encode("not json").is_err());
+		assert_eq!(MessageKind::Binary.encode("0x01 02ff").unwrap(), Message::binary(vec![0x01, 0x02, 0xff]));
+		assert!(MessageKind::Binary.encode("abc").is_err());
+		assert!(MessageKind::Binary.encode("zz").is_err());
+	}
+
+	#[test]
+	fn test_long_payloads_are_truncated() {
+		let entry = LogEntry::new(Direction::Received, EntryKind::Text, "a".repeat(MAX_SHOWN_BYTES + 1));
+		assert!(entry.formatted().ends_with(&format!("a … ({} bytes)", MAX_SHOWN_BYTES + 1)));
+	}
+}
@@ -0,0 +1,204 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+use crate::secrets::SECRETS_FILE;
+
+pub const WORKSPACE_FILE: &str = "resto.toml";
+
+#[derive(Debug)]
+pub enum WorkspaceError {
+	InvalidWorkspaceFile(PathBuf, String),
+	InvalidEnvironment(PathBuf, String),
+}
+
+impl std::fmt::Display for WorkspaceError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::InvalidWorkspaceFile(path, message) => write!(f, "Invalid workspace file {}: {message}", path.display()),
+			Self::InvalidEnvironment(path, message) => write!(f, "Invalid environment {}: {message}", path.display()),
+		}
+	}
+}
+
+impl std::error::Error for WorkspaceError {}
+
+/// Contents of a `resto.toml` workspace file. Paths are relative to the workspace root.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct WorkspaceFile {
+	name: Option<String>,
+	requests: Option<PathBuf>,
+	environments: Option<PathBuf>,
+	default_environment: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Environment {
+	pub name: String,
+	pub variables: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+pub struct Workspace {
+	pub root: PathBuf,
+	pub name: String,
+	pub is_global: bool,
+	file: WorkspaceFile,
+}
+
+impl Workspace {
+	/// Uses the nearest `resto.toml` at or above `start`, falling back to the global workspace in the data directory.
+	pub fn discover(start: &Path) -> anyhow::Result<Self> {
+		find_workspace_root(start).map_or_else(Self::global, |root| Self::open(&root))
+	}
+
+	pub fn open(root: &Path) -> anyhow::Result<Self> {
+		let path = root.join(WORKSPACE_FILE);
+		let file: WorkspaceFile = if path.is_file() {
+			toml::from_str(&std::fs::read_to_string(&path)?)
+				.map_err(|error| WorkspaceError::InvalidWorkspaceFile(path, error.to_string()))?
+		} else {
+			WorkspaceFile::default()
+		};
+
+		let name = file.name.clone().unwrap_or_else(|| {
+			root.file_name().map_or_else(|| root.display().to_string(), |name| name.to_string_lossy().into_owned())
+		});
+
+		Ok(Self { root: root.to_path_buf(), name, is_global: false, file })
+	}
+
+	pub fn global() -> anyhow::Result<Self> {
+		let mut workspace = Self::open(&paths::data_dir())?;
+		workspace.name = String::from("global");
+		workspace.is_global = true;
+		Ok(workspace)
+	}
+
+	pub fn requests_dir(&self) -> PathBuf {
+		self.root.join(self.file.requests.as_deref().unwrap_or_else(|| Path::new("requests")))
+	}
+
+	pub fn environments_dir(&self) -> PathBuf {
+		self.root.join(self.file.environments.as_deref().unwrap_or_else(|| Path::new("environments")))
+	}
+
+	/// Encrypted secret variables, next to `resto.toml`.
+	pub fn secrets_path(&self) -> PathBuf {
+		self.root.join(SECRETS_FILE)
+	}
+
+	pub fn default_environment(&self) -> Option<&str> {
+		self.file.default_environment.as_deref()
+	}
+
+	/// Loads `<environments dir>/<name>.toml` files, each a flat table of variables, ordered by name.
+	pub fn load_environments(&self) -> anyhow::Result<Vec<Environment>> {
+		let directory = self.environments_dir();
+		if !directory.is_dir() {
+			return Ok(Vec::new());
+		}
+
+		let mut paths: Vec<PathBuf> = std::fs::read_dir(&directory)?
+			.filter_map(Result::ok)
+			.map(|entry| entry.path())
+			.filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+			.collect();
+		paths.sort();
+
+		paths
+			.into_iter()
+			.map(|path| {
+				let content = std::fs::read_to_string(&path)?;
+				let variables: HashMap<String, String> = toml::from_str(&content)
+					.map_err(|error| WorkspaceError::InvalidEnvironment(path.clone(), error.to_string()))?;
+				let name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+				Ok(Environment { name, variables })
+			})
+			.collect()
+	}
+}
+
+pub fn find_workspace_root(start: &Path) -> Option<PathBuf> {
+	start.ancestors().find(|directory| directory.join(WORKSPACE_FILE).is_file()).map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn temp_workspace() -> PathBuf {
+		let root = std::env::temp_dir().join(format!("resto-workspace-{}", uuid::Uuid::new_v4()));
+		std::fs::create_dir_all(root.join("services/api")).unwrap();
+		root
+	}
+
+	#[test]
+	fn test_discovers_workspace_from_nested_directory() {
+		let root = temp_workspace();
+		std::fs::write(root.join(WORKSPACE_FILE), "name = \"shop\"\nrequests = \"http/requests\"\n").unwrap();
+
+		let workspace = Workspace::discover(&root.join("services/api")).unwrap();
+
+		assert_eq!(workspace.root, root);
+		assert_eq!(workspace.name, "shop");
+		assert!(!workspace.is_global);
+		assert_eq!(workspace.requests_dir(), root.join("http/requests"));
+		assert_eq!(workspace.environments_dir(), root.join("environments"));
+
+		std::fs::remove_dir_all(root).unwrap();
+	}
+
+	#[test]
+	fn test_no_workspace_file_found() {
+		let root = temp_workspace();
+
+		assert_eq!(find_workspace_root(&root.join("services/api")), None);
+
+		std::fs::remove_dir_all(root).unwrap();
+	}
+
+	#[test]
+	fn test_loads_environments_in_order() {
+		let root = temp_workspace();
+		std::fs::write(root.join(WORKSPACE_FILE), "default_environment = \"staging\"\n").unwrap();
+		std::fs::create_dir_all(root.join("environments")).unwrap();
+		std::fs::write(root.join("environments/staging.toml"), "host = \"staging.example.com\"\n").unwrap();
+		std::fs::write(root.join("environments/local.toml"), "host = \"localhost:8080\"\ntoken = \"dev\"\n").unwrap();
+
+		let workspace = Workspace::open(&root).unwrap();
+		let environments = workspace.load_environments().unwrap();
+
+		assert_eq!(workspace.default_environment(), Some("staging"));
+		assert_eq!(
+			environments,
+			vec![
+				Environment {
+					name: String::from("local"),
+					variables: HashMap::from([
+						(String::from("host"), String::from("localhost:8080")),
+						(String::from("token"), String::from("dev")),
+					]),
+				},
+				Environment {
+					name: String::from("staging"),
+					variables: HashMap::from([(String::from("host"), String::from("staging.example.com"))]),
+				},
+			]
+		);
+
+		std::fs::remove_dir_all(root).unwrap();
+	}
+
+	#[test]
+	fn test_invalid_workspace_file() {
+		let root = temp_workspace();
+		std::fs::write(root.join(WORKSPACE_FILE), "unknown = true\n").unwrap();
+
+		assert!(Workspace::open(&root).is_err());
+
+		std::fs::remove_dir_all(root).unwrap();
+	}
+}